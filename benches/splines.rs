@@ -0,0 +1,195 @@
+#![allow(clippy::disallowed_methods)] // The wrappers in src/math.rs are private
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use asdfspline::adapters::ConstantSpeedAdapter;
+use asdfspline::quaternion::{angles2quat, CubicDeCasteljau, UnitQuaternion, Vec3};
+use asdfspline::utilities::{RootFinder, SolverOptions};
+use asdfspline::{
+    AsdfPosSpline, AsdfRotSpline, MonotoneCubicSpline, NormWrapper, PiecewiseCubicCurve, Spline,
+};
+
+struct Norm3;
+
+impl NormWrapper<Norm3> for Vec3 {
+    fn norm(&self) -> f32 {
+        self.norm()
+    }
+}
+
+const KEYFRAMES: usize = 50;
+const SAMPLES: usize = 1000;
+
+fn positions() -> Vec<Vec3> {
+    (0..KEYFRAMES)
+        .map(|i| {
+            let x = i as f32;
+            Vec3::new(x.sin() * 3.0, x.cos() * 2.0, (x * 0.3).sin())
+        })
+        .collect()
+}
+
+fn quaternions() -> Vec<UnitQuaternion> {
+    (0..KEYFRAMES)
+        .map(|i| {
+            let x = i as f32;
+            angles2quat(x * 37.0, (x * 0.7).sin() * 60.0, (x * 0.3).cos() * 20.0)
+        })
+        .collect()
+}
+
+fn tcb() -> Vec<[f32; 3]> {
+    vec![[0.0; 3]; KEYFRAMES - 2]
+}
+
+fn curve() -> PiecewiseCubicCurve<Vec3> {
+    PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions(), &tcb(), false, |v| v.norm())
+        .unwrap()
+}
+
+/// Evenly spaced parameter values covering the whole grid.
+fn times(grid: &[f32]) -> Vec<f32> {
+    let (start, end) = (grid[0], *grid.last().unwrap());
+    (0..SAMPLES)
+        .map(|i| start + (end - start) * i as f32 / (SAMPLES - 1) as f32)
+        .collect()
+}
+
+fn construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construction");
+    let (positions, quaternions, tcb) = (positions(), quaternions(), tcb());
+    group.bench_function("kochanek-bartels positions", |b| {
+        b.iter(|| {
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                black_box(&positions),
+                &tcb,
+                false,
+                |v| v.norm(),
+            )
+        })
+    });
+    group.bench_function("kochanek-bartels quaternions", |b| {
+        b.iter(|| {
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(
+                black_box(quaternions.clone()),
+                &tcb,
+                false,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn constant_speed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("constant speed");
+    group.bench_function("adapt", |b| {
+        b.iter_batched(
+            curve,
+            ConstantSpeedAdapter::<_, _, _, Norm3>::adapt,
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("adapt with table", |b| {
+        b.iter_batched(
+            curve,
+            |curve| ConstantSpeedAdapter::<_, _, _, Norm3>::adapt_with_table(curve, 16),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate");
+    let curve = curve();
+    let ts = times(curve.grid());
+    group.bench_function("piecewise cubic curve", |b| {
+        b.iter(|| {
+            ts.iter()
+                .map(|&t| curve.evaluate(black_box(t)))
+                .sum::<Vec3>()
+        })
+    });
+    let mut times_option = vec![None; KEYFRAMES];
+    times_option[0] = Some(0.0);
+    times_option[KEYFRAMES - 1] = Some(100.0);
+    let speeds = vec![None; KEYFRAMES];
+    let pos = AsdfPosSpline::<Vec3, Norm3>::new(positions(), &times_option, speeds, tcb(), false)
+        .unwrap();
+    let ts = times(pos.grid());
+    group.bench_function("asdf position spline", |b| {
+        b.iter(|| ts.iter().map(|&t| pos.evaluate(black_box(t))).sum::<Vec3>())
+    });
+    let rot = AsdfRotSpline::new(quaternions(), &times_option, tcb(), false).unwrap();
+    let ts = times(rot.grid());
+    group.bench_function("asdf rotation spline", |b| {
+        b.iter(|| {
+            ts.iter()
+                .map(|&t| rot.evaluate(black_box(t)).w)
+                .sum::<f32>()
+        })
+    });
+    group.finish();
+}
+
+fn s2t(c: &mut Criterion) {
+    let mut group = c.benchmark_group("s2t");
+    for (name, root_finder) in [
+        ("bisection", RootFinder::Bisection),
+        ("brent", RootFinder::Brent),
+        ("itp", RootFinder::Itp),
+        ("newton", RootFinder::Newton),
+    ] {
+        let adapter = ConstantSpeedAdapter::<_, _, _, Norm3>::adapt(curve())
+            .with_solver_options(SolverOptions {
+                root_finder,
+                ..SolverOptions::default()
+            })
+            .unwrap();
+        let ts = times(adapter.grid());
+        group.bench_with_input(BenchmarkId::new("root finder", name), &ts, |b, ts| {
+            b.iter(|| {
+                ts.iter()
+                    .map(|&s| adapter.evaluate(black_box(s)))
+                    .sum::<Vec3>()
+            })
+        });
+    }
+    let table = ConstantSpeedAdapter::<_, _, _, Norm3>::adapt_with_table(curve(), 16);
+    let ts = times(table.grid());
+    group.bench_function("lookup table", |b| {
+        b.iter(|| {
+            ts.iter()
+                .map(|&s| table.evaluate(black_box(s)))
+                .sum::<Vec3>()
+        })
+    });
+    group.finish();
+}
+
+fn get_time(c: &mut Criterion) {
+    let values: Vec<f32> = (0..KEYFRAMES).map(|i| (i * i) as f32).collect();
+    let grid: Vec<f32> = (0..KEYFRAMES).map(|i| i as f32).collect();
+    let spline = MonotoneCubicSpline::new(values, grid, false).unwrap();
+    let last = ((KEYFRAMES - 1) * (KEYFRAMES - 1)) as f32;
+    let values: Vec<f32> = (0..SAMPLES)
+        .map(|i| last * (i as f32 + 0.5) / SAMPLES as f32)
+        .collect();
+    c.bench_function("get_time", |b| {
+        b.iter(|| {
+            values
+                .iter()
+                .filter_map(|&v| spline.get_time(black_box(v)))
+                .sum::<f32>()
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    construction,
+    constant_speed,
+    evaluate,
+    s2t,
+    get_time
+);
+criterion_main!(benches);
@@ -0,0 +1,62 @@
+//! Output arrays which are allocated on the Rust side.
+
+use libc::size_t;
+
+use crate::{write_vector_output, AsdfLayout};
+
+/// Array of `float` values, allocated by the library.
+///
+/// It must be freed with `asdf_buffer_free()`.
+#[repr(C)]
+pub struct AsdfBuffer {
+    pub data: *mut f32,
+    /// Number of `float` values (not bytes).
+    pub len: size_t,
+}
+
+impl AsdfBuffer {
+    fn new(data: Vec<f32>) -> AsdfBuffer {
+        let data = data.into_boxed_slice();
+        let len = data.len();
+        AsdfBuffer {
+            data: Box::into_raw(data).cast(),
+            len,
+        }
+    }
+}
+
+/// Frees an `AsdfBuffer`.
+///
+/// # Safety
+///
+/// The buffer must have been obtained from one of the `*_alloc()` functions.
+/// Each buffer can only be freed once.
+/// Passing a buffer with a NULL `data` pointer is allowed.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_buffer_free(buffer: AsdfBuffer) {
+    if buffer.data.is_null() {
+        return;
+    }
+    // SAFETY: The pointer and length have been obtained from a boxed slice.
+    drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)) });
+}
+
+/// Like `write_vector_output()`, but allocates the output.
+///
+/// # Safety
+///
+/// If not NULL, `inputs` must point to `count` values.
+pub(crate) unsafe fn allocate_output<const N: usize>(
+    inputs: *const f32,
+    count: usize,
+    layout: AsdfLayout,
+    f: impl Fn(f32) -> [f32; N],
+) -> AsdfBuffer {
+    let mut data = Vec::with_capacity(N * count);
+    unsafe {
+        write_vector_output(inputs, count, data.as_mut_ptr(), layout, f);
+        // SAFETY: All elements have been initialized.
+        data.set_len(N * count);
+    }
+    AsdfBuffer::new(data)
+}
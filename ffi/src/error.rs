@@ -0,0 +1,392 @@
+//! Error reporting for the C API.
+//!
+//! Functions which can fail return NULL (or `false`) and store the error,
+//! which can then be queried with `asdf_last_error*()`.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fmt::Display;
+
+use libc::{c_char, size_t};
+
+use asdfspline::asdfposspline::Error as AsdfPosSplineError;
+use asdfspline::asdfrotspline::Error as AsdfRotSplineError;
+use asdfspline::centripetalkochanekbartelsspline::Error as KochanekBartelsError;
+use asdfspline::cubichermitespline::Error as HermiteError;
+use asdfspline::monotonecubicspline::{MonotoneError, MonotoneWithSlopesError};
+use asdfspline::piecewisemonotonecubicspline::{
+    PiecewiseMonotoneError, PiecewiseMonotoneWithSlopesError,
+};
+use asdfspline::quaternion::cubicdecasteljau::Error as CubicDeCasteljauError;
+use asdfspline::utilities::GridError;
+
+/// Numeric error code, see `asdf_last_error_code()`.
+///
+/// New codes might be added in the future.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsdfErrorCode {
+    NoError = 0,
+    LessThanTwoValues = 1,
+    TimesVsPositions = 2,
+    SpeedsVsPositions = 3,
+    WaitsVsPositions = 4,
+    TcbVsPositions = 5,
+    GridVsValues = 6,
+    SlopesVsValues = 7,
+    TimeNan = 8,
+    TimesNotAscending = 9,
+    FirstTimeMissing = 10,
+    LastTimeMissing = 11,
+    WaitWithoutTime = 12,
+    InvalidWait = 13,
+    SpeedWithoutTime = 14,
+    DuplicatePositionWithoutTime = 15,
+    NonFiniteDistance = 16,
+    DistanceTooSmall = 17,
+    TooSteep = 18,
+    WrongSign = 19,
+    Decreasing = 20,
+    CyclicWithSlope = 21,
+    RepeatedQuaternion = 22,
+    InvalidQuaternion = 23,
+    TangentsVsSegments = 24,
+    ControlPolygonVsGrid = 25,
+    RotationTooLong = 26,
+}
+
+/// Code plus (optionally) the index and value which caused the error.
+pub(crate) struct ErrorDetails {
+    code: AsdfErrorCode,
+    index: Option<usize>,
+    value: Option<f32>,
+}
+
+impl ErrorDetails {
+    fn new(code: AsdfErrorCode) -> ErrorDetails {
+        ErrorDetails {
+            code,
+            index: None,
+            value: None,
+        }
+    }
+
+    fn with_index(code: AsdfErrorCode, index: usize) -> ErrorDetails {
+        ErrorDetails {
+            index: Some(index),
+            ..ErrorDetails::new(code)
+        }
+    }
+
+    fn with_value(code: AsdfErrorCode, index: usize, value: f32) -> ErrorDetails {
+        ErrorDetails {
+            value: Some(value),
+            ..ErrorDetails::with_index(code, index)
+        }
+    }
+}
+
+pub(crate) trait FfiError: Display {
+    fn details(&self) -> ErrorDetails;
+}
+
+/// Invalid input which is detected before calling into the Rust API.
+#[derive(Debug)]
+pub(crate) enum InputError {
+    InvalidQuaternion { index: usize },
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::InvalidQuaternion { index } => {
+                write!(f, "index {index}: quaternion must be finite and non-zero")
+            }
+        }
+    }
+}
+
+struct LastError {
+    message: CString,
+    details: ErrorDetails,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<LastError> = RefCell::new(LastError {
+        message: CString::new("no error").unwrap(),
+        details: ErrorDetails::new(AsdfErrorCode::NoError),
+    });
+}
+
+pub(crate) fn set_error<E: FfiError>(error: E) {
+    LAST_ERROR.with(|cell| {
+        *cell.borrow_mut() = LastError {
+            message: CString::new(error.to_string()).unwrap(),
+            details: error.details(),
+        };
+    });
+}
+
+/// The error message will be freed if another error occurs. It is the caller's
+/// responsibility to make sure they're no longer using the string before
+/// calling any other function which may fail.
+#[no_mangle]
+// NB: libc re-exports c_char from core, which is fine for older compilers, too
+#[allow(clippy::incompatible_msrv)]
+pub extern "C" fn asdf_last_error() -> *const c_char {
+    LAST_ERROR.with(|cell| cell.borrow().message.as_ptr())
+}
+
+/// Returns the code of the last error (in the current thread).
+#[no_mangle]
+pub extern "C" fn asdf_last_error_code() -> AsdfErrorCode {
+    LAST_ERROR.with(|cell| cell.borrow().details.code)
+}
+
+/// Provides the index (e.g. of a position or grid element) which caused the last error.
+///
+/// Returns `false` (and leaves `index` unchanged) if the error isn't related to an index.
+///
+/// # Safety
+///
+/// The pointer must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_last_error_index(index: *mut size_t) -> bool {
+    match LAST_ERROR.with(|cell| cell.borrow().details.index) {
+        Some(value) => {
+            unsafe { index.write(value) };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Provides the offending value (e.g. a speed or slope) of the last error.
+///
+/// Returns `false` (and leaves `value` unchanged) if the error isn't related to a value.
+///
+/// # Safety
+///
+/// The pointer must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_last_error_value(value: *mut f32) -> bool {
+    match LAST_ERROR.with(|cell| cell.borrow().details.value) {
+        Some(v) => {
+            unsafe { value.write(v) };
+            true
+        }
+        None => false,
+    }
+}
+
+impl FfiError for InputError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        match *self {
+            InputError::InvalidQuaternion { index } => {
+                ErrorDetails::with_index(InvalidQuaternion, index)
+            }
+        }
+    }
+}
+
+impl FfiError for GridError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        match *self {
+            GridError::GridNan { index } => ErrorDetails::with_index(TimeNan, index),
+            GridError::GridNotAscending { index } => {
+                ErrorDetails::with_index(TimesNotAscending, index)
+            }
+        }
+    }
+}
+
+impl FfiError for AsdfPosSplineError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use AsdfPosSplineError as E;
+        match *self {
+            E::LessThanTwoPositions => ErrorDetails::new(LessThanTwoValues),
+            E::TimesVsPositions { .. } => ErrorDetails::new(TimesVsPositions),
+            E::SpeedsVsPositions { .. } => ErrorDetails::new(SpeedsVsPositions),
+            E::WaitsVsPositions { .. } => ErrorDetails::new(WaitsVsPositions),
+            E::WaitWithoutTime { index } => ErrorDetails::with_index(WaitWithoutTime, index),
+            E::InvalidWait { index, wait } => ErrorDetails::with_value(InvalidWait, index, wait),
+            E::SpeedWithoutTime { index } => ErrorDetails::with_index(SpeedWithoutTime, index),
+            E::FirstTimeMissing => ErrorDetails::new(FirstTimeMissing),
+            E::LastTimeMissing => ErrorDetails::new(LastTimeMissing),
+            E::DuplicatePositionWithoutTime { index } => {
+                ErrorDetails::with_index(DuplicatePositionWithoutTime, index)
+            }
+            E::NonFiniteDistance { index } => ErrorDetails::with_index(NonFiniteDistance, index),
+            E::DistanceTooSmall { index } => ErrorDetails::with_index(DistanceTooSmall, index),
+            E::TcbVsPositions { .. } => ErrorDetails::new(TcbVsPositions),
+            E::TimeNan { index } => ErrorDetails::with_index(TimeNan, index),
+            E::TimesNotAscending { index } => ErrorDetails::with_index(TimesNotAscending, index),
+            E::TooFast { index, speed, .. } => ErrorDetails::with_value(TooSteep, index, speed),
+            E::NegativeSpeed { index, speed } => ErrorDetails::with_value(WrongSign, index, speed),
+        }
+    }
+}
+
+impl FfiError for AsdfRotSplineError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use AsdfRotSplineError as E;
+        match *self {
+            E::LessThanTwoQuaternions => ErrorDetails::new(LessThanTwoValues),
+            E::TimesVsQuaternions { .. } => ErrorDetails::new(TimesVsPositions),
+            E::FirstTimeMissing => ErrorDetails::new(FirstTimeMissing),
+            E::LastTimeMissing => ErrorDetails::new(LastTimeMissing),
+            E::DuplicateQuaternionWithoutTime { index } => {
+                ErrorDetails::with_index(DuplicatePositionWithoutTime, index)
+            }
+            E::TimeNan { index } => ErrorDetails::with_index(TimeNan, index),
+            E::TimesNotAscending { index } => ErrorDetails::with_index(TimesNotAscending, index),
+            E::TcbVsQuaternions { .. } => ErrorDetails::new(TcbVsPositions),
+            E::RepeatedQuaternion { index } => ErrorDetails::with_index(RepeatedQuaternion, index),
+            E::RotationTooSmall { index } => ErrorDetails::with_index(DistanceTooSmall, index),
+            E::TooLongRotation { index } => ErrorDetails::with_index(RotationTooLong, index),
+            E::SpeedsVsQuaternions { .. } => ErrorDetails::new(SpeedsVsPositions),
+            E::SpeedWithoutTime { index } => ErrorDetails::with_index(SpeedWithoutTime, index),
+            E::TooFast { index, speed, .. } => ErrorDetails::with_value(TooSteep, index, speed),
+            E::NegativeSpeed { index, speed } => ErrorDetails::with_value(WrongSign, index, speed),
+        }
+    }
+}
+
+impl FfiError for CubicDeCasteljauError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use CubicDeCasteljauError as E;
+        match self {
+            E::GridTooShort => ErrorDetails::new(LessThanTwoValues),
+            E::GridVsControlPolygon { .. } => ErrorDetails::new(ControlPolygonVsGrid),
+            E::FromGridError(e) => e.details(),
+        }
+    }
+}
+
+impl FfiError for KochanekBartelsError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use KochanekBartelsError as E;
+        match *self {
+            E::LessThanTwoPositions => ErrorDetails::new(LessThanTwoValues),
+            E::TcbVsPositions { .. } => ErrorDetails::new(TcbVsPositions),
+            E::NonFiniteDistance { index } => ErrorDetails::with_index(NonFiniteDistance, index),
+            E::DistanceTooSmall { index } => ErrorDetails::with_index(DistanceTooSmall, index),
+        }
+    }
+}
+
+impl FfiError for HermiteError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use HermiteError as E;
+        match self {
+            E::LessThanTwoPositions => ErrorDetails::new(LessThanTwoValues),
+            E::TangentsVsSegments { .. } => ErrorDetails::new(TangentsVsSegments),
+            E::GridVsPositions { .. } => ErrorDetails::new(GridVsValues),
+            E::FromGridError(e) => e.details(),
+        }
+    }
+}
+
+impl FfiError for PiecewiseMonotoneError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use PiecewiseMonotoneError as E;
+        match self {
+            E::LessThanTwoValues => ErrorDetails::new(LessThanTwoValues),
+            E::GridVsValues { .. } => ErrorDetails::new(GridVsValues),
+            E::FromGridError(e) => e.details(),
+        }
+    }
+}
+
+impl FfiError for PiecewiseMonotoneWithSlopesError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use PiecewiseMonotoneWithSlopesError as E;
+        match *self {
+            E::FromPiecewiseMonotoneError(ref e) => e.details(),
+            E::SlopesVsValues { .. } => ErrorDetails::new(SlopesVsValues),
+            E::SlopeTooSteep { index, slope, .. } => {
+                ErrorDetails::with_value(TooSteep, index, slope)
+            }
+            E::SlopeWrongSign { index, slope } => ErrorDetails::with_value(WrongSign, index, slope),
+        }
+    }
+}
+
+impl FfiError for MonotoneError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use MonotoneError as E;
+        match self {
+            E::Decreasing => ErrorDetails::new(Decreasing),
+            E::LessThanTwoValues => ErrorDetails::new(LessThanTwoValues),
+            E::GridVsValues { .. } => ErrorDetails::new(GridVsValues),
+            E::FromGridError(e) => e.details(),
+        }
+    }
+}
+
+impl FfiError for MonotoneWithSlopesError {
+    fn details(&self) -> ErrorDetails {
+        use AsdfErrorCode::*;
+        use MonotoneWithSlopesError as E;
+        match *self {
+            E::FromMonotoneError(ref e) => e.details(),
+            E::SlopesVsValues { .. } => ErrorDetails::new(SlopesVsValues),
+            E::SlopeTooSteep { index, slope, .. } => {
+                ErrorDetails::with_value(TooSteep, index, slope)
+            }
+            E::NegativeSlope { index, slope } => ErrorDetails::with_value(WrongSign, index, slope),
+            E::CyclicWithSlope { .. } => ErrorDetails::new(CyclicWithSlope),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::asdf_monotonecubic_with_slopes;
+
+    #[test]
+    fn slope_too_steep() {
+        let values = [0.0, 1.0, 2.0];
+        let slopes = [f32::NAN, 5.0, f32::NAN];
+        let grid = [0.0, 1.0, 2.0];
+        let curve = unsafe {
+            asdf_monotonecubic_with_slopes(
+                values.as_ptr(),
+                values.len(),
+                slopes.as_ptr(),
+                slopes.len(),
+                grid.as_ptr(),
+                grid.len(),
+                false,
+            )
+        };
+        assert!(curve.is_none());
+        assert_eq!(asdf_last_error_code(), AsdfErrorCode::TooSteep);
+        let mut index = 0;
+        assert!(unsafe { asdf_last_error_index(&mut index) });
+        assert_eq!(index, 1);
+        let mut value = 0.0;
+        assert!(unsafe { asdf_last_error_value(&mut value) });
+        assert_eq!(value, 5.0);
+    }
+
+    #[test]
+    fn no_error() {
+        assert_eq!(asdf_last_error_code(), AsdfErrorCode::NoError);
+        let mut index = 42;
+        assert!(!unsafe { asdf_last_error_index(&mut index) });
+        assert_eq!(index, 42);
+    }
+}
@@ -1,38 +1,38 @@
+//! C API for the `asdfspline` crate.
+//!
+//! Functions taking a `const` pointer to a spline don't modify it,
+//! they can be called concurrently on the same spline from multiple threads.
+
 #![deny(unsafe_op_in_unsafe_fn)]
 
-use std::cell::RefCell;
-use std::ffi::CString;
-use std::fmt::Display;
 use std::mem::MaybeUninit;
 
-use libc::{c_char, size_t};
-use nalgebra::{Vector2, Vector3};
-
-use asdfspline::{AsdfPosSpline, MonotoneCubicSpline, NormWrapper, PiecewiseCubicCurve, Spline};
+use libc::size_t;
+use nalgebra::{SVector, Vector2, Vector3};
 
-thread_local! {
-    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::new("no error").unwrap());
-}
+use asdfspline::quaternion::nalgebra::Quaternion;
+use asdfspline::quaternion::{CubicDeCasteljau, UnitQuaternion};
+use asdfspline::{
+    AsdfPosSpline, AsdfRotSpline, MonotoneCubicSpline, NormWrapper, PiecewiseCubicCurve, Spline,
+    SplineWithVelocity,
+};
 
-fn set_error<D: Display>(error: D) {
-    LAST_ERROR.with(|cell| {
-        *cell.borrow_mut() = CString::new(error.to_string()).unwrap();
-    });
-}
+mod buffer;
+mod error;
 
-/// The error message will be freed if another error occurs. It is the caller's
-/// responsibility to make sure they're no longer using the string before
-/// calling any other function which may fail.
-#[no_mangle]
-pub extern "C" fn asdf_last_error() -> *const c_char {
-    LAST_ERROR.with(|cell| cell.borrow().as_ptr())
-}
+use crate::buffer::allocate_output;
+pub use crate::buffer::{asdf_buffer_free, AsdfBuffer};
+pub use crate::error::{
+    asdf_last_error, asdf_last_error_code, asdf_last_error_index, asdf_last_error_value,
+    AsdfErrorCode,
+};
+use crate::error::{set_error, FfiError, InputError};
 
 trait ResultExt<T, E> {
     fn into_box(self) -> Option<Box<T>>;
 }
 
-impl<T, E: Display> ResultExt<T, E> for Result<T, E> {
+impl<T, E: FfiError> ResultExt<T, E> for Result<T, E> {
     fn into_box(self) -> Option<Box<T>> {
         self.map(Box::new).map_err(|e| set_error(e)).ok()
     }
@@ -55,6 +55,17 @@ pub type AsdfCubicCurve3 = PiecewiseCubicCurve<Vec3>;
 pub type AsdfCubicCurve2 = PiecewiseCubicCurve<Vec2>;
 pub type AsdfCubicCurve1 = PiecewiseCubicCurve<f32>;
 pub type AsdfMonotoneCubic = MonotoneCubicSpline;
+pub type AsdfCubicDeCasteljau = CubicDeCasteljau;
+
+/// Memory layout of multi-component output values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsdfLayout {
+    /// All components of an element are stored next to each other (e.g. xyzxyz...).
+    Interleaved,
+    /// Each component is stored contiguously for all elements (e.g. xx...yy...zz...).
+    Planar,
+}
 
 /// Create slice from pointer and length.
 ///
@@ -88,6 +99,189 @@ unsafe fn ffi_slice_mut<'a, T>(ptr: *mut T, len: usize) -> &'a mut [T] {
     }
 }
 
+// NB: The exported functions are spelled out (instead of being generated by macros)
+// because cbindgen doesn't see macro-generated functions.
+// They should only be thin wrappers around the following generic helpers.
+
+/// Calls `f` for each input value and writes the results to `output`.
+///
+/// # Safety
+///
+/// If not NULL, `inputs` must point to `count` values,
+/// `output` must provide space for `count` elements of type `O`.
+unsafe fn write_output<O>(
+    inputs: *const f32,
+    count: usize,
+    output: *mut f32,
+    f: impl Fn(f32) -> O,
+) {
+    let inputs = unsafe { ffi_slice(inputs, count) };
+    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<O>>(), count) };
+    for (input, out) in inputs.iter().zip(output) {
+        *out = MaybeUninit::new(f(*input));
+    }
+}
+
+/// Like [`write_output()`], but with the input values `start + i * step`.
+///
+/// # Safety
+///
+/// If not NULL, `output` must provide space for `count` elements of type `O`.
+unsafe fn write_range_output<O>(
+    start: f32,
+    step: f32,
+    count: usize,
+    output: *mut f32,
+    f: impl Fn(f32) -> O,
+) {
+    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<O>>(), count) };
+    for (i, out) in output.iter_mut().enumerate() {
+        *out = MaybeUninit::new(f(start + i as f32 * step));
+    }
+}
+
+/// Like [`write_output()`], but for vectors with the given `layout`.
+///
+/// # Safety
+///
+/// If not NULL, `inputs` must point to `count` values,
+/// `output` must provide space for `N * count` values.
+unsafe fn write_vector_output<const N: usize>(
+    inputs: *const f32,
+    count: usize,
+    output: *mut f32,
+    layout: AsdfLayout,
+    f: impl Fn(f32) -> [f32; N],
+) {
+    match layout {
+        AsdfLayout::Interleaved => unsafe { write_output(inputs, count, output, f) },
+        AsdfLayout::Planar => {
+            let inputs = unsafe { ffi_slice(inputs, count) };
+            unsafe { write_planar(output, inputs.iter().map(|&t| f(t))) }
+        }
+    }
+}
+
+/// Like [`write_range_output()`], but for vectors with the given `layout`.
+///
+/// # Safety
+///
+/// If not NULL, `output` must provide space for `N * count` values.
+unsafe fn write_range_vector_output<const N: usize>(
+    start: f32,
+    step: f32,
+    count: usize,
+    output: *mut f32,
+    layout: AsdfLayout,
+    f: impl Fn(f32) -> [f32; N],
+) {
+    match layout {
+        AsdfLayout::Interleaved => unsafe { write_range_output(start, step, count, output, f) },
+        AsdfLayout::Planar => unsafe {
+            write_planar(output, (0..count).map(|i| f(start + i as f32 * step)));
+        },
+    }
+}
+
+/// Writes all `values` with [`AsdfLayout::Planar`].
+///
+/// # Safety
+///
+/// If not NULL, `output` must provide space for `N * values.len()` values.
+unsafe fn write_planar<const N: usize>(
+    output: *mut f32,
+    values: impl ExactSizeIterator<Item = [f32; N]>,
+) {
+    let count = values.len();
+    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<f32>>(), N * count) };
+    for (i, value) in values.enumerate() {
+        for (j, &component) in value.iter().enumerate() {
+            output[j * count + i] = MaybeUninit::new(component);
+        }
+    }
+}
+
+/// Writes a pointer to the (`f32`-based) elements of `slice` to `output`.
+///
+/// Returns the number of elements.
+///
+/// # Safety
+///
+/// `output` must be valid, `T` must consist only of `f32` values.
+unsafe fn provide_slice<T>(slice: &[T], output: *mut *const f32) -> size_t {
+    unsafe { output.write(slice.as_ptr().cast()) };
+    slice.len()
+}
+
+/// Writes the first and last grid element to `start` and `end`.
+///
+/// # Safety
+///
+/// Both pointers must be valid.
+unsafe fn provide_domain(grid: &[f32], start: *mut f32, end: *mut f32) {
+    unsafe {
+        start.write(*grid.first().unwrap());
+        end.write(*grid.last().unwrap());
+    }
+}
+
+/// Creates vectors from `count` groups of `N` values.
+///
+/// # Safety
+///
+/// If ptr is not NULL, it must point to `N * count` values.
+unsafe fn ffi_vectors<const N: usize>(ptr: *const f32, count: usize) -> Vec<SVector<f32, N>> {
+    unsafe { ffi_slice(ptr.cast::<[f32; N]>(), count) }
+        .iter()
+        .map(|&coords| coords.into())
+        .collect()
+}
+
+/// NaN values mean "not given".
+///
+/// # Safety
+///
+/// If ptr is not NULL, it must point to `count` values.
+unsafe fn ffi_optional(ptr: *const f32, count: usize) -> Vec<Option<f32>> {
+    unsafe { ffi_slice(ptr, count) }
+        .iter()
+        .map(|&x| if x.is_nan() { None } else { Some(x) })
+        .collect()
+}
+
+/// Creates normalized quaternions from `count` groups of four values (x, y, z, w).
+///
+/// # Safety
+///
+/// If ptr is not NULL, it must point to `4 * count` values.
+unsafe fn ffi_quaternions(
+    ptr: *const f32,
+    count: usize,
+) -> Result<Vec<UnitQuaternion>, InputError> {
+    unsafe { ffi_slice(ptr.cast::<[f32; 4]>(), count) }
+        .iter()
+        .enumerate()
+        .map(|(index, &[x, y, z, w])| {
+            match UnitQuaternion::try_new(Quaternion::new(w, x, y, z), 0.0) {
+                Some(q) if q.coords.iter().all(|c| c.is_finite()) => Ok(q),
+                _ => Err(InputError::InvalidQuaternion { index }),
+            }
+        })
+        .collect()
+}
+
+/// 3x3 rotation matrix in row-major order.
+fn matrix3(q: UnitQuaternion) -> [[f32; 3]; 3] {
+    // NB: nalgebra matrices are column-major
+    q.to_rotation_matrix().into_inner().transpose().into()
+}
+
+/// 4x4 homogeneous transformation matrix in row-major order.
+fn matrix4(q: UnitQuaternion) -> [[f32; 4]; 4] {
+    // NB: nalgebra matrices are column-major
+    q.to_homogeneous().transpose().into()
+}
+
 /// Creates an `AsdfPosSpline3`.
 ///
 /// Each element in `positions` (3D coordinates) and `tcb`
@@ -110,18 +304,9 @@ pub unsafe extern "C" fn asdf_asdfposspline3(
     tcb_count: size_t,
     closed: bool,
 ) -> Option<Box<AsdfPosSpline3>> {
-    let positions: Vec<_> = unsafe { ffi_slice(positions.cast::<[f32; 3]>(), positions_count) }
-        .iter()
-        .map(|coords| Vec3::from_column_slice(coords))
-        .collect();
-    let times: Vec<_> = unsafe { ffi_slice(times, times_count) }
-        .iter()
-        .map(|&t| if t.is_nan() { None } else { Some(t) })
-        .collect();
-    let speeds: Vec<_> = unsafe { ffi_slice(speeds, speeds_count) }
-        .iter()
-        .map(|&t| if t.is_nan() { None } else { Some(t) })
-        .collect();
+    let positions = unsafe { ffi_vectors::<3>(positions, positions_count) };
+    let times = unsafe { ffi_optional(times, times_count) };
+    let speeds = unsafe { ffi_optional(speeds, speeds_count) };
     let tcb = unsafe { ffi_slice(tcb.cast::<[f32; 3]>(), tcb_count) };
     AsdfPosSpline3::new(positions, times, speeds, tcb, closed).into_box()
 }
@@ -146,15 +331,169 @@ pub unsafe extern "C" fn asdf_asdfposspline3_free(_: Option<Box<AsdfPosSpline3>>
 /// Pointers can be NULL, but in this case `count` must be 0.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_asdfposspline3_evaluate(
-    curve: &mut AsdfPosSpline3,
+    curve: &AsdfPosSpline3,
     times: *const f32,
     count: size_t,
     output: *mut f32,
 ) {
-    let times = unsafe { ffi_slice(times, count) };
-    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<[f32; 3]>>(), count) };
-    for (time, out) in times.iter().zip(output) {
-        *out = MaybeUninit::new(curve.evaluate(*time).into());
+    unsafe {
+        asdf_asdfposspline3_evaluate_layout(curve, times, count, output, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_asdfposspline3_evaluate()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_evaluate_layout(
+    curve: &AsdfPosSpline3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe { write_vector_output::<3>(times, count, output, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Like `asdf_asdfposspline3_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains *three* `float`s per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_evaluate_alloc(
+    curve: &AsdfPosSpline3,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe {
+        asdf_asdfposspline3_evaluate_alloc_layout(curve, times, count, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_asdfposspline3_evaluate_alloc()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_evaluate_alloc_layout(
+    curve: &AsdfPosSpline3,
+    times: *const f32,
+    count: size_t,
+    layout: AsdfLayout,
+) -> AsdfBuffer {
+    unsafe { allocate_output::<3>(times, count, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Returns velocity vector(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_evaluate_velocity(
+    curve: &AsdfPosSpline3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_asdfposspline3_evaluate_velocity_layout(
+            curve,
+            times,
+            count,
+            output,
+            AsdfLayout::Interleaved,
+        )
+    }
+}
+
+/// Like `asdf_asdfposspline3_evaluate_velocity()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_evaluate_velocity_layout(
+    curve: &AsdfPosSpline3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe {
+        write_vector_output::<3>(times, count, output, layout, |t| {
+            curve.evaluate_velocity(t).into()
+        })
+    }
+}
+
+/// Returns curve values at the `count` times `start + i * step`.
+///
+/// This is equivalent to `asdf_asdfposspline3_evaluate()` with uniformly spaced times.
+///
+/// # Safety
+///
+/// `output` must provide space for *three* `float`s per element.
+/// It can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_sample(
+    curve: &AsdfPosSpline3,
+    start: f32,
+    step: f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_asdfposspline3_sample_layout(
+            curve,
+            start,
+            step,
+            count,
+            output,
+            AsdfLayout::Interleaved,
+        )
+    }
+}
+
+/// Like `asdf_asdfposspline3_sample()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// `output` must provide space for *three* `float`s per element.
+/// It can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_sample_layout(
+    curve: &AsdfPosSpline3,
+    start: f32,
+    step: f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe {
+        write_range_vector_output::<3>(start, step, count, output, layout, |t| {
+            curve.evaluate(t).into()
+        })
     }
 }
 
@@ -165,12 +504,437 @@ pub unsafe extern "C" fn asdf_asdfposspline3_evaluate(
 /// All pointers must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_asdfposspline3_grid(
-    curve: &mut AsdfPosSpline3,
+    curve: &AsdfPosSpline3,
     output: *mut *const f32,
 ) -> size_t {
-    let grid = curve.grid();
-    unsafe { output.write(grid.as_ptr()) };
-    grid.len()
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfposspline3_domain(
+    curve: &AsdfPosSpline3,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// Returns the time of the first grid element.
+#[no_mangle]
+pub extern "C" fn asdf_asdfposspline3_start_time(curve: &AsdfPosSpline3) -> f32 {
+    curve.start_time()
+}
+
+/// Returns the time of the last grid element.
+#[no_mangle]
+pub extern "C" fn asdf_asdfposspline3_end_time(curve: &AsdfPosSpline3) -> f32 {
+    curve.end_time()
+}
+
+/// Returns the difference between end time and start time.
+#[no_mangle]
+pub extern "C" fn asdf_asdfposspline3_duration(curve: &AsdfPosSpline3) -> f32 {
+    curve.duration()
+}
+
+/// Creates an `AsdfRotSpline`.
+///
+/// Each element in `quaternions` contains *four* `float` values
+/// (in the order x, y, z, w), they are normalized.
+/// Each element in `tcb` (tension, continuity, bias) contains *three* `float` values,
+/// `times` and `speeds` (in degrees per second) contain one `float` per element.
+///
+/// # Safety
+///
+/// All input pointers must be valid for the corresponding `*_count` numbers
+/// of elements (not bytes).
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline(
+    quaternions: *const f32,
+    quaternions_count: size_t,
+    times: *const f32,
+    times_count: size_t,
+    speeds: *const f32,
+    speeds_count: size_t,
+    tcb: *const f32,
+    tcb_count: size_t,
+    closed: bool,
+) -> Option<Box<AsdfRotSpline>> {
+    let quaternions = match unsafe { ffi_quaternions(quaternions, quaternions_count) } {
+        Ok(quaternions) => quaternions,
+        Err(e) => {
+            set_error(e);
+            return None;
+        }
+    };
+    let times = unsafe { ffi_optional(times, times_count) };
+    let speeds = unsafe { ffi_optional(speeds, speeds_count) };
+    let tcb = unsafe { ffi_slice(tcb.cast::<[f32; 3]>(), tcb_count) };
+    AsdfRotSpline::with_speeds(quaternions, times, speeds, tcb, closed).into_box()
+}
+
+/// Frees an `AsdfRotSpline`
+///
+/// # Safety
+///
+/// The pointer must have been obtained with `asdf_asdfrotspline()`.
+/// Each pointer can only be freed once.
+/// Passing NULL is allowed.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_free(_: Option<Box<AsdfRotSpline>>) {}
+
+/// Returns quaternion(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *four* `float`s (x, y, z, w) per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_asdfrotspline_evaluate_layout(curve, times, count, output, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_asdfrotspline_evaluate()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *four* `float`s (x, y, z, w) per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate_layout(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe {
+        write_vector_output::<4>(times, count, output, layout, |t| {
+            curve.evaluate(t).coords.into()
+        })
+    }
+}
+
+/// Like `asdf_asdfrotspline_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains *four* `float`s (x, y, z, w) per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate_alloc(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe {
+        asdf_asdfrotspline_evaluate_alloc_layout(curve, times, count, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_asdfrotspline_evaluate_alloc()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate_alloc_layout(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+    layout: AsdfLayout,
+) -> AsdfBuffer {
+    unsafe { allocate_output::<4>(times, count, layout, |t| curve.evaluate(t).coords.into()) }
+}
+
+/// Returns rotation matrices at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *nine* `float`s per element
+/// (a 3x3 matrix in row-major order).
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate_matrix3(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe { write_output(times, count, output, |t| matrix3(curve.evaluate(t))) }
+}
+
+/// Returns homogeneous transformation matrices (without translation) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *sixteen* `float`s per element
+/// (a 4x4 matrix in row-major order).
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_evaluate_matrix4(
+    curve: &AsdfRotSpline,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe { write_output(times, count, output, |t| matrix4(curve.evaluate(t))) }
+}
+
+/// Provides a pointer to (and number of) grid elements.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_grid(
+    curve: &AsdfRotSpline,
+    output: *mut *const f32,
+) -> size_t {
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_asdfrotspline_domain(
+    curve: &AsdfRotSpline,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// Creates an `AsdfCubicDeCasteljau` (a rotation spline) from a control polygon.
+///
+/// Each element in `control_polygon` contains *four* `float` values
+/// (in the order x, y, z, w), they are normalized.
+/// There must be one control quaternion for each grid element,
+/// plus two between each pair of grid elements.
+///
+/// # Safety
+///
+/// All input pointers must be valid for the corresponding `*_count` numbers
+/// of elements (not bytes).
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau(
+    control_polygon: *const f32,
+    control_polygon_count: size_t,
+    grid: *const f32,
+    grid_count: size_t,
+) -> Option<Box<AsdfCubicDeCasteljau>> {
+    let control_polygon = match unsafe { ffi_quaternions(control_polygon, control_polygon_count) } {
+        Ok(quaternions) => quaternions,
+        Err(e) => {
+            set_error(e);
+            return None;
+        }
+    };
+    let grid = unsafe { ffi_slice(grid, grid_count) };
+    CubicDeCasteljau::new(control_polygon, grid).into_box()
+}
+
+/// Frees an `AsdfCubicDeCasteljau`
+///
+/// # Safety
+///
+/// The pointer must have been obtained with `asdf_cubicdecasteljau()`.
+/// Each pointer can only be freed once.
+/// Passing NULL is allowed.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_free(_: Option<Box<AsdfCubicDeCasteljau>>) {}
+
+/// Returns quaternion(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *four* `float`s (x, y, z, w) per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_cubicdecasteljau_evaluate_layout(curve, times, count, output, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_cubicdecasteljau_evaluate()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *four* `float`s (x, y, z, w) per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate_layout(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe {
+        write_vector_output::<4>(times, count, output, layout, |t| {
+            curve.evaluate(t).coords.into()
+        })
+    }
+}
+
+/// Like `asdf_cubicdecasteljau_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains *four* `float`s (x, y, z, w) per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate_alloc(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe {
+        asdf_cubicdecasteljau_evaluate_alloc_layout(curve, times, count, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_cubicdecasteljau_evaluate_alloc()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate_alloc_layout(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+    layout: AsdfLayout,
+) -> AsdfBuffer {
+    unsafe { allocate_output::<4>(times, count, layout, |t| curve.evaluate(t).coords.into()) }
+}
+
+/// Returns rotation matrices at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *nine* `float`s per element
+/// (a 3x3 matrix in row-major order).
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate_matrix3(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe { write_output(times, count, output, |t| matrix3(curve.evaluate(t))) }
+}
+
+/// Returns homogeneous transformation matrices (without translation) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *sixteen* `float`s per element
+/// (a 4x4 matrix in row-major order).
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_evaluate_matrix4(
+    curve: &AsdfCubicDeCasteljau,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe { write_output(times, count, output, |t| matrix4(curve.evaluate(t))) }
+}
+
+/// Provides a pointer to (and number of) grid elements.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_grid(
+    curve: &AsdfCubicDeCasteljau,
+    output: *mut *const f32,
+) -> size_t {
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubicdecasteljau_domain(
+    curve: &AsdfCubicDeCasteljau,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// KB-spline for any number of dimensions.
+///
+/// # Safety
+///
+/// Same as for `asdf_centripetalkochanekbartelsspline3()`.
+unsafe fn kochanek_bartels<const N: usize>(
+    positions: *const f32,
+    positions_count: size_t,
+    tcb: *const f32,
+    tcb_count: size_t,
+    closed: bool,
+) -> Option<Box<PiecewiseCubicCurve<SVector<f32, N>>>> {
+    let positions = unsafe { ffi_vectors::<N>(positions, positions_count) };
+    let tcb = unsafe { ffi_slice(tcb.cast::<[f32; 3]>(), tcb_count) };
+    PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, tcb, closed, |v| v.norm())
+        .into_box()
 }
 
 /// Creates a three-dimensional KB-spline.
@@ -190,14 +954,7 @@ pub unsafe extern "C" fn asdf_centripetalkochanekbartelsspline3(
     tcb_count: size_t,
     closed: bool,
 ) -> Option<Box<AsdfCubicCurve3>> {
-    let positions = unsafe { ffi_slice(positions.cast::<[f32; 3]>(), positions_count) };
-    let positions: Vec<_> = positions
-        .iter()
-        .map(|coords| Vec3::from_column_slice(coords))
-        .collect();
-    let tcb = unsafe { ffi_slice(tcb.cast::<[f32; 3]>(), tcb_count) };
-    PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, tcb, closed, Vec3::norm)
-        .into_box()
+    unsafe { kochanek_bartels(positions, positions_count, tcb, tcb_count, closed) }
 }
 
 /// Creates a two-dimensional KB-spline.
@@ -217,14 +974,112 @@ pub unsafe extern "C" fn asdf_centripetalkochanekbartelsspline2(
     tcb_count: size_t,
     closed: bool,
 ) -> Option<Box<AsdfCubicCurve2>> {
-    let positions = unsafe { ffi_slice(positions.cast::<[f32; 2]>(), positions_count) };
-    let positions: Vec<_> = positions
-        .iter()
-        .map(|coords| Vec2::from_column_slice(coords))
-        .collect();
-    let tcb = unsafe { ffi_slice(tcb.cast::<[f32; 3]>(), tcb_count) };
-    PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, tcb, closed, Vec2::norm)
-        .into_box()
+    unsafe { kochanek_bartels(positions, positions_count, tcb, tcb_count, closed) }
+}
+
+/// Cubic Hermite spline for any number of dimensions.
+///
+/// # Safety
+///
+/// Same as for `asdf_cubiccurve3_hermite()`.
+unsafe fn hermite<const N: usize>(
+    positions: *const f32,
+    positions_count: size_t,
+    tangents: *const f32,
+    tangents_count: size_t,
+    grid: *const f32,
+    grid_count: size_t,
+) -> Option<Box<PiecewiseCubicCurve<SVector<f32, N>>>> {
+    let positions = unsafe { ffi_vectors::<N>(positions, positions_count) };
+    let tangents = unsafe { ffi_vectors::<N>(tangents, tangents_count) };
+    let grid = unsafe { ffi_slice(grid, grid_count) };
+    PiecewiseCubicCurve::new_hermite(&positions, &tangents, grid).into_box()
+}
+
+/// Creates a three-dimensional cubic Hermite spline.
+///
+/// Each element in `positions` and `tangents` contains *three* `float` values.
+/// There must be two tangents (outgoing and incoming) per segment,
+/// `grid` must have one element per position.
+///
+/// # Safety
+///
+/// All input pointers must be valid for the corresponding `*_count` numbers
+/// of elements (not bytes).
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_hermite(
+    positions: *const f32,
+    positions_count: size_t,
+    tangents: *const f32,
+    tangents_count: size_t,
+    grid: *const f32,
+    grid_count: size_t,
+) -> Option<Box<AsdfCubicCurve3>> {
+    unsafe {
+        hermite(
+            positions,
+            positions_count,
+            tangents,
+            tangents_count,
+            grid,
+            grid_count,
+        )
+    }
+}
+
+/// Creates a two-dimensional cubic Hermite spline.
+///
+/// Each element in `positions` and `tangents` contains *two* `float` values.
+/// There must be two tangents (outgoing and incoming) per segment,
+/// `grid` must have one element per position.
+///
+/// # Safety
+///
+/// All input pointers must be valid for the corresponding `*_count` numbers
+/// of elements (not bytes).
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_hermite(
+    positions: *const f32,
+    positions_count: size_t,
+    tangents: *const f32,
+    tangents_count: size_t,
+    grid: *const f32,
+    grid_count: size_t,
+) -> Option<Box<AsdfCubicCurve2>> {
+    unsafe {
+        hermite(
+            positions,
+            positions_count,
+            tangents,
+            tangents_count,
+            grid,
+            grid_count,
+        )
+    }
+}
+
+/// Creates a one-dimensional cubic Hermite spline.
+///
+/// There must be two tangents (outgoing and incoming) per segment,
+/// `grid` must have one element per position.
+///
+/// # Safety
+///
+/// All input pointers must be valid for the corresponding `*_count` numbers
+/// of elements (not bytes).
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve1_hermite(
+    positions: *const f32,
+    positions_count: size_t,
+    tangents: *const f32,
+    tangents_count: size_t,
+    grid: *const f32,
+    grid_count: size_t,
+) -> Option<Box<AsdfCubicCurve1>> {
+    let positions = unsafe { ffi_slice(positions, positions_count) };
+    let tangents = unsafe { ffi_slice(tangents, tangents_count) };
+    let grid = unsafe { ffi_slice(grid, grid_count) };
+    PiecewiseCubicCurve::new_hermite(positions, tangents, grid).into_box()
 }
 
 /// Creates a one-dimensional piecewise monotone cubic spline.
@@ -263,11 +1118,7 @@ pub unsafe extern "C" fn asdf_piecewisemonotonecubicspline_with_slopes(
     closed: bool,
 ) -> Option<Box<AsdfCubicCurve1>> {
     let values = unsafe { ffi_slice(values, values_count) };
-    let slopes = unsafe { ffi_slice(slopes, slopes_count) };
-    let slopes: Vec<_> = slopes
-        .iter()
-        .map(|&x| if x.is_nan() { None } else { Some(x) })
-        .collect();
+    let slopes = unsafe { ffi_optional(slopes, slopes_count) };
     let grid = unsafe { ffi_slice(grid, grid_count) };
     PiecewiseCubicCurve::new_piecewise_monotone_with_slopes(values, slopes, grid, closed).into_box()
 }
@@ -308,11 +1159,7 @@ pub unsafe extern "C" fn asdf_monotonecubic_with_slopes(
     cyclic: bool,
 ) -> Option<Box<AsdfMonotoneCubic>> {
     let values = unsafe { ffi_slice(values, values_count) };
-    let slopes = unsafe { ffi_slice(slopes, slopes_count) };
-    let slopes: Vec<_> = slopes
-        .iter()
-        .map(|&x| if x.is_nan() { None } else { Some(x) })
-        .collect();
+    let slopes = unsafe { ffi_optional(slopes, slopes_count) };
     let grid = unsafe { ffi_slice(grid, grid_count) };
     MonotoneCubicSpline::with_slopes(values, slopes, grid, cyclic).into_box()
 }
@@ -334,7 +1181,7 @@ pub unsafe extern "C" fn asdf_monotonecubic_free(_: Option<Box<AsdfMonotoneCubic
 /// The pointer must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_monotonecubic_inner(
-    curve: &mut AsdfMonotoneCubic,
+    curve: &AsdfMonotoneCubic,
 ) -> *const AsdfCubicCurve1 {
     curve.inner_ref()
 }
@@ -349,25 +1196,38 @@ pub unsafe extern "C" fn asdf_monotonecubic_inner(
 /// Pointers can be NULL, but in this case `count` must be 0.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_monotonecubic_get_time(
-    curve: &mut AsdfMonotoneCubic,
+    curve: &AsdfMonotoneCubic,
     values: *const f32,
     count: size_t,
     output: *mut f32,
 ) {
-    let values = unsafe { ffi_slice(values, count) };
-    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<_>>(), count) };
-    for (val, out) in values.iter().zip(output) {
-        *out = MaybeUninit::new(curve.get_time(*val).unwrap_or(std::f32::NAN));
+    unsafe {
+        write_output::<f32>(values, count, output, |t| {
+            curve.get_time(t).unwrap_or(f32::NAN)
+        })
     }
 }
 
-// TODO: avoid duplication for 1, 2 and 3 dimensions ...
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_monotonecubic_domain(
+    curve: &AsdfMonotoneCubic,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.inner_ref().grid(), start, end) }
+}
 
 /// Frees an `AsdfCubicCurve3`
 ///
 /// # Safety
 ///
-/// The pointer must have been obtained with `asdf_centripetalkochanekbartelsspline3()`.
+/// The pointer must have been obtained with `asdf_centripetalkochanekbartelsspline3()`
+/// or `asdf_cubiccurve3_hermite()`.
 /// Each pointer can only be freed once.
 /// Passing NULL is allowed.
 #[no_mangle]
@@ -383,15 +1243,117 @@ pub unsafe extern "C" fn asdf_cubiccurve3_free(_: Option<Box<AsdfCubicCurve3>>)
 /// Pointers can be NULL, but in this case `count` must be 0.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve3_evaluate(
-    curve: &mut AsdfCubicCurve3,
+    curve: &AsdfCubicCurve3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_cubiccurve3_evaluate_layout(curve, times, count, output, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_cubiccurve3_evaluate()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_evaluate_layout(
+    curve: &AsdfCubicCurve3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe { write_vector_output::<3>(times, count, output, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Like `asdf_cubiccurve3_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains *three* `float`s per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_evaluate_alloc(
+    curve: &AsdfCubicCurve3,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe { asdf_cubiccurve3_evaluate_alloc_layout(curve, times, count, AsdfLayout::Interleaved) }
+}
+
+/// Like `asdf_cubiccurve3_evaluate_alloc()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_evaluate_alloc_layout(
+    curve: &AsdfCubicCurve3,
+    times: *const f32,
+    count: size_t,
+    layout: AsdfLayout,
+) -> AsdfBuffer {
+    unsafe { allocate_output::<3>(times, count, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Returns velocity vector(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_evaluate_velocity(
+    curve: &AsdfCubicCurve3,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_cubiccurve3_evaluate_velocity_layout(
+            curve,
+            times,
+            count,
+            output,
+            AsdfLayout::Interleaved,
+        )
+    }
+}
+
+/// Like `asdf_cubiccurve3_evaluate_velocity()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *three* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_evaluate_velocity_layout(
+    curve: &AsdfCubicCurve3,
     times: *const f32,
     count: size_t,
     output: *mut f32,
+    layout: AsdfLayout,
 ) {
-    let times = unsafe { ffi_slice(times, count) };
-    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<[f32; 3]>>(), count) };
-    for (time, out) in times.iter().zip(output) {
-        *out = MaybeUninit::new(curve.evaluate(*time).into());
+    unsafe {
+        write_vector_output::<3>(times, count, output, layout, |t| {
+            curve.evaluate_velocity(t).into()
+        })
     }
 }
 
@@ -402,21 +1364,51 @@ pub unsafe extern "C" fn asdf_cubiccurve3_evaluate(
 /// All pointers must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve3_grid(
-    curve: &mut AsdfCubicCurve3,
+    curve: &AsdfCubicCurve3,
     output: *mut *const f32,
 ) -> size_t {
-    let grid = curve.grid();
-    unsafe {
-        output.write(grid.as_ptr());
-    }
-    grid.len()
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_domain(
+    curve: &AsdfCubicCurve3,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// Provides a pointer to (and number of) segments.
+///
+/// Each segment consists of *twelve* `float`s, i.e. the four coefficients
+/// (each with *three* `float`s) of the cubic polynomial
+/// `a0 + a1 * t + a2 * t^2 + a3 * t^3`,
+/// where `t` goes from 0 to 1 between the corresponding grid elements,
+/// see `asdf_cubiccurve3_grid()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve3_segments(
+    curve: &AsdfCubicCurve3,
+    output: *mut *const f32,
+) -> size_t {
+    unsafe { provide_slice(curve.segments(), output) }
 }
 
 /// Frees an `AsdfCubicCurve2`
 ///
 /// # Safety
 ///
-/// The pointer must have been obtained with `asdf_centripetalkochanekbartelsspline2()`.
+/// The pointer must have been obtained with `asdf_centripetalkochanekbartelsspline2()`
+/// or `asdf_cubiccurve2_hermite()`.
 /// Each pointer can only be freed once.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve2_free(_: Option<Box<AsdfCubicCurve2>>) {}
@@ -431,15 +1423,117 @@ pub unsafe extern "C" fn asdf_cubiccurve2_free(_: Option<Box<AsdfCubicCurve2>>)
 /// Pointers can be NULL, but in this case `count` must be 0.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve2_evaluate(
-    curve: &mut AsdfCubicCurve2,
+    curve: &AsdfCubicCurve2,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_cubiccurve2_evaluate_layout(curve, times, count, output, AsdfLayout::Interleaved)
+    }
+}
+
+/// Like `asdf_cubiccurve2_evaluate()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *two* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_evaluate_layout(
+    curve: &AsdfCubicCurve2,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+    layout: AsdfLayout,
+) {
+    unsafe { write_vector_output::<2>(times, count, output, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Like `asdf_cubiccurve2_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains *two* `float`s per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_evaluate_alloc(
+    curve: &AsdfCubicCurve2,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe { asdf_cubiccurve2_evaluate_alloc_layout(curve, times, count, AsdfLayout::Interleaved) }
+}
+
+/// Like `asdf_cubiccurve2_evaluate_alloc()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_evaluate_alloc_layout(
+    curve: &AsdfCubicCurve2,
+    times: *const f32,
+    count: size_t,
+    layout: AsdfLayout,
+) -> AsdfBuffer {
+    unsafe { allocate_output::<2>(times, count, layout, |t| curve.evaluate(t).into()) }
+}
+
+/// Returns velocity vector(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *two* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_evaluate_velocity(
+    curve: &AsdfCubicCurve2,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe {
+        asdf_cubiccurve2_evaluate_velocity_layout(
+            curve,
+            times,
+            count,
+            output,
+            AsdfLayout::Interleaved,
+        )
+    }
+}
+
+/// Like `asdf_cubiccurve2_evaluate_velocity()`, but with the given output `layout`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *two* `float`s per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_evaluate_velocity_layout(
+    curve: &AsdfCubicCurve2,
     times: *const f32,
     count: size_t,
     output: *mut f32,
+    layout: AsdfLayout,
 ) {
-    let times = unsafe { ffi_slice(times, count) };
-    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<[f32; 2]>>(), count) };
-    for (time, out) in times.iter().zip(output) {
-        *out = MaybeUninit::new(curve.evaluate(*time).into());
+    unsafe {
+        write_vector_output::<2>(times, count, output, layout, |t| {
+            curve.evaluate_velocity(t).into()
+        })
     }
 }
 
@@ -450,22 +1544,51 @@ pub unsafe extern "C" fn asdf_cubiccurve2_evaluate(
 /// All pointers must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve2_grid(
-    curve: &mut AsdfCubicCurve2,
+    curve: &AsdfCubicCurve2,
     output: *mut *const f32,
 ) -> size_t {
-    let grid = curve.grid();
-    unsafe {
-        output.write(grid.as_ptr());
-    }
-    grid.len()
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_domain(
+    curve: &AsdfCubicCurve2,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// Provides a pointer to (and number of) segments.
+///
+/// Each segment consists of *eight* `float`s, i.e. the four coefficients
+/// (each with *two* `float`s) of the cubic polynomial
+/// `a0 + a1 * t + a2 * t^2 + a3 * t^3`,
+/// where `t` goes from 0 to 1 between the corresponding grid elements,
+/// see `asdf_cubiccurve2_grid()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve2_segments(
+    curve: &AsdfCubicCurve2,
+    output: *mut *const f32,
+) -> size_t {
+    unsafe { provide_slice(curve.segments(), output) }
 }
 
 /// Frees an `AsdfCubicCurve1`
 ///
 /// # Safety
 ///
-/// The pointer must have been obtained with `asdf_piecewisemonotonecubicspline()` or
-/// `asdf_piecewisemonotonecubicspline_with_slopes()`.
+/// The pointer must have been obtained with `asdf_piecewisemonotonecubicspline()`,
+/// `asdf_piecewisemonotonecubicspline_with_slopes()` or `asdf_cubiccurve1_hermite()`.
 /// Each pointer can only be freed once.
 /// Passing NULL is allowed.
 #[no_mangle]
@@ -481,18 +1604,55 @@ pub unsafe extern "C" fn asdf_cubiccurve1_free(_: Option<Box<AsdfCubicCurve1>>)
 /// Pointers can be NULL, but in this case `count` must be 0.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve1_evaluate(
-    curve: &mut AsdfCubicCurve1,
+    curve: &AsdfCubicCurve1,
     times: *const f32,
     count: size_t,
     output: *mut f32,
 ) {
-    let times = unsafe { ffi_slice(times, count) };
-    let output = unsafe { ffi_slice_mut(output.cast::<MaybeUninit<f32>>(), count) };
-    for (time, out) in times.iter().zip(output) {
-        *out = MaybeUninit::new(curve.evaluate(*time));
+    unsafe { write_output::<f32>(times, count, output, |t| curve.evaluate(t)) }
+}
+
+/// Like `asdf_cubiccurve1_evaluate()`, but the output is allocated by the library.
+///
+/// The returned buffer contains one `float` per element,
+/// it must be freed with `asdf_buffer_free()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element.
+/// `times` can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve1_evaluate_alloc(
+    curve: &AsdfCubicCurve1,
+    times: *const f32,
+    count: size_t,
+) -> AsdfBuffer {
+    unsafe {
+        allocate_output::<1>(times, count, AsdfLayout::Interleaved, |t| {
+            [curve.evaluate(t)]
+        })
     }
 }
 
+/// Returns velocity vector(s) at given time(s).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+/// `times` contains one `float` per element,
+/// `output` must provide space for *one* `float` per element.
+/// Pointers can be NULL, but in this case `count` must be 0.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve1_evaluate_velocity(
+    curve: &AsdfCubicCurve1,
+    times: *const f32,
+    count: size_t,
+    output: *mut f32,
+) {
+    unsafe { write_output::<f32>(times, count, output, |t| curve.evaluate_velocity(t)) }
+}
+
 /// Provides a pointer to (and number of) grid elements.
 ///
 /// # Safety
@@ -500,12 +1660,343 @@ pub unsafe extern "C" fn asdf_cubiccurve1_evaluate(
 /// All pointers must be valid.
 #[no_mangle]
 pub unsafe extern "C" fn asdf_cubiccurve1_grid(
-    curve: &mut AsdfCubicCurve1,
+    curve: &AsdfCubicCurve1,
     output: *mut *const f32,
 ) -> size_t {
-    let grid = curve.grid();
-    unsafe {
-        output.write(grid.as_ptr());
+    unsafe { provide_slice(curve.grid(), output) }
+}
+
+/// Provides the first and last grid element (i.e. the valid time range).
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve1_domain(
+    curve: &AsdfCubicCurve1,
+    start: *mut f32,
+    end: *mut f32,
+) {
+    unsafe { provide_domain(curve.grid(), start, end) }
+}
+
+/// Provides a pointer to (and number of) segments.
+///
+/// Each segment consists of *four* `float`s, i.e. the four coefficients
+/// (each with one `float`) of the cubic polynomial
+/// `a0 + a1 * t + a2 * t^2 + a3 * t^3`,
+/// where `t` goes from 0 to 1 between the corresponding grid elements,
+/// see `asdf_cubiccurve1_grid()`.
+///
+/// # Safety
+///
+/// All pointers must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn asdf_cubiccurve1_segments(
+    curve: &AsdfCubicCurve1,
+    output: *mut *const f32,
+) -> size_t {
+    unsafe { provide_slice(curve.segments(), output) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shareable_between_threads() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<AsdfPosSpline3>();
+        assert_sync::<AsdfRotSpline>();
+        assert_sync::<AsdfCubicCurve3>();
+        assert_sync::<AsdfCubicCurve2>();
+        assert_sync::<AsdfCubicCurve1>();
+        assert_sync::<AsdfMonotoneCubic>();
+        assert_sync::<AsdfCubicDeCasteljau>();
+    }
+
+    #[test]
+    fn rotation_matrix() {
+        // 90 degrees around the z axis, then 90 degrees around the x axis
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        let quaternions = [[0.0, 0.0, h, h], [h, 0.0, 0.0, h]];
+        let times = [0.0, 1.0];
+        let speeds = [f32::NAN; 2];
+        let curve = unsafe {
+            asdf_asdfrotspline(
+                quaternions.as_ptr().cast(),
+                2,
+                times.as_ptr(),
+                2,
+                speeds.as_ptr(),
+                2,
+                std::ptr::null(),
+                0,
+                false,
+            )
+        }
+        .unwrap();
+        let mut matrix3 = [[[0.0f32; 3]; 3]; 2];
+        let mut matrix4 = [[[0.0f32; 4]; 4]; 2];
+        unsafe {
+            asdf_asdfrotspline_evaluate_matrix3(
+                &curve,
+                times.as_ptr(),
+                2,
+                matrix3.as_mut_ptr().cast(),
+            );
+            asdf_asdfrotspline_evaluate_matrix4(
+                &curve,
+                times.as_ptr(),
+                2,
+                matrix4.as_mut_ptr().cast(),
+            );
+        }
+        let expected = [
+            [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+            [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]],
+        ];
+        for k in 0..2 {
+            for row in 0..3 {
+                for col in 0..3 {
+                    assert!((matrix3[k][row][col] - expected[k][row][col]).abs() < 1e-6);
+                    assert_eq!(matrix4[k][row][col], matrix3[k][row][col]);
+                }
+                assert_eq!(matrix4[k][row][3], 0.0);
+                assert_eq!(matrix4[k][3][row], 0.0);
+            }
+            assert_eq!(matrix4[k][3][3], 1.0);
+        }
+    }
+
+    #[test]
+    fn invalid_quaternion() {
+        let quaternions = [[0.0, 0.0, 0.0, 1.0], [0.0; 4]];
+        let times = [0.0, 1.0];
+        let curve = unsafe {
+            asdf_asdfrotspline(
+                quaternions.as_ptr().cast(),
+                2,
+                times.as_ptr(),
+                2,
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                0,
+                false,
+            )
+        };
+        assert!(curve.is_none());
+        assert_eq!(asdf_last_error_code(), AsdfErrorCode::InvalidQuaternion);
+    }
+
+    #[test]
+    fn segments() {
+        let positions = [[0.0f32, 1.0], [2.0, 3.0]];
+        let curve = unsafe {
+            asdf_centripetalkochanekbartelsspline2(
+                positions.as_ptr().cast(),
+                2,
+                std::ptr::null(),
+                0,
+                false,
+            )
+        }
+        .unwrap();
+        let mut segments = std::ptr::null();
+        let count = unsafe { asdf_cubiccurve2_segments(&curve, &mut segments) };
+        assert_eq!(count, 1);
+        let coefficients = unsafe { std::slice::from_raw_parts(segments, 8) };
+        assert_eq!(coefficients, [0.0, 1.0, 2.0, 2.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn control_polygon() {
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        // Not normalized
+        let control_polygon = [
+            [0.0, 0.0, 0.0, 2.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, h, h],
+            [0.0, 0.0, 1.0, 1.0],
+        ];
+        let grid = [0.0, 1.0];
+        let curve =
+            unsafe { asdf_cubicdecasteljau(control_polygon.as_ptr().cast(), 4, grid.as_ptr(), 2) }
+                .unwrap();
+        let mut output = [[0.0f32; 4]; 2];
+        unsafe {
+            asdf_cubicdecasteljau_evaluate(&curve, grid.as_ptr(), 2, output.as_mut_ptr().cast());
+        }
+        assert_eq!(output[0], [0.0, 0.0, 0.0, 1.0]);
+        assert!((output[1][2] - h).abs() < 1e-6);
+        assert!((output[1][3] - h).abs() < 1e-6);
+        let curve =
+            unsafe { asdf_cubicdecasteljau(control_polygon.as_ptr().cast(), 3, grid.as_ptr(), 2) };
+        assert!(curve.is_none());
+        assert_eq!(asdf_last_error_code(), AsdfErrorCode::ControlPolygonVsGrid);
+    }
+
+    #[test]
+    fn hermite() {
+        let positions = [[0.0f32, 0.0], [1.0, 2.0]];
+        let tangents = [[1.0f32, 0.0], [0.0, 1.0]];
+        let grid = [0.0, 2.0];
+        let curve = unsafe {
+            asdf_cubiccurve2_hermite(
+                positions.as_ptr().cast(),
+                2,
+                tangents.as_ptr().cast(),
+                2,
+                grid.as_ptr(),
+                2,
+            )
+        }
+        .unwrap();
+        let mut velocity = [[0.0f32; 2]; 2];
+        unsafe {
+            asdf_cubiccurve2_evaluate_velocity(
+                &curve,
+                grid.as_ptr(),
+                2,
+                velocity.as_mut_ptr().cast(),
+            );
+        }
+        assert_eq!(velocity, tangents);
+        let curve = unsafe {
+            asdf_cubiccurve1_hermite([0.0].as_ptr(), 1, std::ptr::null(), 0, [0.0].as_ptr(), 1)
+        };
+        assert!(curve.is_none());
+        assert_eq!(asdf_last_error_code(), AsdfErrorCode::LessThanTwoValues);
+    }
+
+    #[test]
+    fn sample() {
+        let positions = [[0.0f32, 0.0, 0.0], [1.0, 2.0, 3.0], [4.0, 0.0, 1.0]];
+        let times = [0.0, f32::NAN, 3.0];
+        let speeds = [f32::NAN; 3];
+        let tcb = [[0.0f32; 3]];
+        let curve = unsafe {
+            asdf_asdfposspline3(
+                positions.as_ptr().cast(),
+                3,
+                times.as_ptr(),
+                3,
+                speeds.as_ptr(),
+                3,
+                tcb.as_ptr().cast(),
+                1,
+                false,
+            )
+        }
+        .unwrap();
+        let sample_times: Vec<_> = (0..7).map(|i| -0.5 + i as f32 * 0.6).collect();
+        let mut expected = [[0.0f32; 3]; 7];
+        let mut sampled = [[0.0f32; 3]; 7];
+        unsafe {
+            asdf_asdfposspline3_evaluate(
+                &curve,
+                sample_times.as_ptr(),
+                7,
+                expected.as_mut_ptr().cast(),
+            );
+            asdf_asdfposspline3_sample(&curve, -0.5, 0.6, 7, sampled.as_mut_ptr().cast());
+        }
+        assert_eq!(sampled, expected);
+    }
+
+    #[test]
+    fn domain() {
+        let values = [0.0, 1.0, 3.0];
+        let grid = [-1.0, 0.5, 2.0];
+        let curve =
+            unsafe { asdf_monotonecubic(values.as_ptr(), 3, grid.as_ptr(), 3, false) }.unwrap();
+        let (mut start, mut end) = (0.0, 0.0);
+        unsafe { asdf_monotonecubic_domain(&curve, &mut start, &mut end) };
+        assert_eq!((start, end), (-1.0, 2.0));
+    }
+
+    #[test]
+    fn allocated_output() {
+        let positions = [[0.0f32, 1.0], [2.0, 3.0]];
+        let curve = unsafe {
+            asdf_centripetalkochanekbartelsspline2(
+                positions.as_ptr().cast(),
+                2,
+                std::ptr::null(),
+                0,
+                false,
+            )
+        }
+        .unwrap();
+        let times = [0.0, 0.5, 2.0];
+        let buffer = unsafe { asdf_cubiccurve2_evaluate_alloc(&curve, times.as_ptr(), 3) };
+        assert_eq!(buffer.len, 6);
+        let data = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        let mut expected = [0.0; 6];
+        unsafe {
+            asdf_cubiccurve2_evaluate(&curve, times.as_ptr(), 3, expected.as_mut_ptr());
+        }
+        assert_eq!(data, expected);
+        unsafe { asdf_buffer_free(buffer) };
+        let empty = unsafe { asdf_cubiccurve2_evaluate_alloc(&curve, std::ptr::null(), 0) };
+        assert_eq!(empty.len, 0);
+        unsafe { asdf_buffer_free(empty) };
+    }
+
+    #[test]
+    fn planar_layout() {
+        let positions = [[0.0f32, 1.0, 2.0], [3.0, 4.0, 5.0], [6.0, 8.0, 7.0]];
+        let tcb = [0.0f32; 3];
+        let curve = unsafe {
+            asdf_centripetalkochanekbartelsspline3(
+                positions.as_ptr().cast(),
+                3,
+                tcb.as_ptr(),
+                1,
+                false,
+            )
+        }
+        .unwrap();
+        let times = [0.0, 0.7, 1.4, 2.1];
+        let mut interleaved = [[0.0f32; 3]; 4];
+        let mut planar = [[0.0f32; 4]; 3];
+        unsafe {
+            asdf_cubiccurve3_evaluate(&curve, times.as_ptr(), 4, interleaved.as_mut_ptr().cast());
+            asdf_cubiccurve3_evaluate_layout(
+                &curve,
+                times.as_ptr(),
+                4,
+                planar.as_mut_ptr().cast(),
+                AsdfLayout::Planar,
+            );
+        }
+        for (i, element) in interleaved.iter().enumerate() {
+            for (j, &component) in element.iter().enumerate() {
+                assert_eq!(planar[j][i], component);
+            }
+        }
+        let buffer = unsafe {
+            asdf_cubiccurve3_evaluate_alloc_layout(&curve, times.as_ptr(), 4, AsdfLayout::Planar)
+        };
+        let data = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        assert_eq!(data, planar.concat());
+        unsafe { asdf_buffer_free(buffer) };
+    }
+
+    #[test]
+    fn velocity() {
+        let values = [0.0, 2.0];
+        let grid = [0.0, 1.0];
+        let curve = unsafe {
+            asdf_piecewisemonotonecubicspline(values.as_ptr(), 2, grid.as_ptr(), 2, false)
+        }
+        .unwrap();
+        let times = [0.0, 0.5, 1.0];
+        let mut output = [0.0; 3];
+        unsafe {
+            asdf_cubiccurve1_evaluate_velocity(&curve, times.as_ptr(), 3, output.as_mut_ptr());
+        }
+        assert_eq!(output, [2.0; 3]);
     }
-    grid.len()
 }
@@ -0,0 +1,44 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use asdfspline::quaternion::Vec3;
+use asdfspline::{AsdfPosSpline, NormWrapper, Spline, SplineWithVelocity};
+
+struct Norm3;
+
+impl NormWrapper<Norm3> for Vec3 {
+    fn norm(&self) -> f32 {
+        self.norm()
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    positions: Vec<[f32; 3]>,
+    times: Vec<Option<f32>>,
+    speeds: Vec<Option<f32>>,
+    waits: Vec<f32>,
+    tcb: Vec<[f32; 3]>,
+    closed: bool,
+    evaluation_times: Vec<f32>,
+}
+
+fuzz_target!(|input: Input| {
+    let positions: Vec<_> = input.positions.iter().copied().map(Vec3::from).collect();
+    let Ok(s) = AsdfPosSpline::<Vec3, Norm3>::with_waits(
+        positions,
+        &input.times,
+        &input.speeds,
+        &input.waits,
+        &input.tcb,
+        input.closed,
+    ) else {
+        return;
+    };
+    for &t in s.grid().iter().chain(&input.evaluation_times) {
+        s.evaluate(t);
+        s.evaluate_velocity(t);
+    }
+});
@@ -0,0 +1,38 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use asdfspline::{AsdfRotSpline, Spline, SplineWithVelocity};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    /// Azimuth, elevation and roll in degrees, e.g. 180 degree rotations.
+    angles: Vec<(f32, f32, f32)>,
+    times: Vec<Option<f32>>,
+    speeds: Vec<Option<f32>>,
+    tcb: Vec<[f32; 3]>,
+    closed: bool,
+    evaluation_times: Vec<f32>,
+}
+
+fuzz_target!(|input: Input| {
+    let quaternions: Vec<_> = input
+        .angles
+        .iter()
+        .map(|&(azim, elev, roll)| asdfspline::quaternion::angles2quat(azim, elev, roll))
+        .collect();
+    let Ok(s) = AsdfRotSpline::with_speeds(
+        quaternions,
+        &input.times,
+        input.speeds,
+        &input.tcb,
+        input.closed,
+    ) else {
+        return;
+    };
+    for &t in s.grid().iter().chain(&input.evaluation_times) {
+        s.evaluate(t);
+        s.evaluate_velocity(t);
+    }
+});
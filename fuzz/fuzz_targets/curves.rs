@@ -0,0 +1,63 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use asdfspline::{MonotoneCubicSpline, PiecewiseCubicCurve, Spline, SplineWithVelocity};
+
+#[derive(Arbitrary, Debug)]
+enum Input {
+    /// Zero-length segments are only allowed at `jumps`.
+    Curve {
+        segments: Vec<[f32; 4]>,
+        grid: Vec<f32>,
+        jumps: Vec<usize>,
+        times: Vec<f32>,
+    },
+    Monotone {
+        values: Vec<f32>,
+        slopes: Vec<Option<f32>>,
+        grid: Vec<f32>,
+        cyclic: bool,
+        values_to_invert: Vec<f32>,
+    },
+}
+
+fuzz_target!(|input: Input| {
+    match input {
+        Input::Curve {
+            segments,
+            grid,
+            jumps,
+            times,
+        } => {
+            let Ok(curve) = PiecewiseCubicCurve::with_jumps(segments, grid, &jumps) else {
+                return;
+            };
+            for &t in &times {
+                curve.evaluate(t);
+                let _ = curve.evaluate_left(t);
+                curve.evaluate_velocity(t);
+            }
+            let mut output = vec![0.0; times.len()];
+            curve.evaluate_into(&times, &mut output);
+            let curve = curve.with_bucket_index().with_soa_layout();
+            curve.evaluate_into(&times, &mut output);
+            let _ = curve.nearest_time(0.0);
+        }
+        Input::Monotone {
+            values,
+            slopes,
+            grid,
+            cyclic,
+            values_to_invert,
+        } => {
+            let Ok(s) = MonotoneCubicSpline::with_slopes(values, slopes, grid, cyclic) else {
+                return;
+            };
+            for &value in &values_to_invert {
+                let _ = s.get_time(value);
+            }
+        }
+    }
+});
@@ -0,0 +1,194 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use asdfspline_ffi::*;
+
+/// Buffers are always valid, but their lengths might not match.
+#[derive(Arbitrary, Debug)]
+enum Input {
+    AsdfPosSpline3 {
+        positions: Vec<[f32; 3]>,
+        /// NaN means "not given".
+        times: Vec<f32>,
+        speeds: Vec<f32>,
+        tcb: Vec<[f32; 3]>,
+        closed: bool,
+        evaluation_times: Vec<f32>,
+    },
+    AsdfRotSpline {
+        quaternions: Vec<[f32; 4]>,
+        /// NaN means "not given".
+        times: Vec<f32>,
+        speeds: Vec<f32>,
+        tcb: Vec<[f32; 3]>,
+        closed: bool,
+        evaluation_times: Vec<f32>,
+    },
+    CubicDeCasteljau {
+        control_polygon: Vec<[f32; 4]>,
+        grid: Vec<f32>,
+        evaluation_times: Vec<f32>,
+    },
+    KochanekBartels3 {
+        positions: Vec<[f32; 3]>,
+        tcb: Vec<[f32; 3]>,
+        closed: bool,
+        evaluation_times: Vec<f32>,
+    },
+    Monotone {
+        values: Vec<f32>,
+        slopes: Vec<f32>,
+        grid: Vec<f32>,
+        cyclic: bool,
+        values_to_invert: Vec<f32>,
+    },
+}
+
+fuzz_target!(|input: Input| unsafe {
+    match input {
+        Input::AsdfPosSpline3 {
+            positions,
+            times,
+            speeds,
+            tcb,
+            closed,
+            evaluation_times,
+        } => {
+            let Some(s) = asdf_asdfposspline3(
+                positions.as_ptr().cast(),
+                positions.len(),
+                times.as_ptr(),
+                times.len(),
+                speeds.as_ptr(),
+                speeds.len(),
+                tcb.as_ptr().cast(),
+                tcb.len(),
+                closed,
+            ) else {
+                asdf_last_error();
+                return;
+            };
+            let mut output = vec![[0.0f32; 3]; evaluation_times.len()];
+            asdf_asdfposspline3_evaluate(
+                &s,
+                evaluation_times.as_ptr(),
+                evaluation_times.len(),
+                output.as_mut_ptr().cast(),
+            );
+            let mut grid = std::ptr::null();
+            asdf_asdfposspline3_grid(&s, &mut grid);
+            asdf_asdfposspline3_duration(&s);
+            asdf_asdfposspline3_free(Some(s));
+        }
+        Input::AsdfRotSpline {
+            quaternions,
+            times,
+            speeds,
+            tcb,
+            closed,
+            evaluation_times,
+        } => {
+            let Some(s) = asdf_asdfrotspline(
+                quaternions.as_ptr().cast(),
+                quaternions.len(),
+                times.as_ptr(),
+                times.len(),
+                speeds.as_ptr(),
+                speeds.len(),
+                tcb.as_ptr().cast(),
+                tcb.len(),
+                closed,
+            ) else {
+                asdf_last_error();
+                return;
+            };
+            let mut output = vec![[0.0f32; 9]; evaluation_times.len()];
+            asdf_asdfrotspline_evaluate_matrix3(
+                &s,
+                evaluation_times.as_ptr(),
+                evaluation_times.len(),
+                output.as_mut_ptr().cast(),
+            );
+            asdf_asdfrotspline_free(Some(s));
+        }
+        Input::CubicDeCasteljau {
+            control_polygon,
+            grid,
+            evaluation_times,
+        } => {
+            let Some(s) = asdf_cubicdecasteljau(
+                control_polygon.as_ptr().cast(),
+                control_polygon.len(),
+                grid.as_ptr(),
+                grid.len(),
+            ) else {
+                asdf_last_error();
+                return;
+            };
+            let mut output = vec![[0.0f32; 16]; evaluation_times.len()];
+            asdf_cubicdecasteljau_evaluate_matrix4(
+                &s,
+                evaluation_times.as_ptr(),
+                evaluation_times.len(),
+                output.as_mut_ptr().cast(),
+            );
+            asdf_cubicdecasteljau_free(Some(s));
+        }
+        Input::KochanekBartels3 {
+            positions,
+            tcb,
+            closed,
+            evaluation_times,
+        } => {
+            let Some(s) = asdf_centripetalkochanekbartelsspline3(
+                positions.as_ptr().cast(),
+                positions.len(),
+                tcb.as_ptr().cast(),
+                tcb.len(),
+                closed,
+            ) else {
+                asdf_last_error();
+                return;
+            };
+            let mut output = vec![[0.0f32; 3]; evaluation_times.len()];
+            asdf_cubiccurve3_evaluate_layout(
+                &s,
+                evaluation_times.as_ptr(),
+                evaluation_times.len(),
+                output.as_mut_ptr().cast(),
+                AsdfLayout::Planar,
+            );
+            asdf_cubiccurve3_free(Some(s));
+        }
+        Input::Monotone {
+            values,
+            slopes,
+            grid,
+            cyclic,
+            values_to_invert,
+        } => {
+            let Some(s) = asdf_monotonecubic_with_slopes(
+                values.as_ptr(),
+                values.len(),
+                slopes.as_ptr(),
+                slopes.len(),
+                grid.as_ptr(),
+                grid.len(),
+                cyclic,
+            ) else {
+                asdf_last_error();
+                return;
+            };
+            let mut output = vec![0.0f32; values_to_invert.len()];
+            asdf_monotonecubic_get_time(
+                &s,
+                values_to_invert.as_ptr(),
+                values_to_invert.len(),
+                output.as_mut_ptr(),
+            );
+            asdf_monotonecubic_free(Some(s));
+        }
+    }
+});
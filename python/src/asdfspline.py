@@ -93,10 +93,33 @@ class AsdfSpline(_FromPtr):
     def evaluate(self, t):
         return _evaluate(t, (3,), _lib.asdf_asdfposspline3_evaluate, self._ptr)
 
+    def evaluate_velocity(self, t):
+        return _evaluate(
+            t, (3,), _lib.asdf_asdfposspline3_evaluate_velocity, self._ptr)
+
+    def sample(self, start, step, count):
+        output = _np.empty((count, 3), dtype='float32')
+        _lib.asdf_asdfposspline3_sample(
+            self._ptr, start, step, count,
+            _ffi.from_buffer('float[]', output))
+        return output
+
     @property
     def grid(self):
         return _grid(_lib.asdf_asdfposspline3_grid, self._ptr)
 
+    @property
+    def start_time(self):
+        return _lib.asdf_asdfposspline3_start_time(self._ptr)
+
+    @property
+    def end_time(self):
+        return _lib.asdf_asdfposspline3_end_time(self._ptr)
+
+    @property
+    def duration(self):
+        return _lib.asdf_asdfposspline3_duration(self._ptr)
+
 
 def _evaluate(t, extra_dim, func, ptr):
     t = _np.ascontiguousarray(t, dtype='float32')
@@ -120,6 +143,25 @@ def _grid(func, ptr):
     return array
 
 
+def _domain(func, ptr):
+    start = _ffi.new('float*')
+    end = _ffi.new('float*')
+    func(ptr, start, end)
+    return start[0], end[0]
+
+
+def _segments(func, ptr, extra_dim):
+    segments_ptr = _ffi.new('float**')
+    segments_len = func(ptr, segments_ptr)
+    shape = (segments_len, 4) + extra_dim
+    buffer = _ffi.buffer(
+        segments_ptr[0], _np.prod(shape) * _np.dtype('float32').itemsize)
+    array = _np.frombuffer(buffer, dtype='float32').reshape(shape)
+    # NB: Writing to this array would be Undefined Behavior
+    array.flags.writeable = False
+    return array
+
+
 def _make_buffer(dim, numbers, name=None):
     # NB: If name is None, this is supposed to never fail
     numbers = _np.ascontiguousarray(numbers, dtype='float32')
@@ -161,10 +203,22 @@ class _CubicCurve3(_FromPtr):
     def evaluate(self, t):
         return _evaluate(t, (3,), _lib.asdf_cubiccurve3_evaluate, self._ptr)
 
+    def evaluate_velocity(self, t):
+        return _evaluate(
+            t, (3,), _lib.asdf_cubiccurve3_evaluate_velocity, self._ptr)
+
     @property
     def grid(self):
         return _grid(_lib.asdf_cubiccurve3_grid, self._ptr)
 
+    @property
+    def domain(self):
+        return _domain(_lib.asdf_cubiccurve3_domain, self._ptr)
+
+    @property
+    def segments(self):
+        return _segments(_lib.asdf_cubiccurve3_segments, self._ptr, (3,))
+
 
 class CentripetalKochanekBartelsSpline3(_CubicCurve3):
 
@@ -181,15 +235,43 @@ class CentripetalKochanekBartelsSpline3(_CubicCurve3):
         super().__init__(ptr)
 
 
+class CubicHermiteSpline3(_CubicCurve3):
+
+    def __init__(self, positions, tangents, grid):
+        positions, positions_ptr = _make_buffer(3, positions, 'positions')
+        tangents, tangents_ptr = _make_buffer(3, tangents, 'tangents')
+        grid, grid_ptr = _make_buffer(1, grid, 'grid')
+        ptr = _ffi.gc(
+            _lib.asdf_cubiccurve3_hermite(
+                positions_ptr, len(positions),
+                tangents_ptr, len(tangents),
+                grid_ptr, len(grid),
+            ),
+            _lib.asdf_cubiccurve3_free)
+        super().__init__(ptr)
+
+
 class _CubicCurve2(_FromPtr):
 
     def evaluate(self, t):
         return _evaluate(t, (2,), _lib.asdf_cubiccurve2_evaluate, self._ptr)
 
+    def evaluate_velocity(self, t):
+        return _evaluate(
+            t, (2,), _lib.asdf_cubiccurve2_evaluate_velocity, self._ptr)
+
     @property
     def grid(self):
         return _grid(_lib.asdf_cubiccurve2_grid, self._ptr)
 
+    @property
+    def domain(self):
+        return _domain(_lib.asdf_cubiccurve2_domain, self._ptr)
+
+    @property
+    def segments(self):
+        return _segments(_lib.asdf_cubiccurve2_segments, self._ptr, (2,))
+
 
 class CentripetalKochanekBartelsSpline2(_CubicCurve2):
 
@@ -206,15 +288,59 @@ class CentripetalKochanekBartelsSpline2(_CubicCurve2):
         super().__init__(ptr)
 
 
+class CubicHermiteSpline2(_CubicCurve2):
+
+    def __init__(self, positions, tangents, grid):
+        positions, positions_ptr = _make_buffer(2, positions, 'positions')
+        tangents, tangents_ptr = _make_buffer(2, tangents, 'tangents')
+        grid, grid_ptr = _make_buffer(1, grid, 'grid')
+        ptr = _ffi.gc(
+            _lib.asdf_cubiccurve2_hermite(
+                positions_ptr, len(positions),
+                tangents_ptr, len(tangents),
+                grid_ptr, len(grid),
+            ),
+            _lib.asdf_cubiccurve2_free)
+        super().__init__(ptr)
+
+
 class _CubicCurve1(_FromPtr):
 
     def evaluate(self, t):
         return _evaluate(t, (), _lib.asdf_cubiccurve1_evaluate, self._ptr)
 
+    def evaluate_velocity(self, t):
+        return _evaluate(
+            t, (), _lib.asdf_cubiccurve1_evaluate_velocity, self._ptr)
+
     @property
     def grid(self):
         return _grid(_lib.asdf_cubiccurve1_grid, self._ptr)
 
+    @property
+    def domain(self):
+        return _domain(_lib.asdf_cubiccurve1_domain, self._ptr)
+
+    @property
+    def segments(self):
+        return _segments(_lib.asdf_cubiccurve1_segments, self._ptr, ())
+
+
+class CubicHermiteSpline1(_CubicCurve1):
+
+    def __init__(self, positions, tangents, grid):
+        positions, positions_ptr = _make_buffer(1, positions, 'positions')
+        tangents, tangents_ptr = _make_buffer(1, tangents, 'tangents')
+        grid, grid_ptr = _make_buffer(1, grid, 'grid')
+        ptr = _ffi.gc(
+            _lib.asdf_cubiccurve1_hermite(
+                positions_ptr, len(positions),
+                tangents_ptr, len(tangents),
+                grid_ptr, len(grid),
+            ),
+            _lib.asdf_cubiccurve1_free)
+        super().__init__(ptr)
+
 
 class PiecewiseMonotoneCubicSpline(_CubicCurve1):
 
@@ -0,0 +1,13 @@
+from asdfspline import AsdfSpline
+import pytest
+
+
+def test_evaluate():
+    s = AsdfSpline([
+        {'position': (0, 0, 0), 'time': 0},
+        {'position': (1, 0, 0), 'time': 1},
+    ])
+    assert s.evaluate([0, 1]).ravel().tolist() == pytest.approx(
+        [0, 0, 0, 1, 0, 0])
+    assert s.evaluate_velocity([0, 0.5]).shape == (2, 3)
+    assert s.sample(0, 0.5, 3).tolist() == s.evaluate([0, 0.5, 1]).tolist()
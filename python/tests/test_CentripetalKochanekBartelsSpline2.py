@@ -29,3 +29,10 @@ def test_python_errors():
         CentripetalKochanekBartelsSpline2([[0, 0], [1, 1]], tcb=0)
     with pytest.raises(ValueError, match='TCB.*list of triples'):
         CentripetalKochanekBartelsSpline2([[0, 0], [1, 1]], tcb=[0])
+
+
+def test_evaluate():
+    s = CentripetalKochanekBartelsSpline2([[0, 0], [3, 4]])
+    assert s.evaluate(s.grid).ravel().tolist() == pytest.approx([0, 0, 3, 4])
+    assert s.evaluate(0).shape == (2,)
+    assert s.evaluate_velocity(s.grid).shape == (2, 2)
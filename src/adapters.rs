@@ -1,58 +1,560 @@
 use std::marker::PhantomData;
+use std::mem::size_of_val;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::RwLock;
 
-use crate::utilities::{bisect, GridError};
+use nalgebra::Point3;
+use once_cell::sync::OnceCell;
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::memory::MemoryUsage;
+use crate::quaternion::{Isometry3, UnitQuaternion, Vec3};
+use crate::utilities::{
+    bisect, brent, itp, newton_bisect, subdivide_grid, GridError, Quadrature, RootFinder,
+    SolverCounters, SolverOptions, SolverOptionsError, SolverStatistics,
+};
 use crate::{
     MonotoneCubicSpline, NormWrapper, PiecewiseCubicCurve, Spline, SplineWithVelocity, Vector,
 };
 
 pub struct ConstantSpeedAdapter<Value, Velocity, Inner, U> {
     inner: Inner,
-    grid: Box<[f32]>,
+    /// Cumulative arc lengths, empty until first needed, see [`ConstantSpeedAdapter::adapt_lazy()`].
+    grid: OnceCell<Box<[f32]>>,
+    /// Segments of the inner spline with constant velocity (e.g. straight lines),
+    /// those don't need numerical integration and root finding.
+    linear: Box<[bool]>,
+    lookup: Option<LookupTable>,
+    memo: Memo,
+    solver: SolverOptions,
+    statistics: SolverCounters,
     _phantom_output: PhantomData<Value>,
     _phantom_velocity: PhantomData<Velocity>,
     _phantom_dummy: PhantomData<U>,
 }
 
+/// Optional per-segment approximations of the inverse arc length function.
+struct LookupTable {
+    subdivisions: usize,
+    segments: LookupSegments,
+}
+
+/// `None` if the monotone spline couldn't be created.
+type LookupSegment = Option<PiecewiseCubicCurve<f32>>;
+
+enum LookupSegments {
+    Eager(Box<[LookupSegment]>),
+    /// `None` if not yet computed, see [`ConstantSpeedAdapter::adapt_with_lazy_table()`].
+    Lazy(Box<[RwLock<Option<LookupSegment>>]>),
+}
+
+/// Number of parts per segment for [`Memo`].
+const MEMO_SUBDIVISIONS: usize = 8;
+
+/// Arc lengths (relative to the segment start) at equidistant inner parameters
+/// of each segment, stored as bit patterns.
+///
+/// They are computed when the root finder is first used in a segment,
+/// later evaluations start their root finding (and quadrature) from the nearest node.
+/// The nodes don't depend on the order of evaluations,
+/// which makes the results independent of previous evaluations.
+struct Memo(Box<[AtomicU32]>);
+
+type MemoNodes = [f32; MEMO_SUBDIVISIONS - 1];
+
+impl Memo {
+    /// A NaN value, which is never stored.
+    const EMPTY: u32 = u32::MAX;
+
+    fn new(segments: usize) -> Memo {
+        Memo(
+            (0..segments * (MEMO_SUBDIVISIONS - 1))
+                .map(|_| AtomicU32::new(Self::EMPTY))
+                .collect(),
+        )
+    }
+
+    /// Returns the nodes of segment `idx`, they are computed with `f` if needed.
+    fn get_or_compute(&self, idx: usize, f: impl FnOnce() -> MemoNodes) -> MemoNodes {
+        let n = MEMO_SUBDIVISIONS - 1;
+        let entries = &self.0[idx * n..(idx + 1) * n];
+        let mut nodes = [0.0; MEMO_SUBDIVISIONS - 1];
+        if entries[0].load(Ordering::Acquire) != Self::EMPTY {
+            for (node, entry) in nodes.iter_mut().zip(entries) {
+                *node = f32::from_bits(entry.load(Ordering::Relaxed));
+            }
+            return nodes;
+        }
+        nodes = f();
+        // Concurrent callers might store the same values redundantly.
+        // The first entry is stored last, it marks the segment as complete.
+        for (entry, node) in entries.iter().zip(&nodes).skip(1) {
+            entry.store(node.to_bits(), Ordering::Relaxed);
+        }
+        entries[0].store(nodes[0].to_bits(), Ordering::Release);
+        nodes
+    }
+
+    fn clear(&self) {
+        for entry in self.0.iter() {
+            entry.store(Self::EMPTY, Ordering::Relaxed);
+        }
+    }
+}
+
 impl<Value, Velocity, Inner, U> ConstantSpeedAdapter<Value, Velocity, Inner, U>
 where
     Velocity: Vector + NormWrapper<U>,
     Inner: SplineWithVelocity<Value, Velocity>,
 {
     pub fn adapt(inner: Inner) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
-        let mut grid = Vec::with_capacity(inner.grid().len());
-        grid.push(0.0);
-        let grid = inner
-            .grid()
-            .windows(2)
-            .enumerate()
-            .fold(grid, |mut l, (i, ts)| {
-                if let [t0, t1] = *ts {
-                    l.push(*l.last().unwrap() + inner.integrated_speed(i, t0, t1));
-                    l
-                } else {
-                    unreachable!()
-                }
-            });
+        let result = Self::adapt_lazy(inner);
+        result.prepare_grid();
+        result
+    }
+
+    /// Like [`adapt()`](Self::adapt), but the arc lengths are only integrated
+    /// when they are first needed (or when [`prepare()`](Self::prepare) is called).
+    ///
+    /// Finding the segment for any given arc length requires the lengths
+    /// of all preceding segments, therefore the whole [`grid()`](Spline::grid)
+    /// is computed on first use.
+    /// This is meant for loading large scenes where many splines might never be evaluated.
+    pub fn adapt_lazy(inner: Inner) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        Self::without_grid(inner)
+    }
+
+    /// The grid is filled in on first use.
+    fn without_grid(inner: Inner) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        let segments = inner.grid().len() - 1;
+        let linear = (0..segments)
+            .map(|idx| has_constant_velocity(&inner, idx))
+            .collect();
         ConstantSpeedAdapter {
             inner,
-            grid: grid.into(),
+            grid: OnceCell::new(),
+            linear,
+            lookup: None,
+            memo: Memo::new(segments),
+            solver: SolverOptions::default(),
+            statistics: SolverCounters::default(),
             _phantom_output: PhantomData,
             _phantom_velocity: PhantomData,
             _phantom_dummy: PhantomData,
         }
     }
 
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Settings for integrating and inverting the arc length function.
+    ///
+    /// If the quadrature method changes, the grid
+    /// (and the lookup table, if any) is re-computed.
+    pub fn with_solver_options(
+        mut self,
+        options: SolverOptions,
+    ) -> Result<Self, SolverOptionsError> {
+        options.validate()?;
+        let recompute = options.quadrature != self.solver.quadrature;
+        self.solver = options;
+        self.memo.clear();
+        if recompute {
+            // A lazy grid stays lazy
+            if self.grid.take().is_some() {
+                self.prepare_grid();
+            }
+            if let Some(table) = &self.lookup {
+                self.lookup = Some(match table.segments {
+                    LookupSegments::Eager(_) => self.lookup_table(table.subdivisions),
+                    LookupSegments::Lazy(_) => self.lazy_lookup_table(table.subdivisions),
+                });
+            }
+        }
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn solver_options(&self) -> &SolverOptions {
+        &self.solver
+    }
+
+    /// Statistics of all root finding operations since creation
+    /// (or since [`reset_solver_statistics()`](Self::reset_solver_statistics)).
+    ///
+    /// The residuals are arc lengths.
+    /// Evaluations using the lookup table (or in "hold" segments) are not counted.
+    #[must_use]
+    pub fn solver_statistics(&self) -> SolverStatistics {
+        self.statistics.statistics()
+    }
+
+    pub fn reset_solver_statistics(&self) {
+        self.statistics.reset();
+    }
+
+    /// Like [`adapt()`](Self::adapt), but with a precomputed lookup table.
+    ///
+    /// Each segment is divided into `subdivisions` parts,
+    /// the arc length at the boundaries is integrated in advance
+    /// and in between, the inverse is interpolated with a monotone cubic spline.
+    /// This makes evaluation much cheaper, at the cost of a small error.
+    pub fn adapt_with_table(
+        inner: Inner,
+        subdivisions: usize,
+    ) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        let mut result = Self::adapt(inner);
+        result.lookup = Some(result.lookup_table(subdivisions));
+        result
+    }
+
+    /// Like [`adapt_with_table()`](Self::adapt_with_table),
+    /// but the arc lengths (see [`adapt_lazy()`](Self::adapt_lazy))
+    /// and each segment of the lookup table are only computed when they are first needed
+    /// (or when [`prepare()`](Self::prepare) is called).
+    pub fn adapt_with_lazy_table(
+        inner: Inner,
+        subdivisions: usize,
+    ) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        let mut result = Self::adapt_lazy(inner);
+        result.lookup = Some(result.lazy_lookup_table(subdivisions));
+        result
+    }
+
+    /// Computes the arc lengths (if not yet done) and the lookup table segments
+    /// overlapping the given `range` (of the new grid, i.e. arc length),
+    /// see [`adapt_lazy()`](Self::adapt_lazy) and
+    /// [`adapt_with_lazy_table()`](Self::adapt_with_lazy_table).
+    ///
+    /// This can be used to avoid latency during playback.
+    /// If nothing is lazy, nothing happens.
+    pub fn prepare(&self, range: Range<f32>) {
+        let (_, first) = self.clamp_parameter_and_find_index(range.start);
+        let (_, last) = self.clamp_parameter_and_find_index(range.end);
+        for idx in first..=last {
+            self.lazy_lookup_segment(idx, |_| ());
+        }
+    }
+
+    fn lazy_lookup_table(&self, subdivisions: usize) -> LookupTable {
+        let segments = (0..self.inner.grid().len() - 1)
+            .map(|_| RwLock::new(None))
+            .collect();
+        LookupTable {
+            subdivisions,
+            segments: LookupSegments::Lazy(segments),
+        }
+    }
+
+    /// Calls `f` with the (possibly newly computed) segment `idx` of a lazy lookup table.
+    ///
+    /// Returns `None` if the lookup table is not lazy.
+    fn lazy_lookup_segment<T>(&self, idx: usize, f: impl FnOnce(&LookupSegment) -> T) -> Option<T> {
+        let (subdivisions, segments) = match &self.lookup {
+            Some(LookupTable {
+                subdivisions,
+                segments: LookupSegments::Lazy(segments),
+            }) => (*subdivisions, segments),
+            _ => return None,
+        };
+        if let Some(segment) = &*segments[idx].read().unwrap() {
+            return Some(f(segment));
+        }
+        // The computation is done without holding the lock,
+        // concurrent callers might compute the same segment redundantly.
+        let segment = Self::lookup_segment(
+            &self.inner,
+            self.lengths(),
+            &self.linear,
+            self.solver.quadrature,
+            subdivisions,
+            idx,
+        );
+        let mut guard = segments[idx].write().unwrap();
+        Some(f(guard.get_or_insert(segment)))
+    }
+
+    fn integrated_speed(&self, index: usize, a: f32, b: f32) -> f32 {
+        Self::integrate_speed(
+            &self.inner,
+            &self.linear,
+            self.solver.quadrature,
+            index,
+            a,
+            b,
+        )
+    }
+
+    /// This doesn't use `self`, to allow parallel execution.
+    fn integrate_speed(
+        inner: &Inner,
+        linear: &[bool],
+        quadrature: Quadrature,
+        index: usize,
+        a: f32,
+        b: f32,
+    ) -> f32 {
+        if linear[index] {
+            let t0 = inner.grid()[index];
+            let t1 = inner.grid()[index + 1];
+            return inner.evaluate_velocity((t0 + t1) / 2.0).norm() * (b - a);
+        }
+        inner.integrated_speed_with(index, a, b, quadrature)
+    }
+
+    /// The grid, which is computed if needed.
+    fn lengths(&self) -> &[f32] {
+        self.grid.get_or_init(|| self.arc_lengths())
+    }
+
+    fn prepare_grid(&self) {
+        let _ = self.lengths();
+    }
+
+    /// Cumulative arc lengths at the grid points of the inner spline.
+    fn arc_lengths(&self) -> Box<[f32]> {
+        let lengths = self
+            .inner
+            .grid()
+            .windows(2)
+            .enumerate()
+            .map(|(i, ts)| self.integrated_speed(i, ts[0], ts[1]));
+        cumulative_sum(lengths)
+    }
+
+    fn lookup_table(&self, subdivisions: usize) -> LookupTable {
+        let segments = (0..self.inner.grid().len() - 1)
+            .map(|idx| {
+                Self::lookup_segment(
+                    &self.inner,
+                    self.lengths(),
+                    &self.linear,
+                    self.solver.quadrature,
+                    subdivisions,
+                    idx,
+                )
+            })
+            .collect();
+        LookupTable {
+            subdivisions,
+            segments: LookupSegments::Eager(segments),
+        }
+    }
+
+    /// This doesn't use `self`, to allow parallel execution.
+    fn lookup_segment(
+        inner: &Inner,
+        grid: &[f32],
+        linear: &[bool],
+        quadrature: Quadrature,
+        subdivisions: usize,
+        idx: usize,
+    ) -> LookupSegment {
+        let s0 = grid[idx];
+        let s1 = grid[idx + 1];
+        #[allow(clippy::float_cmp)]
+        if s0 == s1 || linear[idx] {
+            // Not needed, see s2t()
+            return None;
+        }
+        let times = subdivide_grid(&inner.grid()[idx..idx + 2], subdivisions);
+        let mut lengths = Vec::with_capacity(times.len());
+        lengths.push(s0);
+        for ts in times.windows(2) {
+            let length = inner.integrated_speed_with(idx, ts[0], ts[1], quadrature);
+            lengths.push(lengths.last().unwrap() + length);
+        }
+        // Avoid a mismatch due to accumulated rounding errors
+        *lengths.last_mut().unwrap() = s1;
+        // If this fails, the root finder is used as a fallback
+        MonotoneCubicSpline::new(times, lengths, false)
+            .ok()
+            .map(MonotoneCubicSpline::into_inner)
+    }
+
     /// If s is outside, return clipped t.
     fn s2t(&self, s: f32) -> f32 {
-        // TODO: proper accuracy (a bit less than single-precision?)
-        let accuracy = 0.0001;
+        let SolverOptions {
+            root_finder,
+            accuracy,
+            max_calls,
+            ..
+        } = self.solver;
         let (s, idx) = self.clamp_parameter_and_find_index(s);
+        let grid = self.lengths();
         let mut s = s;
-        s -= self.grid[idx];
+        s -= grid[idx];
         let t0 = self.inner.grid()[idx];
         let t1 = self.inner.grid()[idx + 1];
-        let func = |t| self.inner.integrated_speed(idx, t0, t) - s;
-        bisect(func, t0, t1, accuracy, 50)
+        let accuracy = accuracy.absolute(t0, t1);
+        let length = grid[idx + 1] - grid[idx];
+        #[allow(clippy::float_cmp)]
+        if grid[idx] == grid[idx + 1] {
+            // Zero-length ("hold") segment, any t would do
+            return t0;
+        }
+        if self.linear[idx] {
+            // Constant speed, no need for the lookup table or the root finder
+            return t0 + (t1 - t0) * s / length;
+        }
+        match self.lookup.as_ref().map(|l| &l.segments) {
+            Some(LookupSegments::Eager(segments)) => {
+                if let Some(curve) = &segments[idx] {
+                    return curve.evaluate(s + grid[idx]);
+                }
+            }
+            Some(LookupSegments::Lazy(_)) => {
+                let t = self.lazy_lookup_segment(idx, |segment| {
+                    segment.as_ref().map(|curve| curve.evaluate(s + grid[idx]))
+                });
+                if let Some(Some(t)) = t {
+                    return t;
+                }
+            }
+            None => {}
+        }
+        let node_time = |k: usize| {
+            if k == MEMO_SUBDIVISIONS {
+                t1
+            } else {
+                t0 + (t1 - t0) * k as f32 / MEMO_SUBDIVISIONS as f32
+            }
+        };
+        let nodes = self.memo.get_or_compute(idx, || {
+            let mut nodes = [0.0; MEMO_SUBDIVISIONS - 1];
+            let mut total = 0.0;
+            for (k, node) in nodes.iter_mut().enumerate() {
+                total += self.integrated_speed(idx, node_time(k), node_time(k + 1));
+                *node = total;
+            }
+            nodes
+        });
+        // Part k is between the nodes k - 1 and k (with the segment boundaries at both ends)
+        let k = nodes.iter().take_while(|&&node| node <= s).count();
+        let (ta, tb) = (node_time(k), node_time(k + 1));
+        let sa = if k == 0 { 0.0 } else { nodes[k - 1] };
+        let sb = nodes.get(k).copied().unwrap_or(length);
+        let func = |t| {
+            if k < MEMO_SUBDIVISIONS - 1 {
+                sa + self.integrated_speed(idx, ta, t) - s
+            } else {
+                // Integrating from the end of the segment makes the result
+                // consistent with the segment length (up to rounding errors)
+                length - self.integrated_speed(idx, t, t1) - s
+            }
+        };
+        let root = match root_finder {
+            RootFinder::Bisection => bisect(func, ta, tb, accuracy, max_calls),
+            RootFinder::Brent => brent(func, ta, tb, accuracy, max_calls),
+            RootFinder::Itp => itp(func, ta, tb, accuracy, max_calls),
+            RootFinder::Newton => {
+                // The derivative of the arc length is the speed
+                let func = |t| (func(t), self.inner.evaluate_velocity(t).norm());
+                let guess = if sa < sb {
+                    ta + (tb - ta) * (s - sa) / (sb - sa)
+                } else {
+                    ta
+                };
+                newton_bisect(func, guess, ta, tb, accuracy, max_calls)
+            }
+        };
+        self.statistics.record(&root);
+        root.x
+    }
+}
+
+/// Prefix sum, starting with zero.
+fn cumulative_sum(lengths: impl IntoIterator<Item = f32>) -> Box<[f32]> {
+    let mut result = vec![0.0];
+    for length in lengths {
+        result.push(result.last().unwrap() + length);
+    }
+    result.into()
+}
+
+/// Whether the velocity is the same everywhere within segment `idx`.
+///
+/// The velocity of a cubic segment is a quadratic polynomial,
+/// which is constant if it has the same value at three different points.
+/// Inner points are used to avoid ambiguities at grid values.
+fn has_constant_velocity<Value, Velocity, U>(
+    inner: &impl SplineWithVelocity<Value, Velocity>,
+    idx: usize,
+) -> bool
+where
+    Velocity: Vector + NormWrapper<U>,
+{
+    let t0 = inner.grid()[idx];
+    let t1 = inner.grid()[idx + 1];
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(t0 < t1) {
+        return false;
+    }
+    let velocity = |fraction: f32| inner.evaluate_velocity(t0 + (t1 - t0) * fraction);
+    let middle = velocity(0.5);
+    #[allow(clippy::float_cmp)]
+    let equal = |other: Velocity| (other - middle).norm() == 0.0;
+    equal(velocity(0.25)) && equal(velocity(0.75))
+}
+
+/// Splines with fewer segments are adapted serially by the `par_*` constructors.
+#[cfg(feature = "rayon")]
+const PARALLEL_MIN_SEGMENTS: usize = 16;
+
+#[cfg(feature = "rayon")]
+impl<Value, Velocity, Inner, U> ConstantSpeedAdapter<Value, Velocity, Inner, U>
+where
+    Velocity: Vector + NormWrapper<U>,
+    Inner: SplineWithVelocity<Value, Velocity> + Sync,
+{
+    /// Like [`adapt()`](Self::adapt), but the arc lengths of the segments
+    /// are integrated in parallel.
+    ///
+    /// The results are exactly the same, this only makes a difference
+    /// for splines with many segments (others are adapted serially).
+    pub fn par_adapt(inner: Inner) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        use rayon::prelude::*;
+        if inner.grid().len() - 1 < PARALLEL_MIN_SEGMENTS {
+            return Self::adapt(inner);
+        }
+        let mut result = Self::without_grid(inner);
+        let (inner, linear) = (&result.inner, &result.linear);
+        let quadrature = result.solver.quadrature;
+        let lengths: Vec<_> = inner
+            .grid()
+            .par_windows(2)
+            .enumerate()
+            .map(|(i, ts)| Self::integrate_speed(inner, linear, quadrature, i, ts[0], ts[1]))
+            .collect();
+        result.grid = OnceCell::with_value(cumulative_sum(lengths));
+        result
+    }
+
+    /// Like [`adapt_with_table()`](Self::adapt_with_table),
+    /// but the arc lengths and the segments of the lookup table are computed in parallel.
+    pub fn par_adapt_with_table(
+        inner: Inner,
+        subdivisions: usize,
+    ) -> ConstantSpeedAdapter<Value, Velocity, Inner, U> {
+        use rayon::prelude::*;
+        if inner.grid().len() - 1 < PARALLEL_MIN_SEGMENTS {
+            return Self::adapt_with_table(inner, subdivisions);
+        }
+        let mut result = Self::par_adapt(inner);
+        let (inner, grid, linear) = (&result.inner, result.lengths(), &result.linear);
+        let quadrature = result.solver.quadrature;
+        let segments: Vec<_> = (0..inner.grid().len() - 1)
+            .into_par_iter()
+            .map(|idx| Self::lookup_segment(inner, grid, linear, quadrature, subdivisions, idx))
+            .collect();
+        result.lookup = Some(LookupTable {
+            subdivisions,
+            segments: LookupSegments::Eager(segments.into()),
+        });
+        result
     }
 }
 
@@ -66,7 +568,25 @@ where
     }
 
     fn grid(&self) -> &[f32] {
-        &self.grid
+        self.lengths()
+    }
+}
+
+impl<Value, Velocity, Inner, U> SplineWithVelocity<Value, Velocity>
+    for ConstantSpeedAdapter<Value, Velocity, Inner, U>
+where
+    Velocity: Vector + NormWrapper<U>,
+    Inner: SplineWithVelocity<Value, Velocity>,
+{
+    /// The result has unit length (except in "hold" segments, where it is zero).
+    fn evaluate_velocity(&self, s: f32) -> Velocity {
+        let velocity = self.inner.evaluate_velocity(self.s2t(s));
+        let speed = velocity.norm();
+        if speed == 0.0 {
+            velocity
+        } else {
+            velocity / speed
+        }
     }
 }
 
@@ -134,6 +654,33 @@ where
         })
     }
 
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// The mapping from the new grid to the grid of the inner spline.
+    #[cfg(feature = "serde")]
+    pub(crate) fn t2u(&self) -> &PiecewiseCubicCurve<f32> {
+        &self.t2u
+    }
+
+    /// The inverse of [`inner_ref()`](Self::inner_ref), [`grid()`](Spline::grid)
+    /// and [`t2u()`](Self::t2u), without any checks.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(
+        inner: Inner,
+        grid: Box<[f32]>,
+        t2u: PiecewiseCubicCurve<f32>,
+    ) -> NewGridAdapter<Value, Inner> {
+        NewGridAdapter {
+            inner,
+            grid,
+            t2u,
+            _phantom_output: PhantomData,
+        }
+    }
+
     pub fn adapt_with_speeds(
         inner: Inner,
         new_grid: impl AsRef<[Option<f32>]>,
@@ -192,8 +739,14 @@ where
 
         let mut u_grid = Vec::new();
         let mut u_missing = Vec::new();
-        for (i, &u) in inner.grid().iter().enumerate() {
-            if missing_times.iter().any(|&x| x == i) {
+        let old_grid = inner.grid();
+        for (i, &u) in old_grid.iter().enumerate() {
+            if missing_times.contains(&i) {
+                // NB: A plateau can only be resolved if the neighbors have grid values
+                #[allow(clippy::float_cmp)]
+                if old_grid[i - 1] == u || old_grid[i + 1] == u {
+                    return Err(DuplicateValueWithoutGrid { index: i }.into());
+                }
                 u_missing.push(u);
             } else {
                 u_grid.push(u);
@@ -262,7 +815,10 @@ where
             if let Some(time) = t2u.get_time(u_missing[i]) {
                 grid.insert(missing_times[i], time);
             } else {
-                return Err(DuplicateValueWithoutGrid { index: i }.into());
+                return Err(DuplicateValueWithoutGrid {
+                    index: missing_times[i],
+                }
+                .into());
             }
         }
         let t2u = t2u.into_inner();
@@ -289,3 +845,702 @@ where
         &self.grid
     }
 }
+
+impl<Value, Velocity, Inner> SplineWithVelocity<Value, Velocity> for NewGridAdapter<Value, Inner>
+where
+    Velocity: Vector,
+    Inner: SplineWithVelocity<Value, Velocity>,
+{
+    fn evaluate_velocity(&self, t: f32) -> Velocity {
+        self.inner.evaluate_velocity(self.t2u.evaluate(t)) * self.t2u.evaluate_velocity(t)
+    }
+}
+
+/// Values that can be expressed relative to a parent coordinate frame.
+pub trait Transform {
+    fn transform(self, parent: &Isometry3) -> Self;
+}
+
+impl Transform for Vec3 {
+    fn transform(self, parent: &Isometry3) -> Vec3 {
+        parent.transform_point(&Point3::from(self)).coords
+    }
+}
+
+impl Transform for UnitQuaternion {
+    fn transform(self, parent: &Isometry3) -> UnitQuaternion {
+        parent.rotation * self
+    }
+}
+
+impl Transform for Isometry3 {
+    fn transform(self, parent: &Isometry3) -> Isometry3 {
+        parent * self
+    }
+}
+
+impl<Value, Velocity, Inner, U> Fingerprint for ConstantSpeedAdapter<Value, Velocity, Inner, U>
+where
+    Velocity: Vector + NormWrapper<U>,
+    Inner: SplineWithVelocity<Value, Velocity> + Fingerprint,
+{
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.inner.write_fingerprint(hasher);
+        self.lengths().write_fingerprint(hasher);
+    }
+}
+
+impl<Value, Inner> Fingerprint for NewGridAdapter<Value, Inner>
+where
+    Inner: Fingerprint,
+{
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.inner.write_fingerprint(hasher);
+        self.grid.write_fingerprint(hasher);
+        self.t2u.write_fingerprint(hasher);
+    }
+}
+
+impl<Value, Velocity, Inner, U> MemoryUsage for ConstantSpeedAdapter<Value, Velocity, Inner, U>
+where
+    Inner: MemoryUsage,
+{
+    fn memory_usage(&self) -> usize {
+        let lookup = self
+            .lookup
+            .as_ref()
+            .map_or(0, |lookup| match &lookup.segments {
+                LookupSegments::Eager(segments) => {
+                    size_of_val(&**segments) + segments.memory_usage()
+                }
+                LookupSegments::Lazy(segments) => {
+                    size_of_val(&**segments)
+                        + segments
+                            .iter()
+                            .map(|segment| segment.read().unwrap().memory_usage())
+                            .sum::<usize>()
+                }
+            });
+        self.inner.memory_usage()
+            + self.grid.get().map_or(0, |grid| size_of_val(&**grid))
+            + size_of_val(&*self.linear)
+            + size_of_val(&*self.memo.0)
+            + lookup
+    }
+}
+
+impl<Value, Inner> MemoryUsage for NewGridAdapter<Value, Inner>
+where
+    Inner: MemoryUsage,
+{
+    fn memory_usage(&self) -> usize {
+        self.inner.memory_usage() + size_of_val(&*self.grid) + self.t2u.memory_usage()
+    }
+}
+
+/// Evaluates the inner spline within the (moving) coordinate frame of the parent spline.
+///
+/// The grid is taken from the inner spline, the parent is evaluated at the same times
+/// (which are clipped to the parent's own grid).
+pub struct ParentedAdapter<Value, Inner, Parent> {
+    inner: Inner,
+    parent: Parent,
+    _phantom_output: PhantomData<Value>,
+}
+
+impl<Value, Inner, Parent> ParentedAdapter<Value, Inner, Parent>
+where
+    Value: Transform,
+    Inner: Spline<Value>,
+    Parent: Spline<Isometry3>,
+{
+    pub fn adapt(inner: Inner, parent: Parent) -> ParentedAdapter<Value, Inner, Parent> {
+        ParentedAdapter {
+            inner,
+            parent,
+            _phantom_output: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn parent(&self) -> &Parent {
+        &self.parent
+    }
+}
+
+impl<Value, Inner, Parent> Spline<Value> for ParentedAdapter<Value, Inner, Parent>
+where
+    Value: Transform,
+    Inner: Spline<Value>,
+    Parent: Spline<Isometry3>,
+{
+    fn evaluate(&self, t: f32) -> Value {
+        self.inner.evaluate(t).transform(&self.parent.evaluate(t))
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+/// Rotates the positions of the inner spline around `pivot`,
+/// e.g. to let a whole trajectory turn around the listener.
+///
+/// The grid is taken from the inner spline, the rotation is evaluated at the same times
+/// (which are clipped to the rotation's own grid).
+pub struct RotatedAdapter<Inner, Rotation> {
+    inner: Inner,
+    rotation: Rotation,
+    pivot: Vec3,
+}
+
+impl<Inner, Rotation> RotatedAdapter<Inner, Rotation>
+where
+    Inner: Spline<Vec3>,
+    Rotation: Spline<UnitQuaternion>,
+{
+    pub fn adapt(inner: Inner, rotation: Rotation, pivot: Vec3) -> RotatedAdapter<Inner, Rotation> {
+        RotatedAdapter {
+            inner,
+            rotation,
+            pivot,
+        }
+    }
+
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+
+    #[must_use]
+    pub fn rotation(&self) -> &Rotation {
+        &self.rotation
+    }
+
+    #[must_use]
+    pub fn pivot(&self) -> Vec3 {
+        self.pivot
+    }
+}
+
+impl<Inner, Rotation> Spline<Vec3> for RotatedAdapter<Inner, Rotation>
+where
+    Inner: Spline<Vec3>,
+    Rotation: Spline<UnitQuaternion>,
+{
+    fn evaluate(&self, t: f32) -> Vec3 {
+        self.pivot + self.rotation.evaluate(t) * (self.inner.evaluate(t) - self.pivot)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+impl<Inner, Rotation> SplineWithVelocity<Vec3, Vec3> for RotatedAdapter<Inner, Rotation>
+where
+    Inner: SplineWithVelocity<Vec3, Vec3>,
+    Rotation: SplineWithVelocity<UnitQuaternion, Vec3>,
+{
+    fn evaluate_velocity(&self, t: f32) -> Vec3 {
+        let rotation = self.rotation.evaluate(t);
+        let relative = rotation * (self.inner.evaluate(t) - self.pivot);
+        rotation * self.inner.evaluate_velocity(t)
+            + self.rotation.evaluate_velocity(t).cross(&relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicUsize;
+
+    use crate::asdfposspline::NormF32;
+    use crate::quaternion::{angles2quat, AngularVelocityNorm};
+    use crate::utilities::Tolerance;
+    use crate::{AsdfPoseSpline, PiecewiseCubicCurve};
+
+    #[test]
+    fn parented() {
+        let parent = AsdfPoseSpline::<AngularVelocityNorm>::new(
+            [Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0)],
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(90.0, 0.0, 0.0)],
+            [Some(0.0), Some(2.0)],
+            [None, None],
+            [],
+            false,
+        )
+        .unwrap();
+        let zero = Vec3::zeros();
+        let child =
+            PiecewiseCubicCurve::new([[Vec3::new(1.0, 0.0, 0.0), zero, zero, zero]], [0.0, 2.0])
+                .unwrap();
+        let s = ParentedAdapter::adapt(child, parent);
+        assert!((s.evaluate(0.0) - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-6);
+        assert!((s.evaluate(2.0) - Vec3::new(2.0, 1.0, 0.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn lookup_table() {
+        use crate::quaternion::CubicDeCasteljau;
+        let path = || {
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(
+                [
+                    angles2quat(0.0, 0.0, 0.0),
+                    angles2quat(90.0, 0.0, 0.0),
+                    angles2quat(90.0, 60.0, 20.0),
+                    angles2quat(-30.0, 10.0, 0.0),
+                ],
+                &[[0.0; 3], [0.5, 0.0, 0.0]],
+                false,
+            )
+            .unwrap()
+        };
+        let exact: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt(path());
+        let table: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt_with_table(path(), 16);
+        assert_eq!(exact.grid(), table.grid());
+        let end = *exact.grid().last().unwrap();
+        for i in 0..=100 {
+            let s = end * i as f32 / 100.0;
+            assert!(exact.evaluate(s).angle_to(&table.evaluate(s)) < 1e-3);
+        }
+        let lazy: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt_with_lazy_table(path(), 16);
+        assert_eq!(lazy.grid(), table.grid());
+        let empty = lazy.memory_usage();
+        assert!(empty < table.memory_usage());
+        let s = (lazy.grid()[1] + lazy.grid()[2]) / 2.0;
+        assert_eq!(lazy.evaluate(s), table.evaluate(s));
+        let one = lazy.memory_usage();
+        assert!(one > empty);
+        lazy.prepare(s..s + 1e-3);
+        assert_eq!(lazy.memory_usage(), one);
+        lazy.prepare(0.0..end);
+        let all = lazy.memory_usage();
+        assert!(all > one);
+        for i in 0..=100 {
+            let s = end * i as f32 / 100.0;
+            assert_eq!(lazy.evaluate(s), table.evaluate(s));
+        }
+        assert_eq!(lazy.memory_usage(), all);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_grid() {
+        let positions: Vec<_> = (0..500)
+            .map(|i| {
+                let angle = i as f32 * 0.3;
+                Vec3::new(
+                    crate::math::cos(angle),
+                    crate::math::sin(angle),
+                    i as f32 * 0.01,
+                )
+            })
+            .collect();
+        let curve = || {
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &positions,
+                &vec![[0.0; 3]; positions.len() - 2],
+                false,
+                |v| v.norm(),
+            )
+            .unwrap()
+        };
+        let serial: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt(curve());
+        let parallel: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::par_adapt(curve());
+        assert_eq!(serial.grid(), parallel.grid());
+        let serial: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt_with_table(curve(), 8);
+        let parallel: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::par_adapt_with_table(curve(), 8);
+        let end = *serial.grid().last().unwrap();
+        for i in 0..=1000 {
+            let s = end * i as f32 / 1000.0;
+            assert_eq!(serial.evaluate(s), parallel.evaluate(s));
+        }
+    }
+
+    #[test]
+    fn solvers() {
+        use std::cell::Cell;
+        // Speed varies a lot along the segment
+        let segment = [
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(-1.0, 3.0, 0.5),
+            Vec3::new(4.0, 0.0, 0.0),
+        ];
+        let curve = || PiecewiseCubicCurve::new([segment], [0.0, 3.0]).unwrap();
+        let newton: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt(curve());
+        assert_eq!(newton.solver_options().root_finder, RootFinder::Newton);
+        let bisection: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt(curve())
+                .with_solver_options(SolverOptions {
+                    root_finder: RootFinder::Bisection,
+                    ..SolverOptions::default()
+                })
+                .unwrap();
+        let with_root_finder = |root_finder| {
+            let adapter: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+                ConstantSpeedAdapter::adapt(curve())
+                    .with_solver_options(SolverOptions {
+                        root_finder,
+                        ..SolverOptions::default()
+                    })
+                    .unwrap();
+            adapter
+        };
+        let brent_adapter = with_root_finder(RootFinder::Brent);
+        let itp_adapter = with_root_finder(RootFinder::Itp);
+        let end = *newton.grid().last().unwrap();
+        for i in 0..=20 {
+            let s = end * i as f32 / 20.0;
+            assert!((newton.evaluate(s) - bisection.evaluate(s)).norm() < 1e-3);
+            assert!((brent_adapter.evaluate(s) - bisection.evaluate(s)).norm() < 1e-3);
+            assert!((itp_adapter.evaluate(s) - bisection.evaluate(s)).norm() < 1e-3);
+        }
+        let statistics = newton.solver_statistics();
+        assert_eq!(statistics.roots, 21);
+        assert!(statistics.calls < bisection.solver_statistics().calls);
+        assert!(statistics.max_calls <= SolverOptions::default().max_calls);
+        assert!(statistics.max_residual < 1e-3);
+        // The default tolerance is relative to the segment duration
+        let with_duration = |duration, accuracy| {
+            let curve = PiecewiseCubicCurve::new([segment], [0.0, duration]).unwrap();
+            let adapter: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+                ConstantSpeedAdapter::adapt(curve)
+                    .with_solver_options(SolverOptions {
+                        accuracy,
+                        ..SolverOptions::default()
+                    })
+                    .unwrap();
+            adapter
+        };
+        let relative = SolverOptions::default().accuracy;
+        let short = with_duration(3e-3, relative);
+        let long = with_duration(3e3, relative);
+        let coarse = with_duration(3e-3, Tolerance::Absolute(1e-3));
+        for i in 0..=20 {
+            let s = end * i as f32 / 20.0;
+            assert!((short.evaluate(s) - newton.evaluate(s)).norm() < 1e-3);
+            assert!((long.evaluate(s) - newton.evaluate(s)).norm() < 1e-3);
+            let _ = coarse.evaluate(s);
+        }
+        assert!(short.solver_statistics().max_residual < 1e-3);
+        assert!(long.solver_statistics().max_residual < 1e-3);
+        assert!(coarse.solver_statistics().calls < short.solver_statistics().calls);
+        assert_eq!(Tolerance::Absolute(0.5).absolute(0.0, 1e3), 0.5);
+        assert_eq!(Tolerance::Relative(0.5).absolute(1.0, 3.0), 1.0);
+        newton.reset_solver_statistics();
+        assert_eq!(newton.solver_statistics(), SolverStatistics::default());
+        let calls = Cell::new(0);
+        let f = |x: f32| {
+            calls.set(calls.get() + 1);
+            (x * x * x + x - 3.0, 3.0 * x * x + 1.0)
+        };
+        let root = newton_bisect(f, 0.0, 0.0, 2.0, 1e-6, 50).x;
+        assert!((root * root * root + root - 3.0).abs() < 1e-5);
+        assert!(calls.get() < 10);
+        let calls = Cell::new(0);
+        let f = |x: f32| {
+            calls.set(calls.get() + 1);
+            x * x * x + x - 3.0
+        };
+        let root = brent(f, 0.0, 2.0, 1e-6, 50).x;
+        assert!((root * root * root + root - 3.0).abs() < 1e-5);
+        assert!(calls.get() < 12);
+        let (bisect_calls, brent_calls) = (Cell::new(0), calls.get());
+        let _ = bisect(
+            |x| {
+                bisect_calls.set(bisect_calls.get() + 1);
+                x * x * x + x - 3.0
+            },
+            0.0,
+            2.0,
+            1e-6,
+            50,
+        );
+        assert!(brent_calls < bisect_calls.get());
+        let itp_calls = Cell::new(0);
+        // Decreasing function
+        let root = itp(
+            |x| {
+                itp_calls.set(itp_calls.get() + 1);
+                3.0 - x * x * x - x
+            },
+            0.0,
+            2.0,
+            1e-6,
+            50,
+        );
+        assert_eq!(root.calls, itp_calls.get());
+        assert!(root.residual.abs() < 1e-5);
+        assert!((root.x * root.x * root.x + root.x - 3.0 + root.residual).abs() < 1e-6);
+        assert!(itp_calls.get() < bisect_calls.get());
+    }
+
+    /// Counts the velocity evaluations (used for all arc length integrations).
+    struct Counting {
+        curve: PiecewiseCubicCurve<f32>,
+        calls: AtomicUsize,
+    }
+
+    impl Spline<f32> for Counting {
+        fn evaluate(&self, t: f32) -> f32 {
+            self.curve.evaluate(t)
+        }
+
+        fn grid(&self) -> &[f32] {
+            self.curve.grid()
+        }
+    }
+
+    impl SplineWithVelocity<f32, f32> for Counting {
+        fn evaluate_velocity(&self, t: f32) -> f32 {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            self.curve.evaluate_velocity(t)
+        }
+    }
+
+    fn counting() -> Counting {
+        Counting {
+            curve: PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &[0.0, 3.0, 3.5, -1.0, 2.0],
+                &[[0.0, 0.5, 0.0]; 3],
+                false,
+                |x: &f32| x.abs(),
+            )
+            .unwrap(),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    #[test]
+    fn lazy_adapters_are_not_computed_up_front() {
+        let calls = |adapter: &ConstantSpeedAdapter<_, _, Counting, NormF32>| {
+            adapter.inner_ref().calls.load(Ordering::Relaxed)
+        };
+        let exact = ConstantSpeedAdapter::adapt(counting());
+        let integration = calls(&exact);
+        let table = ConstantSpeedAdapter::adapt_with_table(counting(), 16);
+        let lazy = ConstantSpeedAdapter::adapt_lazy(counting());
+        let lazy_table = ConstantSpeedAdapter::adapt_with_lazy_table(counting(), 16);
+        // Only the segments with constant velocity are detected up front
+        let detection = calls(&lazy);
+        assert_eq!(calls(&lazy_table), detection);
+        assert!(integration > 2 * detection);
+        assert!(calls(&table) > 10 * integration);
+        // The whole grid is computed on first use
+        assert_eq!(lazy.grid(), exact.grid());
+        assert_eq!(calls(&lazy), integration);
+        let s = (exact.grid()[1] + exact.grid()[2]) / 2.0;
+        assert_eq!(lazy.evaluate(s), exact.evaluate(s));
+        assert_eq!(lazy_table.grid(), exact.grid());
+        assert_eq!(calls(&lazy_table), integration);
+        let before = calls(&lazy_table);
+        lazy_table.evaluate(s);
+        let one = calls(&lazy_table) - before;
+        // Roughly one segment of the lookup table
+        assert!(one > 0);
+        assert!(one < calls(&table) - integration);
+    }
+
+    #[test]
+    fn invalid_solver_options() {
+        let curve = || {
+            PiecewiseCubicCurve::new(
+                [[
+                    Vec3::new(1.0, 2.0, 0.0),
+                    Vec3::new(2.0, 0.0, 1.0),
+                    Vec3::new(-1.0, 3.0, 0.5),
+                    Vec3::new(4.0, 0.0, 0.0),
+                ]],
+                [0.0, 3.0],
+            )
+            .unwrap()
+        };
+        let with_options = |options| {
+            ConstantSpeedAdapter::<_, _, _, AngularVelocityNorm>::adapt(curve())
+                .with_solver_options(options)
+        };
+        let options = |root_finder, max_calls| SolverOptions {
+            root_finder,
+            max_calls,
+            ..SolverOptions::default()
+        };
+        for root_finder in [
+            RootFinder::Bisection,
+            RootFinder::Brent,
+            RootFinder::Itp,
+            RootFinder::Newton,
+        ] {
+            for max_calls in [0, 1, 2] {
+                assert!(matches!(
+                    with_options(options(root_finder, max_calls)),
+                    Err(SolverOptionsError::TooFewCalls { .. })
+                ));
+            }
+            let adapter = with_options(options(root_finder, SolverOptions::MIN_CALLS)).unwrap();
+            assert!(adapter.evaluate(1.0).iter().all(|x| x.is_finite()));
+        }
+        for accuracy in [0.0, -1e-5, f32::NAN, f32::INFINITY] {
+            for accuracy in [Tolerance::Relative(accuracy), Tolerance::Absolute(accuracy)] {
+                assert!(matches!(
+                    with_options(SolverOptions {
+                        accuracy,
+                        ..SolverOptions::default()
+                    }),
+                    Err(SolverOptionsError::InvalidAccuracy { .. })
+                ));
+            }
+        }
+        // The root finders themselves don't panic
+        let f = |x: f32| x - 0.3;
+        for max_calls in 0..3 {
+            for root in [
+                bisect(f, 0.0, 1.0, 1e-6, max_calls),
+                brent(f, 0.0, 1.0, 1e-6, max_calls),
+                itp(f, 0.0, 1.0, 1e-6, max_calls),
+            ] {
+                assert!(root.calls <= max_calls.max(2));
+                assert_eq!(root.residual, f(root.x));
+            }
+            let root = newton_bisect(|x| (f(x), 1.0), 0.5, 0.0, 1.0, 1e-6, max_calls);
+            assert_eq!(root.calls, max_calls.max(1));
+        }
+    }
+
+    #[test]
+    fn memo() {
+        let curve = || {
+            PiecewiseCubicCurve::new(
+                [[
+                    Vec3::new(1.0, 2.0, 0.0),
+                    Vec3::new(2.0, 0.0, 1.0),
+                    Vec3::new(-1.0, 3.0, 0.5),
+                    Vec3::new(4.0, 0.0, 0.0),
+                ]],
+                [0.0, 3.0],
+            )
+            .unwrap()
+        };
+        let adapter = || {
+            let adapter: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+                ConstantSpeedAdapter::adapt(curve())
+                    .with_solver_options(SolverOptions {
+                        root_finder: RootFinder::Bisection,
+                        ..SolverOptions::default()
+                    })
+                    .unwrap();
+            adapter
+        };
+        let sweep = adapter();
+        let end = *sweep.grid().last().unwrap();
+        for i in 0..=100 {
+            let s = end * i as f32 / 100.0;
+            // Results don't depend on previous evaluations
+            assert_eq!(sweep.evaluate(s), adapter().evaluate(s));
+        }
+        // Same position before and after other queries
+        let s = end / 3.0;
+        let before = adapter().evaluate(s);
+        for i in 0..20 {
+            let _ = sweep.evaluate(end * i as f32 / 19.0);
+        }
+        assert_eq!(sweep.evaluate(s), before);
+        assert_eq!(sweep.evaluate(s * 1.01), adapter().evaluate(s * 1.01));
+
+        // Later evaluations in the same segment start from the cached nodes
+        let adapter = ConstantSpeedAdapter::<_, _, _, NormF32>::adapt(counting());
+        let calls = || adapter.inner_ref().calls.load(Ordering::Relaxed);
+        let (a, b) = (adapter.grid()[1], adapter.grid()[2]);
+        let before = calls();
+        adapter.evaluate(a + (b - a) * 0.3);
+        let first = calls() - before;
+        adapter.evaluate(a + (b - a) * 0.7);
+        let second = calls() - before - first;
+        assert!(second < first);
+    }
+
+    #[test]
+    fn quadrature() {
+        use crate::utilities::Quadrature;
+        let zero = Vec3::zeros;
+        let curve = || {
+            PiecewiseCubicCurve::new(
+                [
+                    [
+                        Vec3::new(1.0, 2.0, 0.0),
+                        Vec3::new(2.0, 0.0, 1.0),
+                        zero(),
+                        zero(),
+                    ],
+                    [
+                        Vec3::new(3.0, 2.0, 1.0),
+                        Vec3::new(-1.0, 3.0, 0.5),
+                        Vec3::new(4.0, 0.0, 0.0),
+                        Vec3::new(0.0, 1.0, -2.0),
+                    ],
+                ],
+                [0.0, 1.0, 3.0],
+            )
+            .unwrap()
+        };
+        let default: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+            ConstantSpeedAdapter::adapt_with_table(curve(), 8);
+        let with_quadrature = |quadrature| {
+            let adapter: ConstantSpeedAdapter<_, _, _, AngularVelocityNorm> =
+                ConstantSpeedAdapter::adapt_with_table(curve(), 8)
+                    .with_solver_options(SolverOptions {
+                        quadrature,
+                        ..SolverOptions::default()
+                    })
+                    .unwrap();
+            adapter
+        };
+        let low = with_quadrature(Quadrature::GaussLegendre5);
+        let high = with_quadrature(Quadrature::GaussLegendre21);
+        let romberg = with_quadrature(Quadrature::Romberg);
+        let end = *default.grid().last().unwrap();
+        assert_ne!(low.grid(), default.grid());
+        assert!((high.grid()[2] - end).abs() < 1e-5 * end);
+        assert!((low.grid()[2] - end).abs() < 1e-2 * end);
+        assert!((romberg.grid()[2] - end).abs() < 1e-4 * end);
+        for i in 0..=10 {
+            let s = end * i as f32 / 10.0;
+            assert!((high.evaluate(s) - default.evaluate(s)).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn rotated() {
+        use crate::quaternion::PiecewiseSlerp;
+        let zero = Vec3::zeros();
+        // Standing still at one meter in front of the pivot
+        let still =
+            PiecewiseCubicCurve::new([[Vec3::new(1.0, 2.0, 0.0), zero, zero, zero]], [0.0, 4.0])
+                .unwrap();
+        let turning = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(90.0, 0.0, 0.0)],
+            [0.0, 2.0],
+            false,
+        )
+        .unwrap();
+        let s = RotatedAdapter::adapt(still, turning, Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(s.grid(), [0.0, 4.0]);
+        assert!((s.evaluate(0.0) - Vec3::new(1.0, 2.0, 0.0)).norm() < 1e-6);
+        assert!((s.evaluate(2.0) - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+        // Rotation is clipped
+        assert!((s.evaluate(4.0) - Vec3::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+        let speed = 45.0f32.to_radians();
+        let expected = Vec3::new(-speed, 0.0, 0.0);
+        assert!((s.evaluate_velocity(0.0) - expected).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,142 @@
+use nalgebra::Translation3;
+
+use crate::memory::MemoryUsage;
+use crate::quaternion::{Isometry3, UnitQuaternion, Vec3};
+use crate::{AsdfPosSpline, AsdfRotSpline, NormWrapper, Spline};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("number of quaternions ({quaternions}) and positions ({positions}) must be the same")]
+    QuaternionsVsPositions {
+        quaternions: usize,
+        positions: usize,
+    },
+    #[error(transparent)]
+    FromPosError(#[from] crate::asdfposspline::Error),
+    #[error(transparent)]
+    FromRotError(#[from] crate::asdfrotspline::Error),
+}
+
+/// Position and rotation with a shared grid.
+///
+/// Missing times are obtained from the position spline
+/// and used for the rotation spline.
+pub struct AsdfPoseSpline<U> {
+    position: AsdfPosSpline<Vec3, U>,
+    rotation: AsdfRotSpline,
+}
+
+impl<U> AsdfPoseSpline<U>
+where
+    Vec3: NormWrapper<U>,
+{
+    pub fn new(
+        positions: impl AsRef<[Vec3]>,
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfPoseSpline<U>, Error> {
+        let positions = positions.as_ref();
+        let quaternions = quaternions.into();
+        let tcb = tcb.as_ref();
+        if quaternions.len() != positions.len() {
+            return Err(Error::QuaternionsVsPositions {
+                quaternions: quaternions.len(),
+                positions: positions.len(),
+            });
+        }
+        let position = AsdfPosSpline::new(positions, times, speeds, tcb, closed)?;
+        let times: Vec<_> = position.grid().iter().copied().map(Some).collect();
+        let rotation = AsdfRotSpline::new(quaternions, times, tcb, closed)?;
+        Ok(AsdfPoseSpline { position, rotation })
+    }
+
+    #[must_use]
+    pub fn position(&self) -> &AsdfPosSpline<Vec3, U> {
+        &self.position
+    }
+
+    #[must_use]
+    pub fn rotation(&self) -> &AsdfRotSpline {
+        &self.rotation
+    }
+}
+
+impl<U> Spline<Isometry3> for AsdfPoseSpline<U>
+where
+    Vec3: NormWrapper<U>,
+{
+    fn evaluate(&self, t: f32) -> Isometry3 {
+        Isometry3::from_parts(
+            Translation3::from(self.position.evaluate(t)),
+            self.rotation.evaluate(t),
+        )
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.position.grid()
+    }
+}
+
+impl<U> MemoryUsage for AsdfPoseSpline<U>
+where
+    Vec3: NormWrapper<U>,
+{
+    fn memory_usage(&self) -> usize {
+        self.position.memory_usage() + self.rotation.memory_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, AngularVelocityNorm};
+
+    #[test]
+    fn shared_times() {
+        let s = AsdfPoseSpline::<AngularVelocityNorm>::new(
+            [
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(3.0, 0.0, 0.0),
+            ],
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(10.0, 0.0, 0.0),
+                angles2quat(20.0, 0.0, 0.0),
+            ],
+            [Some(0.0), None, Some(3.0)],
+            [None, None, None],
+            [[0.0, 0.0, 0.0]],
+            false,
+        )
+        .unwrap();
+        assert_eq!(s.grid(), s.rotation().grid());
+        let middle = s.grid()[1];
+        let pose = s.evaluate(middle);
+        assert!((pose.translation.vector - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-4);
+        assert!(pose.rotation.angle_to(&angles2quat(10.0, 0.0, 0.0)) < 1e-3);
+    }
+
+    #[test]
+    fn quaternions_vs_positions() {
+        let result = AsdfPoseSpline::<AngularVelocityNorm>::new(
+            [Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)],
+            [angles2quat(0.0, 0.0, 0.0)],
+            [Some(0.0), Some(1.0)],
+            [None, None],
+            [],
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::QuaternionsVsPositions {
+                quaternions: 1,
+                positions: 2
+            })
+        ));
+    }
+}
@@ -1,5 +1,11 @@
+use std::marker::PhantomData;
+use std::mem::size_of_val;
+
 use crate::adapters::{ConstantSpeedAdapter, NewGridAdapter};
-use crate::{NormWrapper, PiecewiseCubicCurve, Vector};
+use crate::memory::MemoryUsage;
+use crate::motionlimits::{check_motion_limits, LimitViolation, MotionLimits};
+use crate::utilities::SolverStatistics;
+use crate::{NormWrapper, PiecewiseCubicCurve, Spline, SplineWithVelocity, Vector};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -17,6 +23,12 @@ pub enum Error {
     },
     #[error("number of speeds ({speeds}) and positions ({positions}) must be the same")]
     SpeedsVsPositions { speeds: usize, positions: usize },
+    #[error("number of waits ({waits}) and positions ({positions}) must be the same")]
+    WaitsVsPositions { waits: usize, positions: usize },
+    #[error("index {index}: waiting time is only allowed if time is given")]
+    WaitWithoutTime { index: usize },
+    #[error("index {index}: waiting time must be a non-negative number (not {wait:?})")]
+    InvalidWait { index: usize, wait: f32 },
     #[error("index {index}: speed is only allowed if time is given")]
     SpeedWithoutTime { index: usize },
     #[error("first time value must be specified")]
@@ -26,6 +38,10 @@ pub enum Error {
     // TODO: two indices?
     #[error("index {index}: duplicate position without time")]
     DuplicatePositionWithoutTime { index: usize },
+    #[error("index {index}: distance to next position must be finite")]
+    NonFiniteDistance { index: usize },
+    #[error("index {index}: distance to next position is too small")]
+    DistanceTooSmall { index: usize },
     #[error("number of positions ({positions}) must be {} TCB values ({tcb})", if *.closed {
         "the same as"
     } else {
@@ -36,8 +52,6 @@ pub enum Error {
         positions: usize,
         closed: bool,
     },
-    #[error("repeated position (at index {index}) is not allowed")]
-    RepeatedPosition { index: usize },
     #[error("index {index}: time values are not allowed to be NaN")]
     TimeNan { index: usize },
     #[error("index {index}: time values must be strictly ascending")]
@@ -52,8 +66,29 @@ pub enum Error {
     NegativeSpeed { index: usize, speed: f32 },
 }
 
-pub type AsdfPosSpline<V, U> =
-    NewGridAdapter<V, ConstantSpeedAdapter<V, V, PiecewiseCubicCurve<V>, U>>;
+/// Norm for one-dimensional splines, i.e. the absolute value.
+pub struct NormF32;
+
+impl NormWrapper<NormF32> for f32 {
+    fn norm(&self) -> f32 {
+        self.abs()
+    }
+}
+
+/// One-dimensional spline, e.g. for automating a single parameter.
+pub type AsdfPosSpline1 = AsdfPosSpline<f32, NormF32>;
+
+/// Keeps a copy of the original waypoint data,
+/// see [`positions()`](Self::positions) and the other accessor methods.
+pub struct AsdfPosSpline<V, U> {
+    inner: NewGridAdapter<V, ConstantSpeedAdapter<V, V, PiecewiseCubicCurve<V>, U>>,
+    positions: Box<[V]>,
+    times: Box<[Option<f32>]>,
+    speeds: Box<[Option<f32>]>,
+    waits: Box<[f32]>,
+    tcb: Box<[[f32; 3]]>,
+    closed: bool,
+}
 
 impl<V, U> AsdfPosSpline<V, U>
 where
@@ -66,11 +101,92 @@ where
         tcb: impl AsRef<[[f32; 3]]>,
         closed: bool,
     ) -> Result<AsdfPosSpline<V, U>, Error> {
-        use Error::*;
         let positions = positions.as_ref();
-        let times = times.as_ref();
-        let speeds = speeds.as_ref();
-        let tcb = tcb.as_ref();
+        let waits = vec![0.0; positions.len()];
+        AsdfPosSpline::with_waits(positions, times, speeds, waits, tcb, closed)
+    }
+
+    /// Like [`new()`](Self::new), but the arc lengths are integrated in parallel,
+    /// see [`ConstantSpeedAdapter::par_adapt()`].
+    #[cfg(feature = "rayon")]
+    pub fn par_new(
+        positions: impl AsRef<[V]>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfPosSpline<V, U>, Error>
+    where
+        V: Sync,
+    {
+        let positions = positions.as_ref();
+        let waits = vec![0.0; positions.len()];
+        AsdfPosSpline::par_with_waits(positions, times, speeds, waits, tcb, closed)
+    }
+
+    /// Like [`new()`](Self::new), but with a waiting time after reaching each position.
+    ///
+    /// A time value must be given for each position with a (non-zero) waiting time.
+    /// This time specifies the arrival, motion resumes after the waiting time.
+    pub fn with_waits(
+        positions: impl AsRef<[V]>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        waits: impl AsRef<[f32]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfPosSpline<V, U>, Error> {
+        AsdfPosSpline::with_adapter(
+            positions.as_ref(),
+            times.as_ref(),
+            speeds.as_ref(),
+            waits.as_ref(),
+            tcb.as_ref(),
+            closed,
+            ConstantSpeedAdapter::adapt,
+        )
+    }
+
+    /// Like [`with_waits()`](Self::with_waits), but the arc lengths are integrated in parallel,
+    /// see [`ConstantSpeedAdapter::par_adapt()`].
+    #[cfg(feature = "rayon")]
+    pub fn par_with_waits(
+        positions: impl AsRef<[V]>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        waits: impl AsRef<[f32]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfPosSpline<V, U>, Error>
+    where
+        V: Sync,
+    {
+        AsdfPosSpline::with_adapter(
+            positions.as_ref(),
+            times.as_ref(),
+            speeds.as_ref(),
+            waits.as_ref(),
+            tcb.as_ref(),
+            closed,
+            ConstantSpeedAdapter::par_adapt,
+        )
+    }
+
+    fn with_adapter(
+        positions: &[V],
+        times: &[Option<f32>],
+        speeds: &[Option<f32>],
+        waits: &[f32],
+        tcb: &[[f32; 3]],
+        closed: bool,
+        adapt: impl FnOnce(
+            PiecewiseCubicCurve<V>,
+        ) -> ConstantSpeedAdapter<V, V, PiecewiseCubicCurve<V>, U>,
+    ) -> Result<AsdfPosSpline<V, U>, Error> {
+        use Error::*;
+        if positions.len() < 2 {
+            return Err(LessThanTwoPositions);
+        }
         if positions.len() + closed as usize != times.len() {
             return Err(TimesVsPositions {
                 times: times.len(),
@@ -84,30 +200,90 @@ where
                 positions: positions.len(),
             });
         }
+        if waits.len() != positions.len() {
+            return Err(WaitsVsPositions {
+                waits: waits.len(),
+                positions: positions.len(),
+            });
+        }
+        if tcb.len() + 2 * !closed as usize != positions.len() {
+            return Err(TcbVsPositions {
+                tcb: tcb.len(),
+                positions: positions.len(),
+                closed,
+            });
+        }
+        // Each waiting position is repeated, which leads to a "hold" segment.
+        // This maps the indices of the expanded arrays to the original ones:
+        let mut indices = Vec::with_capacity(positions.len());
+        for (index, &wait) in waits.iter().enumerate() {
+            indices.push(index);
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(wait >= 0.0) {
+                return Err(InvalidWait { index, wait });
+            } else if wait > 0.0 {
+                if times[index].is_none() {
+                    return Err(WaitWithoutTime { index });
+                }
+                indices.push(index);
+            }
+        }
+        let original_index = |index: usize| indices.get(index).copied().unwrap_or(positions.len());
+        let mut expanded_positions = Vec::with_capacity(indices.len());
+        let mut expanded_times = Vec::with_capacity(indices.len() + closed as usize);
+        let mut expanded_speeds = Vec::with_capacity(indices.len());
+        let mut expanded_tcb = Vec::with_capacity(indices.len());
+        for (i, &index) in indices.iter().enumerate() {
+            expanded_positions.push(positions[index]);
+            if i > 0 && indices[i - 1] == index {
+                expanded_times.push(times[index].map(|t| t + waits[index]));
+            } else {
+                expanded_times.push(times[index]);
+            }
+            expanded_speeds.push(speeds[index]);
+            if closed {
+                expanded_tcb.push(tcb[index]);
+            } else if 0 < i && i < indices.len() - 1 {
+                // NB: TCB values of repeated positions (including ends) are ignored anyway
+                expanded_tcb.push(
+                    index
+                        .checked_sub(1)
+                        .and_then(|i| tcb.get(i))
+                        .copied()
+                        .unwrap_or_default(),
+                );
+            }
+        }
+        if closed {
+            expanded_times.push(*times.last().unwrap());
+        }
         let path = PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
-            positions,
-            tcb,
+            &expanded_positions,
+            &expanded_tcb,
             closed,
             NormWrapper::norm,
         )
         .map_err(|e| {
             use crate::centripetalkochanekbartelsspline::Error as E;
             match e {
-                E::LessThanTwoPositions => LessThanTwoPositions,
-                E::RepeatedPosition { index } => RepeatedPosition { index },
-                E::TcbVsPositions {
-                    tcb,
-                    positions,
-                    closed,
-                } => TcbVsPositions {
-                    tcb,
-                    positions,
-                    closed,
+                E::LessThanTwoPositions => unreachable!(),
+                E::TcbVsPositions { .. } => unreachable!(),
+                E::NonFiniteDistance { index } => NonFiniteDistance {
+                    index: original_index(index),
+                },
+                E::DistanceTooSmall { index } => DistanceTooSmall {
+                    index: original_index(index),
                 },
             }
         })?;
-        let constant_speed = ConstantSpeedAdapter::adapt(path);
-        NewGridAdapter::adapt_with_speeds(constant_speed, times, speeds, closed).map_err(|e| {
+        let constant_speed = adapt(path);
+        let inner = NewGridAdapter::adapt_with_speeds(
+            constant_speed,
+            expanded_times,
+            expanded_speeds,
+            closed,
+        )
+        .map_err(|e| {
             use crate::adapters::NewGridWithSpeedsError as E;
             match e {
                 E::FromNewGridError(e) => {
@@ -115,58 +291,368 @@ where
                     match e {
                         E::FirstGridMissing => FirstTimeMissing,
                         E::LastGridMissing => LastTimeMissing,
-                        E::DuplicateValueWithoutGrid { index } => {
-                            DuplicatePositionWithoutTime { index }
-                        }
+                        E::DuplicateValueWithoutGrid { index } => DuplicatePositionWithoutTime {
+                            index: original_index(index),
+                        },
                         E::NewGridVsOldGrid { .. } => unreachable!(),
                         E::FromGridError(e) => {
                             use crate::utilities::GridError as E;
                             match e {
-                                E::GridNan { index } => TimeNan { index },
-                                E::GridNotAscending { index } => TimesNotAscending { index },
+                                E::GridNan { index } => TimeNan {
+                                    index: original_index(index),
+                                },
+                                E::GridNotAscending { index } => TimesNotAscending {
+                                    index: original_index(index),
+                                },
                             }
                         }
                     }
                 }
-                E::SpeedWithoutGrid { index } => SpeedWithoutTime { index },
+                E::SpeedWithoutGrid { index } => SpeedWithoutTime {
+                    index: original_index(index),
+                },
                 E::TooFast {
                     index,
                     speed,
                     maximum,
                 } => TooFast {
-                    index,
+                    index: original_index(index),
                     speed,
                     maximum,
                 },
-                E::NegativeSpeed { index, speed } => NegativeSpeed { index, speed },
+                E::NegativeSpeed { index, speed } => NegativeSpeed {
+                    index: original_index(index),
+                    speed,
+                },
                 E::GridVsSpeeds { .. } => unreachable!(),
             }
+        })?;
+        Ok(AsdfPosSpline {
+            inner,
+            positions: positions.into(),
+            times: times.into(),
+            speeds: speeds.into(),
+            waits: waits.into(),
+            tcb: tcb.into(),
+            closed,
         })
     }
+
+    #[must_use]
+    pub fn start_time(&self) -> f32 {
+        *self.grid().first().unwrap()
+    }
+
+    #[must_use]
+    pub fn end_time(&self) -> f32 {
+        *self.grid().last().unwrap()
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.end_time() - self.start_time()
+    }
+
+    /// See [`check_motion_limits()`].
+    #[must_use]
+    pub fn validate(&self, limits: &MotionLimits) -> Vec<LimitViolation> {
+        check_motion_limits(self, limits)
+    }
+
+    #[must_use]
+    pub fn positions(&self) -> &[V] {
+        &self.positions
+    }
+
+    /// Times as given to the constructor, see [`grid()`](Spline::grid) for all times.
+    #[must_use]
+    pub fn times(&self) -> &[Option<f32>] {
+        &self.times
+    }
+
+    #[must_use]
+    pub fn speeds(&self) -> &[Option<f32>] {
+        &self.speeds
+    }
+
+    #[must_use]
+    pub fn waits(&self) -> &[f32] {
+        &self.waits
+    }
+
+    #[must_use]
+    pub fn tcb(&self) -> &[[f32; 3]] {
+        &self.tcb
+    }
+
+    #[must_use]
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    /// See [`ConstantSpeedAdapter::solver_statistics()`].
+    #[must_use]
+    pub fn solver_statistics(&self) -> SolverStatistics {
+        self.inner.inner_ref().solver_statistics()
+    }
+
+    #[must_use]
+    pub fn builder() -> AsdfPosSplineBuilder<V, U> {
+        AsdfPosSplineBuilder::new()
+    }
 }
 
-#[cfg(test)]
-#[allow(clippy::float_cmp)]
-mod tests {
-    use super::*;
+impl<V, U> Spline<V> for AsdfPosSpline<V, U>
+where
+    V: Vector + NormWrapper<U>,
+{
+    fn evaluate(&self, t: f32) -> V {
+        self.inner.evaluate(t)
+    }
 
-    use crate::Spline; // for evaluate()
+    /// Contains the arrival *and* departure times of waypoints with waiting time.
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
 
-    struct NormF32;
+impl<V, U> SplineWithVelocity<V, V> for AsdfPosSpline<V, U>
+where
+    V: Vector + NormWrapper<U>,
+{
+    fn evaluate_velocity(&self, t: f32) -> V {
+        self.inner.evaluate_velocity(t)
+    }
+}
+
+impl<V, U> MemoryUsage for AsdfPosSpline<V, U> {
+    fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
+            + size_of_val(&*self.positions)
+            + size_of_val(&*self.times)
+            + size_of_val(&*self.speeds)
+            + size_of_val(&*self.waits)
+            + size_of_val(&*self.tcb)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BuilderError {
+    #[error(transparent)]
+    FromError(#[from] Error),
+    #[error("{method}() must be preceded by position()")]
+    MissingPosition { method: &'static str },
+    #[error("{method}() is not allowed after closed()")]
+    AfterClosed { method: &'static str },
+    #[error(
+        "index {index}: first and last position cannot have TCB values (except for closed splines)"
+    )]
+    TcbAtEndpoint { index: usize },
+}
+
+/// Collects waypoints one by one, errors are reported by [`build()`](Self::build).
+///
+/// Each waypoint is started with [`position()`](Self::position),
+/// all other methods modify the most recently added waypoint.
+/// After [`closed()`](Self::closed), only [`at_time()`](Self::at_time)
+/// is allowed, which specifies the time when the first position is reached again.
+pub struct AsdfPosSplineBuilder<V, U> {
+    positions: Vec<V>,
+    times: Vec<Option<f32>>,
+    speeds: Vec<Option<f32>>,
+    waits: Vec<f32>,
+    tcb: Vec<[f32; 3]>,
+    closed: bool,
+    error: Option<BuilderError>,
+    _phantom_dummy: PhantomData<U>,
+}
+
+impl<V, U> Default for AsdfPosSplineBuilder<V, U> {
+    fn default() -> Self {
+        AsdfPosSplineBuilder {
+            positions: Vec::new(),
+            times: Vec::new(),
+            speeds: Vec::new(),
+            waits: Vec::new(),
+            tcb: Vec::new(),
+            closed: false,
+            error: None,
+            _phantom_dummy: PhantomData,
+        }
+    }
+}
+
+impl<V, U> AsdfPosSplineBuilder<V, U>
+where
+    V: Vector + NormWrapper<U>,
+{
+    #[must_use]
+    pub fn new() -> AsdfPosSplineBuilder<V, U> {
+        Self::default()
+    }
 
-    impl NormWrapper<NormF32> for f32 {
-        fn norm(&self) -> f32 {
-            self.abs()
+    /// Only the first error is kept.
+    fn fail(&mut self, error: BuilderError) {
+        if self.error.is_none() {
+            self.error = Some(error);
         }
     }
 
-    type AsdfPosSpline1 = AsdfPosSpline<f32, NormF32>;
+    /// Returns `false` (and stores an error) if there is no current waypoint.
+    fn check_waypoint(&mut self, method: &'static str) -> bool {
+        use BuilderError::*;
+        if self.closed {
+            self.fail(AfterClosed { method });
+            false
+        } else if self.positions.is_empty() {
+            self.fail(MissingPosition { method });
+            false
+        } else {
+            true
+        }
+    }
+
+    fn modify_tcb(mut self, method: &'static str, index: usize, value: f32) -> Self {
+        if self.check_waypoint(method) {
+            self.tcb.last_mut().unwrap()[index] = value;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn position(mut self, position: V) -> Self {
+        if self.closed {
+            self.fail(BuilderError::AfterClosed { method: "position" });
+        } else {
+            self.positions.push(position);
+            self.times.push(None);
+            self.speeds.push(None);
+            self.waits.push(0.0);
+            self.tcb.push([0.0, 0.0, 0.0]);
+        }
+        self
+    }
+
+    /// After [`closed()`](Self::closed), this sets the end time of the closed spline.
+    #[must_use]
+    pub fn at_time(mut self, time: f32) -> Self {
+        if self.positions.is_empty() {
+            self.fail(BuilderError::MissingPosition { method: "at_time" });
+        } else {
+            *self.times.last_mut().unwrap() = Some(time);
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        if self.check_waypoint("with_speed") {
+            *self.speeds.last_mut().unwrap() = Some(speed);
+        }
+        self
+    }
+
+    /// Requires [`at_time()`](Self::at_time), see [`AsdfPosSpline::with_waits()`].
+    #[must_use]
+    pub fn wait(mut self, duration: f32) -> Self {
+        if self.check_waypoint("wait") {
+            *self.waits.last_mut().unwrap() = duration;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn tension(self, tension: f32) -> Self {
+        self.modify_tcb("tension", 0, tension)
+    }
+
+    #[must_use]
+    pub fn continuity(self, continuity: f32) -> Self {
+        self.modify_tcb("continuity", 1, continuity)
+    }
+
+    #[must_use]
+    pub fn bias(self, bias: f32) -> Self {
+        self.modify_tcb("bias", 2, bias)
+    }
+
+    /// Connects the last position back to the first one.
+    #[must_use]
+    pub fn closed(mut self) -> Self {
+        if self.check_waypoint("closed") {
+            self.closed = true;
+            self.times.push(None);
+        }
+        self
+    }
+
+    /// The resulting spline doesn't keep any excess capacity of the builder.
+    pub fn build(self) -> Result<AsdfPosSpline<V, U>, BuilderError> {
+        self.build_with(ConstantSpeedAdapter::adapt)
+    }
+
+    /// Like [`build()`](Self::build), but the arc lengths are integrated in parallel,
+    /// see [`ConstantSpeedAdapter::par_adapt()`].
+    #[cfg(feature = "rayon")]
+    pub fn par_build(self) -> Result<AsdfPosSpline<V, U>, BuilderError>
+    where
+        V: Sync,
+    {
+        self.build_with(ConstantSpeedAdapter::par_adapt)
+    }
+
+    fn build_with(
+        self,
+        adapt: impl FnOnce(
+            PiecewiseCubicCurve<V>,
+        ) -> ConstantSpeedAdapter<V, V, PiecewiseCubicCurve<V>, U>,
+    ) -> Result<AsdfPosSpline<V, U>, BuilderError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut tcb = self.tcb;
+        if !self.closed && !tcb.is_empty() {
+            let last = tcb.len() - 1;
+            for index in [0, last] {
+                if tcb[index] != [0.0, 0.0, 0.0] {
+                    return Err(BuilderError::TcbAtEndpoint { index });
+                }
+            }
+            tcb.pop();
+            if !tcb.is_empty() {
+                tcb.remove(0);
+            }
+        }
+        Ok(AsdfPosSpline::with_adapter(
+            &self.positions,
+            &self.times,
+            &self.speeds,
+            &self.waits,
+            &tcb,
+            self.closed,
+            adapt,
+        )?)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
 
     #[test]
     fn simple_linear() {
         let s = AsdfPosSpline1::new([1.0, 2.0], [Some(0.0), Some(3.0)], [None, None], [], false)
             .unwrap();
         assert_eq!(s.evaluate(1.5), 1.5);
+        assert!((s.evaluate_velocity(1.5) - 1.0 / 3.0).abs() < 1e-6);
+        // Straight lines don't need a root finder
+        assert_eq!(s.solver_statistics().roots, 0);
+        let limits = MotionLimits {
+            speed: Some(0.3),
+            ..Default::default()
+        };
+        assert_eq!(s.validate(&limits).len(), 1);
     }
 
     #[test]
@@ -194,4 +680,159 @@ mod tests {
         .unwrap();
         assert_eq!(s.evaluate(4.0), 2.0);
     }
+
+    #[test]
+    fn waypoint_data() {
+        let s = AsdfPosSpline1::new(
+            [1.0, 2.0, 3.0],
+            [Some(0.0), None, Some(3.0)],
+            [None, Some(1.0), None],
+            [[0.5, 0.0, 0.0]],
+            false,
+        );
+        // NB: speed is not allowed without time
+        assert!(s.is_err());
+        let s = AsdfPosSpline1::new(
+            [1.0, 2.0, 3.0],
+            [Some(0.0), None, Some(3.0)],
+            [Some(0.5), None, None],
+            [[0.5, 0.0, 0.0]],
+            false,
+        )
+        .unwrap();
+        assert_eq!(s.positions(), &[1.0, 2.0, 3.0]);
+        assert_eq!(s.times(), &[Some(0.0), None, Some(3.0)]);
+        assert_eq!(s.speeds(), &[Some(0.5), None, None]);
+        assert_eq!(s.waits(), &[0.0, 0.0, 0.0]);
+        assert_eq!(s.tcb(), &[[0.5, 0.0, 0.0]]);
+        assert!(!s.closed());
+        assert_eq!(s.start_time(), 0.0);
+        assert_eq!(s.end_time(), 3.0);
+        assert_eq!(s.duration(), 3.0);
+    }
+
+    #[test]
+    fn pause() {
+        let s = AsdfPosSpline1::new(
+            [1.0, 2.0, 2.0, 3.0],
+            [Some(0.0), Some(1.0), Some(2.0), Some(3.0)],
+            [None, None, None, None],
+            [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+            false,
+        )
+        .unwrap();
+        assert_eq!(s.evaluate(1.0), 2.0);
+        assert_eq!(s.evaluate(1.5), 2.0);
+        assert_eq!(s.evaluate(2.0), 2.0);
+
+        let result = AsdfPosSpline1::new(
+            [1.0, 2.0, 2.0, 3.0],
+            [Some(0.0), Some(1.0), None, Some(3.0)],
+            [None, None, None, None],
+            [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+            false,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::DuplicatePositionWithoutTime { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn waits() {
+        let s = AsdfPosSpline1::with_waits(
+            [1.0, 2.0, 3.0],
+            [Some(0.0), Some(1.0), Some(3.0)],
+            [None, None, None],
+            [0.0, 1.0, 0.0],
+            [[0.0, 0.0, 0.0]],
+            false,
+        )
+        .unwrap();
+        assert_eq!(s.grid(), &[0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(s.evaluate(1.5), 2.0);
+        assert_eq!(s.evaluate(2.0), 2.0);
+
+        let result = AsdfPosSpline1::with_waits(
+            [1.0, 2.0, 3.0],
+            [Some(0.0), None, Some(3.0)],
+            [None, None, None],
+            [0.0, 1.0, 0.0],
+            [[0.0, 0.0, 0.0]],
+            false,
+        );
+        assert!(matches!(result, Err(Error::WaitWithoutTime { index: 1 })));
+        let result = AsdfPosSpline1::with_waits(
+            [1.0, 2.0, 3.0],
+            [Some(0.0), Some(1.0), Some(1.5)],
+            [None, None, None],
+            [0.0, 1.0, 0.0],
+            [[0.0, 0.0, 0.0]],
+            false,
+        );
+        assert!(matches!(result, Err(Error::TimesNotAscending { index: 2 })));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel() {
+        let positions: Vec<_> = (0..100)
+            .map(|i| (i % 7) as f32 * 0.5 + (i % 3) as f32)
+            .collect();
+        let mut times = vec![None; positions.len()];
+        times[0] = Some(0.0);
+        times[99] = Some(50.0);
+        let speeds = vec![None; positions.len()];
+        let tcb = vec![[0.0, 0.0, 0.0]; positions.len() - 2];
+        let serial = AsdfPosSpline1::new(&positions, &times, &speeds, &tcb, false).unwrap();
+        let parallel = AsdfPosSpline1::par_new(&positions, &times, &speeds, &tcb, false).unwrap();
+        assert_eq!(serial.grid(), parallel.grid());
+        let mut builder = AsdfPosSpline1::builder();
+        for (&position, &time) in positions.iter().zip(&times) {
+            builder = builder.position(position);
+            if let Some(time) = time {
+                builder = builder.at_time(time);
+            }
+        }
+        assert_eq!(builder.par_build().unwrap().grid(), serial.grid());
+    }
+
+    #[test]
+    fn builder() {
+        let s = AsdfPosSpline1::builder()
+            .position(1.0)
+            .at_time(0.0)
+            .position(2.0)
+            .closed()
+            .at_time(3.0)
+            .build()
+            .unwrap();
+        assert_eq!(s.evaluate(1.5), 2.0);
+
+        let result = AsdfPosSpline1::builder().at_time(0.0).position(1.0).build();
+        assert!(matches!(
+            result,
+            Err(BuilderError::MissingPosition { method: "at_time" })
+        ));
+        let result = AsdfPosSpline1::builder()
+            .position(1.0)
+            .at_time(0.0)
+            .tension(0.5)
+            .position(2.0)
+            .at_time(1.0)
+            .build();
+        assert!(matches!(
+            result,
+            Err(BuilderError::TcbAtEndpoint { index: 0 })
+        ));
+        let result = AsdfPosSpline1::builder()
+            .position(1.0)
+            .position(2.0)
+            .at_time(1.0)
+            .build();
+        assert!(matches!(
+            result,
+            Err(BuilderError::FromError(Error::FirstTimeMissing))
+        ));
+    }
 }
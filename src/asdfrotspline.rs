@@ -1,5 +1,8 @@
 use crate::adapters::{ConstantSpeedAdapter, NewGridAdapter};
-use crate::quaternion::{AngularVelocityNorm, CubicDeCasteljau, UnitQuaternion, Vec3};
+use crate::quaternion::{
+    angles2quat, AngularVelocityNorm, CubicDeCasteljau, LogQuaternionSpline, UnitQuaternion, Vec3,
+};
+use crate::{Spline, SplineWithVelocity};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -37,8 +40,27 @@ pub enum Error {
     },
     #[error("repeated quaternion (at index {index}) is not allowed")]
     RepeatedQuaternion { index: usize },
+    #[error("index {index}: rotation to next quaternion is too small")]
+    RotationTooSmall { index: usize },
+    #[error("index {index}: rotation from previous quaternion is too long for a single segment")]
+    TooLongRotation { index: usize },
+    #[error("number of speeds ({speeds}) and quaternions ({quaternions}) must be the same")]
+    SpeedsVsQuaternions { speeds: usize, quaternions: usize },
+    #[error("index {index}: speed is only allowed if time is given")]
+    SpeedWithoutTime { index: usize },
+    #[error("speed at index {index} too fast ({speed:?}; maximum: {maximum:?})")]
+    TooFast {
+        index: usize,
+        speed: f32,
+        maximum: f32,
+    },
+    #[error("negative speed ({speed:?}) at index {index}")]
+    NegativeSpeed { index: usize, speed: f32 },
 }
 
+/// Number of lookup table entries per segment, see [`ConstantSpeedAdapter::adapt_with_table()`].
+const LOOKUP_SUBDIVISIONS: usize = 16;
+
 pub type AsdfRotSpline = NewGridAdapter<
     UnitQuaternion,
     ConstantSpeedAdapter<UnitQuaternion, Vec3, CubicDeCasteljau, AngularVelocityNorm>,
@@ -50,18 +72,31 @@ impl AsdfRotSpline {
         times: impl AsRef<[Option<f32>]>,
         tcb: impl AsRef<[[f32; 3]]>,
         closed: bool,
+    ) -> Result<AsdfRotSpline, Error> {
+        let quaternions = quaternions.into();
+        let speeds = vec![None; quaternions.len()];
+        AsdfRotSpline::with_speeds(quaternions, times, speeds, tcb, closed)
+    }
+
+    /// Like [`new()`](Self::new), but with angular speeds (in degrees per second)
+    /// at the given quaternions.
+    ///
+    /// Speeds are only allowed where a time is given.
+    pub fn with_speeds(
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
     ) -> Result<AsdfRotSpline, Error> {
         use Error::*;
         let quaternions = quaternions.into();
         let times = times.as_ref();
+        let speeds = speeds.as_ref();
         let tcb = tcb.as_ref();
-        if quaternions.len() + closed as usize != times.len() {
-            return Err(TimesVsQuaternions {
-                times: times.len(),
-                quaternions: quaternions.len(),
-                closed,
-            });
-        }
+        check_lengths(&quaternions, times, speeds, closed)?;
+        // The constant speed adapter uses radians
+        let speeds: Vec<_> = speeds.iter().map(|s| s.map(f32::to_radians)).collect();
         let path = CubicDeCasteljau::new_centripetal_kochanek_bartels(quaternions, tcb, closed)
             .map_err(|e| {
                 use crate::quaternion::centripetalkochanekbartelsspline::Error as E;
@@ -77,24 +112,334 @@ impl AsdfRotSpline {
                         closed,
                     },
                     E::RepeatedQuaternion { index } => RepeatedQuaternion { index },
+                    E::LongRotation { .. } => unreachable!(),
+                    E::TangentsVsQuaternions { .. } => unreachable!(),
+                    E::InvalidExponent { .. } => unreachable!(),
+                    E::WindingsVsQuaternions { .. } => unreachable!(),
+                    E::TooLongRotation { index } => TooLongRotation { index },
                 }
             })?;
-        let constant_speed = ConstantSpeedAdapter::adapt(path);
-        NewGridAdapter::adapt(constant_speed, times, closed).map_err(|e| {
-            use crate::adapters::NewGridError as E;
+        adapt_path(path, times, speeds, closed)
+    }
+
+    /// Like [`new()`](Self::new), but with (azimuth, elevation, roll) in degrees,
+    /// see [`angles2quat()`].
+    pub fn from_angles(
+        angles: impl AsRef<[(f32, f32, f32)]>,
+        times: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfRotSpline, Error> {
+        let quaternions: Vec<_> = angles
+            .as_ref()
+            .iter()
+            .map(|&(azim, elev, roll)| angles2quat(azim, elev, roll))
+            .collect();
+        AsdfRotSpline::new(quaternions, times, tcb, closed)
+    }
+
+    /// Like [`new()`](Self::new), but with an already constructed `path`
+    /// (which may contain "hold" segments).
+    ///
+    /// There must be one time value for each element of the path's grid.
+    pub(crate) fn from_path(
+        path: CubicDeCasteljau,
+        times: &[Option<f32>],
+    ) -> Result<AsdfRotSpline, Error> {
+        let speeds = vec![None; times.len()];
+        adapt_path(path, times, speeds, false)
+    }
+
+    #[must_use]
+    pub fn start_time(&self) -> f32 {
+        *self.grid().first().unwrap()
+    }
+
+    #[must_use]
+    pub fn end_time(&self) -> f32 {
+        *self.grid().last().unwrap()
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.end_time() - self.start_time()
+    }
+}
+
+/// Like [`AsdfRotSpline`], but interpolated in the logarithm space,
+/// see [`LogQuaternionSpline`].
+pub type AsdfLogRotSpline = NewGridAdapter<
+    UnitQuaternion,
+    ConstantSpeedAdapter<UnitQuaternion, Vec3, LogQuaternionSpline, AngularVelocityNorm>,
+>;
+
+impl AsdfLogRotSpline {
+    /// See [`AsdfRotSpline::new()`].
+    ///
+    /// Repeated quaternions are allowed if their times are given.
+    pub fn new(
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        times: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfLogRotSpline, Error> {
+        let quaternions = quaternions.into();
+        let speeds = vec![None; quaternions.len()];
+        AsdfLogRotSpline::with_speeds(quaternions, times, speeds, tcb, closed)
+    }
+
+    /// See [`AsdfRotSpline::with_speeds()`].
+    pub fn with_speeds(
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        times: impl AsRef<[Option<f32>]>,
+        speeds: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<AsdfLogRotSpline, Error> {
+        use Error::*;
+        let quaternions = quaternions.into();
+        let times = times.as_ref();
+        let speeds = speeds.as_ref();
+        check_lengths(&quaternions, times, speeds, closed)?;
+        let speeds: Vec<_> = speeds.iter().map(|s| s.map(f32::to_radians)).collect();
+        let path = LogQuaternionSpline::new_centripetal_kochanek_bartels(
+            quaternions,
+            tcb.as_ref(),
+            closed,
+        )
+        .map_err(|e| {
+            use crate::quaternion::logquaternionspline::Error as E;
             match e {
-                E::FirstGridMissing => FirstTimeMissing,
-                E::LastGridMissing => LastTimeMissing,
-                E::DuplicateValueWithoutGrid { index } => DuplicateQuaternionWithoutTime { index },
-                E::FromGridError(e) => {
-                    use crate::utilities::GridError as E;
-                    match e {
-                        E::GridNan { index } => TimeNan { index },
-                        E::GridNotAscending { index } => TimesNotAscending { index },
+                E::LessThanTwoQuaternions => LessThanTwoQuaternions,
+                E::TcbVsQuaternions {
+                    tcb,
+                    quaternions,
+                    closed,
+                } => TcbVsQuaternions {
+                    tcb,
+                    quaternions,
+                    closed,
+                },
+                E::RotationTooSmall { index } => RotationTooSmall { index },
+            }
+        })?;
+        adapt_path(path, times, speeds, closed)
+    }
+}
+
+fn check_lengths(
+    quaternions: &[UnitQuaternion],
+    times: &[Option<f32>],
+    speeds: &[Option<f32>],
+    closed: bool,
+) -> Result<(), Error> {
+    use Error::*;
+    if quaternions.len() + closed as usize != times.len() {
+        return Err(TimesVsQuaternions {
+            times: times.len(),
+            quaternions: quaternions.len(),
+            closed,
+        });
+    }
+    if speeds.len() != quaternions.len() {
+        return Err(SpeedsVsQuaternions {
+            speeds: speeds.len(),
+            quaternions: quaternions.len(),
+        });
+    }
+    Ok(())
+}
+
+type AdaptedPath<Path> = NewGridAdapter<
+    UnitQuaternion,
+    ConstantSpeedAdapter<UnitQuaternion, Vec3, Path, AngularVelocityNorm>,
+>;
+
+/// Speeds are in radians per second.
+fn adapt_path<Path>(
+    path: Path,
+    times: &[Option<f32>],
+    speeds: Vec<Option<f32>>,
+    closed: bool,
+) -> Result<AdaptedPath<Path>, Error>
+where
+    Path: SplineWithVelocity<UnitQuaternion, Vec3> + Sync,
+{
+    use Error::*;
+    #[cfg(feature = "rayon")]
+    let constant_speed = ConstantSpeedAdapter::par_adapt_with_table(path, LOOKUP_SUBDIVISIONS);
+    #[cfg(not(feature = "rayon"))]
+    let constant_speed = ConstantSpeedAdapter::adapt_with_table(path, LOOKUP_SUBDIVISIONS);
+    NewGridAdapter::adapt_with_speeds(constant_speed, times, speeds, closed).map_err(|e| {
+        use crate::adapters::NewGridWithSpeedsError as E;
+        match e {
+            E::FromNewGridError(e) => {
+                use crate::adapters::NewGridError as E;
+                match e {
+                    E::FirstGridMissing => FirstTimeMissing,
+                    E::LastGridMissing => LastTimeMissing,
+                    E::DuplicateValueWithoutGrid { index } => {
+                        DuplicateQuaternionWithoutTime { index }
+                    }
+                    E::FromGridError(e) => {
+                        use crate::utilities::GridError as E;
+                        match e {
+                            E::GridNan { index } => TimeNan { index },
+                            E::GridNotAscending { index } => TimesNotAscending { index },
+                        }
                     }
+                    E::NewGridVsOldGrid { .. } => unreachable!(),
                 }
-                E::NewGridVsOldGrid { .. } => unreachable!(),
             }
-        })
+            E::SpeedWithoutGrid { index } => SpeedWithoutTime { index },
+            E::TooFast {
+                index,
+                speed,
+                maximum,
+            } => TooFast {
+                index,
+                speed: speed.to_degrees(),
+                maximum: maximum.to_degrees(),
+            },
+            E::NegativeSpeed { index, speed } => NegativeSpeed {
+                index,
+                speed: speed.to_degrees(),
+            },
+            E::GridVsSpeeds { .. } => unreachable!(),
+        }
+    })
+}
+
+/// The arc length reparameterization is not stored, it is re-computed when loading.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Representation<Path> {
+    path: Path,
+    times: Vec<f32>,
+    t2u_segments: Vec<[f32; 4]>,
+    t2u_grid: Vec<f32>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AsdfRotSpline {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Representation {
+            path: self.inner_ref().inner_ref(),
+            times: self.grid().to_vec(),
+            t2u_segments: self.t2u().segments().to_vec(),
+            t2u_grid: self.t2u().grid().to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AsdfRotSpline {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let r = Representation::<CubicDeCasteljau>::deserialize(deserializer)?;
+        if r.times.len() != r.path.grid().len() {
+            return Err(D::Error::custom(format!(
+                "number of times ({}) must be the same as the length of the path grid ({})",
+                r.times.len(),
+                r.path.grid().len()
+            )));
+        }
+        crate::utilities::check_grid(&r.times).map_err(D::Error::custom)?;
+        let t2u = crate::PiecewiseCubicCurve::new(r.t2u_segments, r.t2u_grid)
+            .map_err(D::Error::custom)?;
+        let constant_speed = ConstantSpeedAdapter::adapt_with_table(r.path, LOOKUP_SUBDIVISIONS);
+        Ok(NewGridAdapter::from_parts(
+            constant_speed,
+            r.times.into(),
+            t2u,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SplineWithVelocity;
+
+    #[test]
+    fn from_angles() {
+        let s = AsdfRotSpline::from_angles(
+            [(0.0, 0.0, 0.0), (90.0, 0.0, 0.0), (90.0, 30.0, 0.0)],
+            [Some(0.0), None, Some(2.0)],
+            [[0.0; 3]],
+            false,
+        )
+        .unwrap();
+        assert!(s.evaluate(2.0).angle_to(&angles2quat(90.0, 30.0, 0.0)) < 1e-5);
+        assert!(matches!(
+            AsdfRotSpline::from_angles([(0.0, 0.0, 0.0)], [Some(0.0)], [], false),
+            Err(Error::LessThanTwoQuaternions)
+        ));
+    }
+
+    #[test]
+    fn speeds() {
+        let quaternions = [angles2quat(0.0, 0.0, 0.0), angles2quat(90.0, 0.0, 0.0)];
+        let times = [Some(0.0), Some(3.0)];
+        let s = AsdfRotSpline::with_speeds(quaternions, times, [Some(10.0), Some(20.0)], [], false)
+            .unwrap();
+        let to_degrees = |v: Vec3| v.norm().to_degrees();
+        assert!((to_degrees(s.evaluate_velocity(0.0)) - 10.0).abs() < 1e-3);
+        assert!((to_degrees(s.evaluate_velocity(3.0)) - 20.0).abs() < 1e-3);
+        assert!(matches!(
+            AsdfRotSpline::with_speeds(quaternions, times, [Some(100.0), None], [], false),
+            Err(Error::TooFast { index: 0, .. })
+        ));
+        assert!(matches!(
+            AsdfRotSpline::with_speeds(quaternions, times, [None], [], false),
+            Err(Error::SpeedsVsQuaternions { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let s = AsdfRotSpline::from_angles(
+            [(0.0, 0.0, 0.0), (90.0, 0.0, 0.0), (90.0, 30.0, 0.0)],
+            [Some(0.0), None, Some(3.0)],
+            [[0.0; 3]],
+            false,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        let restored: AsdfRotSpline = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.grid(), s.grid());
+        for i in 0..=30 {
+            let t = i as f32 * 0.1;
+            assert_eq!(restored.evaluate(t), s.evaluate(t));
+        }
+    }
+
+    #[test]
+    fn logarithmic() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 20.0, 0.0),
+            angles2quat(90.0, 20.0, 0.0),
+            angles2quat(150.0, -10.0, 30.0),
+        ];
+        let times = [Some(0.0), Some(2.0), Some(3.0), Some(5.0)];
+        let s = AsdfLogRotSpline::new(q, times, [[0.0; 3]; 2], false).unwrap();
+        for (&t, q) in s.grid().iter().zip(&q) {
+            assert!(s.evaluate(t).angle_to(q) < 1e-4);
+        }
+        // Hold segment
+        assert!(s.evaluate(2.5).angle_to(&q[1]) < 1e-4);
+        assert!(s.evaluate_velocity(2.5).norm() < 1e-4);
+        // Similar to the (exact) spherical interpolation
+        let q = [q[0], q[1], q[3]];
+        let times = [times[0], times[1], times[3]];
+        let log = AsdfLogRotSpline::new(q, times, [[0.0; 3]], false).unwrap();
+        let exact = AsdfRotSpline::new(q, times, [[0.0; 3]], false).unwrap();
+        for t in [0.5, 1.0, 2.5, 4.0] {
+            assert!(log.evaluate(t).angle_to(&exact.evaluate(t)) < 0.1);
+        }
     }
 }
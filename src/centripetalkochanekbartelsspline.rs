@@ -1,4 +1,5 @@
-use crate::{PiecewiseCubicCurve, Vector};
+use crate::math;
+use crate::{PiecewiseCubicCurve, Spline, Vector};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -14,11 +15,16 @@ pub enum Error {
         positions: usize,
         closed: bool,
     },
-    #[error("repeated position (at index {index}) is not allowed")]
-    RepeatedPosition { index: usize },
+    #[error("index {index}: distance to next position must be finite")]
+    NonFiniteDistance { index: usize },
+    #[error("index {index}: distance to next position is too small")]
+    DistanceTooSmall { index: usize },
 }
 
 impl<V: Vector> PiecewiseCubicCurve<V> {
+    /// Repeated consecutive positions are allowed, they lead to a "hold" segment
+    /// (with zero velocity and a parameter range of 1) between them.
+    /// TCB values of repeated positions are ignored.
     pub fn new_centripetal_kochanek_bartels<F>(
         positions: &[V],
         tcb: &[[f32; 3]],
@@ -33,123 +39,211 @@ impl<V: Vector> PiecewiseCubicCurve<V> {
         if positions_len < 2 {
             return Err(LessThanTwoPositions);
         }
-        let mut positions = positions;
-        // Only used for "closed" splines:
-        let mut positions_vec;
-        if closed {
-            positions_vec = Vec::with_capacity(positions.len() + 2);
-            positions_vec.extend(positions);
-            positions_vec.push(positions[0]);
-            positions_vec.push(positions[1]);
-            positions = &positions_vec;
-        } else {
-            // To avoid error: "use of possibly uninitialized `positions_vec`"
-            positions_vec = Vec::new();
-        }
-
-        if tcb.len() + 2 != positions.len() {
+        if tcb.len() + 2 * !closed as usize != positions_len {
             return Err(TcbVsPositions {
                 tcb: tcb.len(),
                 positions: positions_len,
                 closed,
             });
         }
-
-        // Create grid with centripetal parametrization
-
-        let mut grid = Vec::with_capacity(positions.len());
-        grid.push(0.0);
-        for i in 0..positions.len() - 1 {
-            let x0 = positions[i];
-            let x1 = positions[i + 1];
-            let delta = norm(&(x1 - x0)).sqrt();
-            if delta == 0.0 {
-                return Err(RepeatedPosition { index: i + 1 });
-            }
-            grid.push(*grid.last().unwrap() + delta);
+        let distance = |i: usize| norm(&(positions[(i + 1) % positions_len] - positions[i]));
+        let indices = 0..positions_len - !closed as usize;
+        if let Some(index) = indices.clone().find(|&i| !distance(i).is_finite()) {
+            return Err(NonFiniteDistance { index });
         }
-        let mut tangents = Vec::<V>::new();
-        assert_eq!(positions.len(), grid.len());
-        assert_eq!(positions.len(), tcb.len() + 2);
-        for i in 0..positions.len() - 2 {
-            let x_1 = positions[i];
-            let x0 = positions[i + 1];
-            let x1 = positions[i + 2];
-            let t_1 = grid[i];
-            let t0 = grid[i + 1];
-            let t1 = grid[i + 2];
-            #[allow(non_snake_case)]
-            let [T, C, B] = tcb[(i + closed as usize) % tcb.len()];
-            let a = (1.0 - T) * (1.0 + C) * (1.0 + B);
-            let b = (1.0 - T) * (1.0 - C) * (1.0 - B);
-            let c = (1.0 - T) * (1.0 - C) * (1.0 + B);
-            let d = (1.0 - T) * (1.0 + C) * (1.0 - B);
-
-            let denominator = (t1 - t0) * (t0 - t_1) * (t1 - t_1);
-            let incoming = ((x0 - x_1) * c * (t1 - t0).powi(2)
-                + (x1 - x0) * d * (t0 - t_1).powi(2))
-                / denominator;
-            let outgoing = ((x0 - x_1) * a * (t1 - t0).powi(2)
-                + (x1 - x0) * b * (t0 - t_1).powi(2))
-                / denominator;
-            tangents.push(incoming);
-            tangents.push(outgoing);
+        let repetitions: Vec<_> = indices.filter(|&i| distance(i) == 0.0).collect();
+        if repetitions.is_empty() {
+            return kochanek_bartels_without_repetitions(positions, tcb, closed, norm);
         }
-
-        if closed {
-            // Move last (outgoing) tangent to the beginning:
-            tangents.rotate_right(1);
-
-            // Remove temporary position and grid elements:
-            positions_vec.pop();
-            grid.pop();
-
-            // Update reference
-            positions = &positions_vec;
-        } else if positions.len() == 2 {
-            // Straight line
-            assert_eq!(grid.len(), 2);
-            assert!(tangents.is_empty());
-            let tangent = (positions[1] - positions[0]) / (grid[1] - grid[0]);
-            tangents.push(tangent);
-            tangents.push(tangent);
+        let (segments, grid) = if closed {
+            // Start right after the first repetition, end with a hold segment:
+            let first = repetitions[0] + 1;
+            let rotated: Vec<_> = (0..positions_len)
+                .map(|i| positions[(first + i) % positions_len])
+                .collect();
+            let rotated_tcb: Vec<_> = (1..positions_len - 1)
+                .map(|i| tcb[(first + i) % positions_len])
+                .collect();
+            let open = PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &rotated,
+                &rotated_tcb,
+                false,
+                norm,
+            )?;
+            let mut segments = open.segments().to_vec();
+            let mut deltas: Vec<_> = open.grid().windows(2).map(|w| w[1] - w[0]).collect();
+            segments.push(hold_segment(rotated[positions_len - 1]));
+            deltas.push(1.0);
+            // Rotate back to start with the first position:
+            let shift = (positions_len - first) % positions_len;
+            segments.rotate_left(shift);
+            deltas.rotate_left(shift);
+            let mut grid = Vec::with_capacity(deltas.len() + 1);
+            grid.push(0.0f32);
+            for delta in deltas {
+                grid.push(*grid.last().unwrap() + delta);
+            }
+            (segments, grid)
         } else {
-            // End conditions for non-closed curves
-            assert!(tangents.len() >= 2);
-
-            // "natural" end conditions
-            let natural_end_tangent = |x0, x1, t0, t1, inner_tangent| {
-                let delta = t1 - t0;
-                (x1 * 3.0 - x0 * 3.0 - inner_tangent * delta) / (2.0 * delta)
-            };
-
-            if let (&[x0, x1, ..], &[t0, t1, ..]) = (positions, &grid[..]) {
-                tangents.insert(0, natural_end_tangent(x0, x1, t0, t1, tangents[0]));
-            } else {
-                unreachable!();
+            let mut segments = Vec::new();
+            let mut grid = vec![0.0];
+            let mut start = 0;
+            for end in repetitions.into_iter().chain(Some(positions_len - 1)) {
+                if end > start {
+                    let run = kochanek_bartels_without_repetitions(
+                        &positions[start..=end],
+                        &tcb[start..end - 1],
+                        false,
+                        &norm,
+                    )?;
+                    let offset = *grid.last().unwrap();
+                    segments.extend_from_slice(run.segments());
+                    grid.extend(run.grid()[1..].iter().map(|t| offset + t));
+                }
+                if end + 1 < positions_len {
+                    segments.push(hold_segment(positions[end]));
+                    grid.push(*grid.last().unwrap() + 1.0);
+                }
+                start = end + 1;
             }
-            if let (&[.., x0, x1], &[.., t0, t1]) = (positions, &grid[..]) {
-                tangents.push(natural_end_tangent(
-                    x0,
-                    x1,
-                    t0,
-                    t1,
-                    *tangents.last().unwrap(),
-                ));
-            } else {
-                unreachable!();
+            (segments, grid)
+        };
+        PiecewiseCubicCurve::new(segments, grid).map_err(|e| {
+            use crate::piecewisecubiccurve::Error as E;
+            use crate::utilities::GridError as G;
+            match e {
+                E::ZeroSegments => unreachable!(),
+                E::GridVsSegments { .. } => unreachable!(),
+                // Segment indices are the same as position indices
+                E::FromGridError(G::GridNotAscending { index }) => {
+                    DistanceTooSmall { index: index - 1 }
+                }
+                E::FromGridError(G::GridNan { .. }) => unreachable!(),
             }
-        }
-        use crate::cubichermitespline::Error as E;
-        PiecewiseCubicCurve::new_hermite(positions, &tangents, &grid).map_err(|e| match e {
-            E::LessThanTwoPositions => unreachable!(),
-            E::TangentsVsSegments { .. } => unreachable!(),
-            E::GridVsPositions { .. } => unreachable!(),
-            E::FromGridError(..) => unreachable!(),
         })
     }
 }
 
+fn hold_segment<V: Vector>(position: V) -> [V; 4] {
+    let zero = position * 0.0;
+    [position, zero, zero, zero]
+}
+
+/// Lengths of `positions` and `tcb` must have been checked already.
+fn kochanek_bartels_without_repetitions<V, F>(
+    positions: &[V],
+    tcb: &[[f32; 3]],
+    closed: bool,
+    norm: F,
+) -> Result<PiecewiseCubicCurve<V>, Error>
+where
+    V: Vector,
+    F: Fn(&V) -> f32,
+{
+    let positions_len = positions.len();
+    // Closed curves use the first two positions again at the end:
+    let extended_len = positions_len + 2 * closed as usize;
+    let position = |i: usize| positions[i % positions_len];
+
+    // Create grid with centripetal parametrization
+
+    let mut grid = Vec::with_capacity(extended_len);
+    grid.push(0.0);
+    for i in 0..extended_len - 1 {
+        let x0 = position(i);
+        let x1 = position(i + 1);
+        let delta = math::sqrt(norm(&(x1 - x0)));
+        assert!(delta > 0.0);
+        let previous = *grid.last().unwrap();
+        let next = previous + delta;
+        // Tiny distances might get lost in rounding
+        if next <= previous {
+            return Err(Error::DistanceTooSmall {
+                index: i % positions_len,
+            });
+        }
+        grid.push(next);
+    }
+    if let (false, [x0, x1]) = (closed, positions) {
+        // Straight line, the higher coefficients are exactly zero
+        // (which allows a fast path in ConstantSpeedAdapter)
+        let zero = *x0 * 0.0;
+        use crate::piecewisecubiccurve::Error as E;
+        return match PiecewiseCubicCurve::new(vec![[*x0, *x1 - *x0, zero, zero]], grid) {
+            Ok(curve) => Ok(curve),
+            Err(E::ZeroSegments) => unreachable!(),
+            Err(E::GridVsSegments { .. }) => unreachable!(),
+            Err(E::FromGridError(..)) => unreachable!(),
+        };
+    }
+    assert_eq!(extended_len, tcb.len() + 2);
+    let segments_len = positions_len - !closed as usize;
+    // Incoming and outgoing tangent for each segment:
+    let mut tangents = vec![positions[0] * 0.0; 2 * segments_len];
+    for i in 1..extended_len - 1 {
+        let x_1 = position(i - 1);
+        let x0 = position(i);
+        let x1 = position(i + 1);
+        let t_1 = grid[i - 1];
+        let t0 = grid[i];
+        let t1 = grid[i + 1];
+        #[allow(non_snake_case)]
+        let [T, C, B] = tcb[(i - 1 + closed as usize) % tcb.len()];
+        let a = (1.0 - T) * (1.0 + C) * (1.0 + B);
+        let b = (1.0 - T) * (1.0 - C) * (1.0 - B);
+        let c = (1.0 - T) * (1.0 - C) * (1.0 + B);
+        let d = (1.0 - T) * (1.0 + C) * (1.0 - B);
+
+        let denominator = (t1 - t0) * (t0 - t_1) * (t1 - t_1);
+        let incoming =
+            ((x0 - x_1) * c * (t1 - t0).powi(2) + (x1 - x0) * d * (t0 - t_1).powi(2)) / denominator;
+        let outgoing =
+            ((x0 - x_1) * a * (t1 - t0).powi(2) + (x1 - x0) * b * (t0 - t_1).powi(2)) / denominator;
+        tangents[2 * i - 1] = incoming;
+        // For closed curves, the last outgoing tangent belongs to the first segment:
+        tangents[(2 * i) % (2 * segments_len)] = outgoing;
+    }
+
+    // Closed curves end at the first position, with one temporary grid value to remove:
+    grid.truncate(segments_len + 1);
+    let mut positions_vec;
+    let positions = if closed {
+        positions_vec = Vec::with_capacity(positions_len + 1);
+        positions_vec.extend_from_slice(positions);
+        positions_vec.push(positions[0]);
+        &positions_vec[..]
+    } else {
+        // End conditions for non-closed curves
+
+        // "natural" end conditions
+        let natural_end_tangent = |x0, x1, t0, t1, inner_tangent| {
+            let delta = t1 - t0;
+            (x1 * 3.0 - x0 * 3.0 - inner_tangent * delta) / (2.0 * delta)
+        };
+
+        let last = tangents.len() - 1;
+        if let (&[x0, x1, ..], &[t0, t1, ..]) = (positions, &grid[..]) {
+            tangents[0] = natural_end_tangent(x0, x1, t0, t1, tangents[1]);
+        } else {
+            unreachable!();
+        }
+        if let (&[.., x0, x1], &[.., t0, t1]) = (positions, &grid[..]) {
+            tangents[last] = natural_end_tangent(x0, x1, t0, t1, tangents[last - 1]);
+        } else {
+            unreachable!();
+        }
+        positions
+    };
+    use crate::cubichermitespline::Error as E;
+    match PiecewiseCubicCurve::new_hermite(positions, &tangents, &grid) {
+        Ok(curve) => Ok(curve),
+        Err(E::LessThanTwoPositions) => unreachable!(),
+        Err(E::TangentsVsSegments { .. }) => unreachable!(),
+        Err(E::GridVsPositions { .. }) => unreachable!(),
+        Err(E::FromGridError(..)) => unreachable!(),
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::float_cmp)]
 mod tests {
@@ -171,4 +265,85 @@ mod tests {
         assert_eq!(curve.evaluate(0.0), 1.0);
         assert_eq!(curve.evaluate(*curve.grid().last().unwrap()), 3.0);
     }
+
+    #[test]
+    fn repeated_position() {
+        let positions = [1.0f32, 2.0, 2.0, 4.0];
+        let tcb = [[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]];
+        let curve =
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, &tcb, false, |x| {
+                x.abs()
+            })
+            .unwrap();
+        assert_eq!(curve.segments().len(), 3);
+        assert_eq!(curve.segments()[1], [2.0, 0.0, 0.0, 0.0]);
+        let grid = curve.grid();
+        assert_eq!(curve.evaluate(grid[1]), 2.0);
+        assert_eq!(curve.evaluate((grid[1] + grid[2]) / 2.0), 2.0);
+        assert_eq!(curve.evaluate(grid[3]), 4.0);
+    }
+
+    #[test]
+    fn repeated_position_closed() {
+        let positions = [1.0f32, 2.0, 2.0];
+        let tcb = [[0.0, 0.0, 0.0]; 3];
+        let curve =
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, &tcb, true, |x| {
+                x.abs()
+            })
+            .unwrap();
+        assert_eq!(curve.segments().len(), 3);
+        assert_eq!(curve.segments()[1], [2.0, 0.0, 0.0, 0.0]);
+        assert_eq!(curve.evaluate(0.0), 1.0);
+        assert_eq!(curve.evaluate(*curve.grid().last().unwrap()), 1.0);
+    }
+
+    #[test]
+    fn non_finite() {
+        let abs = |x: &f32| x.abs();
+        let tcb = [[0.0, 0.0, 0.0]];
+        assert!(matches!(
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &[1.0f32, f32::NAN, 3.0],
+                &tcb,
+                false,
+                abs
+            ),
+            Err(Error::NonFiniteDistance { index: 0 })
+        ));
+        assert!(matches!(
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &[1.0f32, f32::MAX, -f32::MAX],
+                &[[0.0, 0.0, 0.0]; 3],
+                true,
+                abs
+            ),
+            Err(Error::NonFiniteDistance { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn distance_too_small() {
+        let abs = |x: &f32| x.abs();
+        let tcb = [[0.0, 0.0, 0.0]; 2];
+        assert!(matches!(
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &[1.0f32, 0.0, 1e-30, 2.0],
+                &tcb,
+                false,
+                abs
+            ),
+            Err(Error::DistanceTooSmall { index: 1 })
+        ));
+        // Same thing after a "hold" segment
+        assert!(matches!(
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+                &[1.0f32, 0.0, 0.0, 1e-30],
+                &tcb,
+                false,
+                abs
+            ),
+            Err(Error::DistanceTooSmall { index: 2 })
+        ));
+    }
 }
@@ -0,0 +1,170 @@
+//! Analysis of the smoothness of splines at their grid values ("knots").
+
+use crate::quaternion::{CubicDeCasteljau, DeCasteljau, UnitQuaternion, Vec3};
+use crate::{PiecewiseCubicCurve, Spline, Vector};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Continuity {
+    Discontinuous,
+    C0,
+    C1,
+    C2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KnotContinuity {
+    /// Grid index of the knot
+    pub index: usize,
+    pub continuity: Continuity,
+    /// Magnitudes of the jumps in value, first and second derivative
+    pub jumps: [f32; 3],
+}
+
+impl KnotContinuity {
+    /// Jumps up to (and including) `tolerance` are considered continuous.
+    fn new(index: usize, jumps: [f32; 3], tolerance: f32) -> KnotContinuity {
+        let continuity = match jumps
+            .iter()
+            .position(|&jump| jump.is_nan() || jump > tolerance)
+        {
+            Some(0) => Continuity::Discontinuous,
+            Some(1) => Continuity::C0,
+            Some(2) => Continuity::C1,
+            None => Continuity::C2,
+            Some(_) => unreachable!(),
+        };
+        KnotContinuity {
+            index,
+            continuity,
+            jumps,
+        }
+    }
+}
+
+impl<V: Vector> PiecewiseCubicCurve<V> {
+    /// Analyzes all interior knots, `tolerance` is an absolute value.
+    pub fn continuity<F>(&self, norm: F, tolerance: f32) -> Vec<KnotContinuity>
+    where
+        F: Fn(&V) -> f32,
+    {
+        let grid = self.grid();
+        let segments = self.segments();
+        (1..grid.len() - 1)
+            .map(|index| {
+                let [a0, a1, a2, a3] = segments[index - 1];
+                let [b0, b1, b2, _] = segments[index];
+                let left = grid[index] - grid[index - 1];
+                let right = grid[index + 1] - grid[index];
+                let jumps = [
+                    norm(&(b0 - (a0 + a1 + a2 + a3))),
+                    norm(&(b1 / right - (a1 + a2 * 2.0 + a3 * 3.0) / left)),
+                    norm(&(b2 * 2.0 / (right * right) - (a2 * 2.0 + a3 * 6.0) / (left * left))),
+                ];
+                KnotContinuity::new(index, jumps, tolerance)
+            })
+            .collect()
+    }
+}
+
+/// Jumps of angle, angular velocity and (finite difference) angular acceleration.
+fn rotation_continuity(
+    grid: &[f32],
+    evaluate: impl Fn(usize, f32) -> UnitQuaternion,
+    velocity: impl Fn(usize, f32) -> Vec3,
+    tolerance: f32,
+) -> Vec<KnotContinuity> {
+    let h = 0.001;
+    (1..grid.len() - 1)
+        .map(|index| {
+            let left = grid[index] - grid[index - 1];
+            let right = grid[index + 1] - grid[index];
+            let left_velocity = velocity(index - 1, 1.0);
+            let right_velocity = velocity(index, 0.0);
+            // One-sided second-order finite differences
+            let left_acceleration = (left_velocity * 3.0 - velocity(index - 1, 1.0 - h) * 4.0
+                + velocity(index - 1, 1.0 - 2.0 * h))
+                / (2.0 * h * left);
+            let right_acceleration = (velocity(index, 2.0 * h) * -1.0 + velocity(index, h) * 4.0
+                - right_velocity * 3.0)
+                / (2.0 * h * right);
+            let jumps = [
+                evaluate(index - 1, 1.0).angle_to(&evaluate(index, 0.0)),
+                (right_velocity - left_velocity).norm(),
+                (right_acceleration - left_acceleration).norm(),
+            ];
+            KnotContinuity::new(index, jumps, tolerance)
+        })
+        .collect()
+}
+
+impl CubicDeCasteljau {
+    /// Analyzes all interior knots, `tolerance` is an absolute value.
+    ///
+    /// Jumps are given as angle (in radians), angular velocity and angular acceleration.
+    /// The angular acceleration is approximated with finite differences.
+    pub fn continuity(&self, tolerance: f32) -> Vec<KnotContinuity> {
+        rotation_continuity(
+            self.grid(),
+            |idx, t| self.segment_evaluate(idx, t),
+            |idx, t| self.segment_velocity(idx, t),
+            tolerance,
+        )
+    }
+}
+
+impl DeCasteljau {
+    /// See [`CubicDeCasteljau::continuity()`].
+    pub fn continuity(&self, tolerance: f32) -> Vec<KnotContinuity> {
+        rotation_continuity(
+            self.grid(),
+            |idx, t| self.segment_evaluate(idx, t),
+            |idx, t| self.segment_velocity(idx, t),
+            tolerance,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn piecewise_cubic() {
+        let curve = PiecewiseCubicCurve::new(
+            [
+                [0.0f32, 1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0, 0.0],
+                [2.0, 2.0, 0.0, 0.0],
+                [5.0, 1.0, 0.0, 0.0],
+            ],
+            [0.0, 1.0, 2.0, 3.0, 4.0],
+        )
+        .unwrap();
+        let result = curve.continuity(|x| x.abs(), 1e-6);
+        let classes: Vec<_> = result.iter().map(|k| k.continuity).collect();
+        assert_eq!(
+            classes,
+            [Continuity::C2, Continuity::C0, Continuity::Discontinuous]
+        );
+        assert_eq!(result[1].jumps, [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn kochanek_bartels_quaternion() {
+        use crate::quaternion::angles2quat;
+        let curve = CubicDeCasteljau::new_centripetal_kochanek_bartels(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(90.0, 0.0, 0.0),
+                angles2quat(90.0, 90.0, 0.0),
+                angles2quat(0.0, 90.0, 0.0),
+            ],
+            &[[0.0, 0.0, 0.0], [0.0, 0.0, 0.0]],
+            false,
+        )
+        .unwrap();
+        let result = curve.continuity(0.001);
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().all(|k| k.continuity >= Continuity::C1));
+    }
+}
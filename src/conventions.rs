@@ -0,0 +1,170 @@
+//! Conversion between coordinate system conventions.
+//!
+//! ASDF uses a right-handed coordinate system with x pointing to the right,
+//! y to the front and z upwards, see [`angles2quat()`].
+//! All supported conventions are right-handed, therefore conversions are rotations.
+
+use nalgebra::{Matrix3, Rotation3};
+
+use crate::quaternion::{angles2quat, UnitQuaternion, Vec3};
+use crate::Spline;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Convention {
+    Asdf,
+    /// x to the right, y upwards, z to the back (i.e. looking along negative z).
+    OpenGl,
+    /// x to the front, y to the left, z upwards (as used in AmbiX).
+    AmbiX,
+}
+
+impl Convention {
+    /// Rotation that turns ASDF coordinates into coordinates of this convention.
+    #[must_use]
+    pub fn from_asdf(self) -> UnitQuaternion {
+        // Rows are the target axes, expressed in ASDF coordinates
+        #[rustfmt::skip]
+        let matrix = match self {
+            Convention::Asdf => Matrix3::identity(),
+            Convention::OpenGl => Matrix3::new(
+                1.0, 0.0, 0.0,
+                0.0, 0.0, 1.0,
+                0.0, -1.0, 0.0,
+            ),
+            Convention::AmbiX => Matrix3::new(
+                0.0, 1.0, 0.0,
+                -1.0, 0.0, 0.0,
+                0.0, 0.0, 1.0,
+            ),
+        };
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(matrix))
+    }
+
+    /// Rotation from this convention to `other`.
+    #[must_use]
+    pub fn to(self, other: Convention) -> UnitQuaternion {
+        other.from_asdf() * self.from_asdf().inverse()
+    }
+
+    #[must_use]
+    pub fn convert_position(self, position: Vec3, target: Convention) -> Vec3 {
+        self.to(target) * position
+    }
+
+    /// Converts an orientation, i.e. a rotation expressed in this convention.
+    #[must_use]
+    pub fn convert_quaternion(
+        self,
+        rotation: UnitQuaternion,
+        target: Convention,
+    ) -> UnitQuaternion {
+        let change = self.to(target);
+        change * rotation * change.inverse()
+    }
+
+    /// Like [`angles2quat()`], but the result is expressed in this convention.
+    #[must_use]
+    pub fn angles2quat(self, azim: f32, elev: f32, roll: f32) -> UnitQuaternion {
+        Convention::Asdf.convert_quaternion(angles2quat(azim, elev, roll), self)
+    }
+}
+
+/// Rotation from AmbiX yaw, pitch and roll (in degrees).
+///
+/// Rotations are applied in the order yaw (around z), pitch (around y) and roll (around x),
+/// with positive angles according to the right-hand rule.
+/// This means that positive pitch turns the front downwards.
+/// The result is expressed in ASDF coordinates.
+#[must_use]
+pub fn ambix_ypr2quat(yaw: f32, pitch: f32, roll: f32) -> UnitQuaternion {
+    angles2quat(yaw, -pitch, roll)
+}
+
+/// Evaluates a position spline in a different convention.
+pub struct ConventionAdapter<Inner> {
+    inner: Inner,
+    rotation: UnitQuaternion,
+}
+
+impl<Inner> ConventionAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    pub fn adapt(inner: Inner, from: Convention, to: Convention) -> ConventionAdapter<Inner> {
+        ConventionAdapter {
+            inner,
+            rotation: from.to(to),
+        }
+    }
+}
+
+impl<Inner> Spline<Vec3> for ConventionAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    fn evaluate(&self, t: f32) -> Vec3 {
+        self.rotation * self.inner.evaluate(t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).norm() < 1e-5
+    }
+
+    #[test]
+    fn positions() {
+        let front = Vec3::new(0.0, 1.0, 0.0);
+        let right = Vec3::new(1.0, 0.0, 0.0);
+        let up = Vec3::new(0.0, 0.0, 1.0);
+        let gl = |p| Convention::Asdf.convert_position(p, Convention::OpenGl);
+        assert!(close(gl(front), Vec3::new(0.0, 0.0, -1.0)));
+        assert!(close(gl(right), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(gl(up), Vec3::new(0.0, 1.0, 0.0)));
+        let ambix = |p| Convention::Asdf.convert_position(p, Convention::AmbiX);
+        assert!(close(ambix(front), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(close(ambix(right), Vec3::new(0.0, -1.0, 0.0)));
+        assert!(close(ambix(up), Vec3::new(0.0, 0.0, 1.0)));
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        let there = Convention::OpenGl.convert_position(p, Convention::AmbiX);
+        assert!(close(
+            Convention::AmbiX.convert_position(there, Convention::OpenGl),
+            p
+        ));
+    }
+
+    #[test]
+    fn rotations() {
+        // Turning left by 90 degrees
+        let q = Convention::OpenGl.angles2quat(90.0, 0.0, 0.0);
+        // Front becomes left
+        assert!(close(
+            q * Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(-1.0, 0.0, 0.0)
+        ));
+        let q = Convention::AmbiX.angles2quat(0.0, 30.0, 0.0);
+        let front = q * Vec3::new(1.0, 0.0, 0.0);
+        assert!(front.z > 0.0);
+        let q = ambix_ypr2quat(0.0, 30.0, 0.0);
+        assert!((q * Vec3::new(0.0, 1.0, 0.0)).z < 0.0);
+    }
+
+    #[test]
+    fn adapter() {
+        let zero = Vec3::zeros();
+        let line =
+            PiecewiseCubicCurve::new([[zero, Vec3::new(0.0, 1.0, 0.0), zero, zero]], [0.0, 1.0])
+                .unwrap();
+        let s = ConventionAdapter::adapt(line, Convention::Asdf, Convention::OpenGl);
+        assert!(close(s.evaluate(1.0), Vec3::new(0.0, 0.0, -1.0)));
+    }
+}
@@ -0,0 +1,181 @@
+//! Comparing splines, e.g. to check that an approximation is within tolerance.
+
+use crate::math;
+use crate::{NormWrapper, Spline, Vector};
+
+/// Each segment is split at least this many times.
+const MIN_DEPTH: usize = 2;
+const MAX_DEPTH: usize = 16;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(
+        "time ranges must be the same ({first_start:?} to {first_end:?} vs. {second_start:?} to {second_end:?})"
+    )]
+    DomainMismatch {
+        first_start: f32,
+        first_end: f32,
+        second_start: f32,
+        second_end: f32,
+    },
+    #[error("tolerance must be a positive number (not {tolerance:?})")]
+    InvalidTolerance { tolerance: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    /// Largest distance found.
+    pub max: f32,
+    /// Time of the largest distance.
+    pub max_time: f32,
+    /// Root mean square of the distance over the whole time range.
+    pub rms: f32,
+}
+
+struct Accumulator {
+    max: f32,
+    max_time: f32,
+    squares: f32,
+}
+
+impl Accumulator {
+    fn sample(&mut self, t: f32, distance: f32) {
+        if distance > self.max {
+            self.max = distance;
+            self.max_time = t;
+        }
+    }
+}
+
+/// Distance between `first` and `second`, which must have the same time range.
+///
+/// Both grids are sampled, each interval is subdivided as long as the distance
+/// in its middle differs by more than `tolerance` from the linear interpolation
+/// of the distances at its ends.
+pub fn deviation<V, U, First, Second>(
+    first: &First,
+    second: &Second,
+    tolerance: f32,
+) -> Result<Deviation, Error>
+where
+    V: Vector + NormWrapper<U>,
+    First: Spline<V> + ?Sized,
+    Second: Spline<V> + ?Sized,
+{
+    use Error::*;
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(tolerance > 0.0) {
+        return Err(InvalidTolerance { tolerance });
+    }
+    let (a, b) = (first.grid(), second.grid());
+    let first_start = *a.first().unwrap();
+    let first_end = *a.last().unwrap();
+    let second_start = *b.first().unwrap();
+    let second_end = *b.last().unwrap();
+    if first_start != second_start || first_end != second_end {
+        return Err(DomainMismatch {
+            first_start,
+            first_end,
+            second_start,
+            second_end,
+        });
+    }
+    let mut grid: Vec<_> = a.iter().chain(b).copied().collect();
+    grid.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    grid.dedup();
+
+    let distance = |t: f32| (first.evaluate(t) - second.evaluate(t)).norm();
+    let mut acc = Accumulator {
+        max: 0.0,
+        max_time: first_start,
+        squares: 0.0,
+    };
+    for w in grid.windows(2) {
+        let (d0, d1) = (distance(w[0]), distance(w[1]));
+        acc.sample(w[0], d0);
+        acc.sample(w[1], d1);
+        refine(&distance, w[0], d0, w[1], d1, tolerance, 0, &mut acc);
+    }
+    let duration = first_end - first_start;
+    let rms = if duration > 0.0 {
+        math::sqrt(acc.squares / duration)
+    } else {
+        acc.max
+    };
+    Ok(Deviation {
+        max: acc.max,
+        max_time: acc.max_time,
+        rms,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn refine(
+    distance: &impl Fn(f32) -> f32,
+    t0: f32,
+    d0: f32,
+    t1: f32,
+    d1: f32,
+    tolerance: f32,
+    depth: usize,
+    acc: &mut Accumulator,
+) {
+    let tm = (t0 + t1) / 2.0;
+    let dm = distance(tm);
+    acc.sample(tm, dm);
+    let converged = depth >= MIN_DEPTH && (dm - (d0 + d1) / 2.0).abs() <= tolerance;
+    if converged || depth >= MAX_DEPTH || tm <= t0 || tm >= t1 {
+        // Simpson's rule
+        acc.squares += (t1 - t0) / 6.0 * (d0 * d0 + 4.0 * dm * dm + d1 * d1);
+    } else {
+        refine(distance, t0, d0, tm, dm, tolerance, depth + 1, acc);
+        refine(distance, tm, dm, t1, d1, tolerance, depth + 1, acc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::Vec3;
+    use crate::PiecewiseCubicCurve;
+
+    struct NormVec3;
+
+    impl NormWrapper<NormVec3> for Vec3 {
+        fn norm(&self) -> f32 {
+            self.norm()
+        }
+    }
+
+    #[test]
+    fn parabola() {
+        let zero = Vec3::zeros();
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let line = PiecewiseCubicCurve::new([[zero, x, zero, zero]], [0.0, 2.0]).unwrap();
+        // Same end points, but bulging upwards by up to 1 in the middle
+        let bump = PiecewiseCubicCurve::new(
+            [[
+                zero,
+                x + Vec3::new(0.0, 4.0, 0.0),
+                Vec3::new(0.0, -4.0, 0.0),
+                zero,
+            ]],
+            [0.0, 2.0],
+        )
+        .unwrap();
+        let d = deviation::<_, NormVec3, _, _>(&line, &bump, 1e-4).unwrap();
+        assert!((d.max - 1.0).abs() < 1e-5);
+        assert!((d.max_time - 1.0).abs() < 1e-5);
+        // RMS of 4x(1-x) over [0, 1] is sqrt(8/15)
+        assert!((d.rms - math::sqrt(8.0 / 15.0)).abs() < 1e-4);
+        let same = deviation::<_, NormVec3, _, _>(&line, &line, 1e-4).unwrap();
+        assert_eq!(same.max, 0.0);
+        assert_eq!(same.rms, 0.0);
+        let short = PiecewiseCubicCurve::new([[zero, x, zero, zero]], [0.0, 1.0]).unwrap();
+        assert!(matches!(
+            deviation::<_, NormVec3, _, _>(&line, &short, 1e-4),
+            Err(Error::DomainMismatch { .. })
+        ));
+    }
+}
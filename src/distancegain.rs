@@ -0,0 +1,254 @@
+use crate::math;
+use crate::quaternion::Vec3;
+use crate::utilities::{subdivide_grid, GridError};
+use crate::{PiecewiseCubicCurve, Spline};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("reference distance must be a positive number (not {distance:?})")]
+    InvalidReferenceDistance { distance: f32 },
+    #[error("distance law exponent must be a non-negative number (not {exponent:?})")]
+    InvalidExponent { exponent: f32 },
+    #[error("listener position must be finite")]
+    NonFiniteListener,
+    #[error("index {index}: gain value must be finite (not {gain:?})")]
+    NonFiniteGain { index: usize, gain: f32 },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceLaw {
+    /// 1/r, i.e. -6 dB per doubling of distance.
+    InverseDistance,
+    /// 1/r<sup>exponent</sup>
+    Exponent(f32),
+}
+
+impl DistanceLaw {
+    fn exponent(&self) -> f32 {
+        match *self {
+            DistanceLaw::InverseDistance => 1.0,
+            DistanceLaw::Exponent(exponent) => exponent,
+        }
+    }
+}
+
+/// Domain in which gain values are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GainDomain {
+    Linear,
+    Decibel,
+}
+
+/// Distance-dependent gain of a position spline relative to a listener.
+///
+/// Distances below the reference distance lead to a gain of 1.
+pub struct DistanceGain<Inner> {
+    inner: Inner,
+    listener: Vec3,
+    law: DistanceLaw,
+    reference_distance: f32,
+}
+
+impl<Inner> DistanceGain<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    pub fn new(
+        inner: Inner,
+        listener: Vec3,
+        law: DistanceLaw,
+        reference_distance: f32,
+    ) -> Result<DistanceGain<Inner>, Error> {
+        use Error::*;
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(reference_distance > 0.0) || reference_distance.is_infinite() {
+            return Err(InvalidReferenceDistance {
+                distance: reference_distance,
+            });
+        }
+        let exponent = law.exponent();
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(exponent >= 0.0) || exponent.is_infinite() {
+            return Err(InvalidExponent { exponent });
+        }
+        if !listener.iter().all(|x| x.is_finite()) {
+            return Err(NonFiniteListener);
+        }
+        Ok(DistanceGain {
+            inner,
+            listener,
+            law,
+            reference_distance,
+        })
+    }
+
+    /// Linear amplitude at time `t`.
+    #[must_use]
+    pub fn gain(&self, t: f32) -> f32 {
+        let distance = (self.inner.evaluate(t) - self.listener).norm();
+        math::powf(
+            self.reference_distance / distance.max(self.reference_distance),
+            self.law.exponent(),
+        )
+    }
+
+    /// Samples the gain and creates a (shape-preserving) 1D spline.
+    ///
+    /// Each segment of the inner spline is divided into `samples_per_segment` parts.
+    ///
+    /// In the decibel domain, this fails if a gain is zero (e.g. at an infinite distance).
+    pub fn to_spline(
+        &self,
+        samples_per_segment: usize,
+        domain: GainDomain,
+    ) -> Result<GainSpline, Error> {
+        let times = subdivide_grid(self.inner.grid(), samples_per_segment);
+        let values: Vec<_> = times
+            .iter()
+            .map(|&t| match domain {
+                GainDomain::Linear => self.gain(t),
+                GainDomain::Decibel => 20.0 * math::log10(self.gain(t)),
+            })
+            .collect();
+        if let Some(index) = values.iter().position(|gain| !gain.is_finite()) {
+            return Err(Error::NonFiniteGain {
+                index,
+                gain: values[index],
+            });
+        }
+        let curve =
+            PiecewiseCubicCurve::new_piecewise_monotone(values, times, false).map_err(|e| {
+                use crate::piecewisemonotonecubicspline::PiecewiseMonotoneError as E;
+                match e {
+                    E::LessThanTwoValues => unreachable!(),
+                    E::GridVsValues { .. } => unreachable!(),
+                    E::FromGridError(e) => Error::FromGridError(e),
+                }
+            })?;
+        Ok(GainSpline { curve, domain })
+    }
+}
+
+impl<Inner> Spline<f32> for DistanceGain<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    fn evaluate(&self, t: f32) -> f32 {
+        self.gain(t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+/// Gain values interpolated in linear or decibel domain.
+///
+/// Evaluation always returns linear amplitude values.
+pub struct GainSpline {
+    curve: PiecewiseCubicCurve<f32>,
+    domain: GainDomain,
+}
+
+impl GainSpline {
+    /// Values are linear amplitudes or decibels, depending on [`domain()`](Self::domain).
+    #[must_use]
+    pub fn curve(&self) -> &PiecewiseCubicCurve<f32> {
+        &self.curve
+    }
+
+    #[must_use]
+    pub fn domain(&self) -> GainDomain {
+        self.domain
+    }
+}
+
+impl Spline<f32> for GainSpline {
+    fn evaluate(&self, t: f32) -> f32 {
+        let value = self.curve.evaluate(t);
+        match self.domain {
+            GainDomain::Linear => value,
+            GainDomain::Decibel => math::powf(10.0, value / 20.0),
+        }
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.curve.grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_distance() {
+        let zero = Vec3::zeros();
+        let line = PiecewiseCubicCurve::new(
+            [[
+                Vec3::new(0.5, 0.0, 0.0),
+                Vec3::new(3.5, 0.0, 0.0),
+                zero,
+                zero,
+            ]],
+            [0.0, 1.0],
+        )
+        .unwrap();
+        let gain = DistanceGain::new(line, zero, DistanceLaw::InverseDistance, 1.0).unwrap();
+        assert!((gain.gain(0.0) - 1.0).abs() < 1e-6);
+        assert!((gain.gain(1.0) - 0.25).abs() < 1e-6);
+        for domain in [GainDomain::Linear, GainDomain::Decibel] {
+            let spline = gain.to_spline(8, domain).unwrap();
+            assert_eq!(spline.grid().len(), 9);
+            assert!((spline.evaluate(1.0) - 0.25).abs() < 1e-5);
+            assert!((spline.evaluate(0.6) - gain.gain(0.6)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn exponent() {
+        let zero = Vec3::zeros();
+        let point =
+            PiecewiseCubicCurve::new([[Vec3::new(2.0, 0.0, 0.0), zero, zero, zero]], [0.0, 1.0])
+                .unwrap();
+        let gain = DistanceGain::new(point, zero, DistanceLaw::Exponent(2.0), 1.0).unwrap();
+        assert!((gain.evaluate(0.5) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let zero = Vec3::zeros();
+        let point = || {
+            PiecewiseCubicCurve::new([[Vec3::new(2.0, 0.0, 0.0), zero, zero, zero]], [0.0, 1.0])
+                .unwrap()
+        };
+        let law = DistanceLaw::InverseDistance;
+        for distance in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                DistanceGain::new(point(), zero, law, distance),
+                Err(Error::InvalidReferenceDistance { .. })
+            ));
+        }
+        for exponent in [-1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                DistanceGain::new(point(), zero, DistanceLaw::Exponent(exponent), 1.0),
+                Err(Error::InvalidExponent { .. })
+            ));
+        }
+        assert!(matches!(
+            DistanceGain::new(point(), Vec3::new(f32::NAN, 0.0, 0.0), law, 1.0),
+            Err(Error::NonFiniteListener)
+        ));
+        // Infinitely far away: -inf dB
+        let far = Vec3::new(f32::INFINITY, 0.0, 0.0);
+        let far = PiecewiseCubicCurve::new([[far, zero, zero, zero]], [0.0, 1.0]).unwrap();
+        let gain = DistanceGain::new(far, zero, law, 1.0).unwrap();
+        assert!(gain.to_spline(4, GainDomain::Linear).is_ok());
+        assert!(matches!(
+            gain.to_spline(4, GainDomain::Decibel),
+            Err(Error::NonFiniteGain { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Evaluation of many splines at the same time instant.
+//!
+//! This is meant for renderers which need e.g. the positions of all sources
+//! at the start of each audio block.
+
+use std::mem::size_of;
+
+use crate::memory::MemoryUsage;
+use crate::{PiecewiseCubicCurve, Spline, Vector};
+
+/// Evaluates all `splines` at time `t` and writes the results into `output`.
+///
+/// # Panics
+///
+/// If `splines` and `output` have different lengths.
+pub fn evaluate_ensemble<V, S>(splines: &[S], t: f32, output: &mut [V])
+where
+    S: Spline<V>,
+{
+    assert_eq!(splines.len(), output.len());
+    for (spline, o) in splines.iter().zip(output) {
+        *o = spline.evaluate(t);
+    }
+}
+
+/// Multiple [`PiecewiseCubicCurve`]s stored contiguously.
+///
+/// The segment index of each curve is cached between calls,
+/// which makes evaluating at (mostly) increasing times very cheap.
+/// The results are the same as with [`Spline::evaluate()`].
+pub struct CurveEnsemble<V> {
+    segments: Vec<[V; 4]>,
+    grids: Vec<f32>,
+    /// `1 / (t1 - t0)` for each segment of all curves.
+    inverse_durations: Vec<f32>,
+    /// Index of the first segment of each curve, plus the total number of segments.
+    offsets: Vec<usize>,
+    /// Most recently used segment index (relative to the curve's offset).
+    cursors: Vec<usize>,
+}
+
+impl<V: Vector> CurveEnsemble<V> {
+    pub fn new<'a>(curves: impl IntoIterator<Item = &'a PiecewiseCubicCurve<V>>) -> Self
+    where
+        V: 'a,
+    {
+        let mut ensemble = CurveEnsemble {
+            segments: Vec::new(),
+            grids: Vec::new(),
+            inverse_durations: Vec::new(),
+            offsets: vec![0],
+            cursors: Vec::new(),
+        };
+        for curve in curves {
+            ensemble.segments.extend_from_slice(curve.segments());
+            ensemble.grids.extend_from_slice(curve.grid());
+            ensemble
+                .inverse_durations
+                .extend(curve.grid().windows(2).map(|t| 1.0 / (t[1] - t[0])));
+            ensemble.offsets.push(ensemble.segments.len());
+            ensemble.cursors.push(0);
+        }
+        ensemble
+    }
+
+    /// Number of curves.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cursors.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cursors.is_empty()
+    }
+
+    /// Releases the excess capacity of the internal storage.
+    pub fn shrink_to_fit(&mut self) {
+        self.segments.shrink_to_fit();
+        self.grids.shrink_to_fit();
+        self.inverse_durations.shrink_to_fit();
+        self.offsets.shrink_to_fit();
+        self.cursors.shrink_to_fit();
+    }
+
+    /// Evaluates all curves at time `t` and writes the results into `output`.
+    ///
+    /// # Panics
+    ///
+    /// If `output` doesn't have the same length as [`len()`](Self::len).
+    pub fn evaluate_into(&mut self, t: f32, output: &mut [V]) {
+        assert_eq!(self.len(), output.len());
+        for (i, o) in output.iter_mut().enumerate() {
+            let offset = self.offsets[i];
+            let count = self.offsets[i + 1] - offset;
+            // Each curve has one more grid value than segments
+            let grid = &self.grids[offset + i..offset + i + count + 1];
+            let (t, idx) = find_index(grid, t, self.cursors[i]);
+            self.cursors[i] = idx;
+            let a = &self.segments[offset + idx];
+            let t = (t - grid[idx]) * self.inverse_durations[offset + idx];
+            *o = ((a[3] * t + a[2]) * t + a[1]) * t + a[0];
+        }
+    }
+}
+
+/// Includes excess capacity, see [`CurveEnsemble::shrink_to_fit()`].
+impl<V> MemoryUsage for CurveEnsemble<V> {
+    fn memory_usage(&self) -> usize {
+        self.segments.capacity() * size_of::<[V; 4]>()
+            + (self.grids.capacity() + self.inverse_durations.capacity()) * size_of::<f32>()
+            + (self.offsets.capacity() + self.cursors.capacity()) * size_of::<usize>()
+    }
+}
+
+/// Like [`Spline::clamp_parameter_and_find_index()`],
+/// but checking the segment `hint` (and its successor) first.
+fn find_index(grid: &[f32], t: f32, hint: usize) -> (f32, usize) {
+    let first = grid[0];
+    let last = grid[grid.len() - 1];
+    let contains = |idx: usize| idx + 1 < grid.len() && grid[idx] <= t && t < grid[idx + 1];
+    if t < first {
+        (first, 0)
+    } else if t < last {
+        if contains(hint) {
+            (t, hint)
+        } else if contains(hint + 1) {
+            (t, hint + 1)
+        } else {
+            (t, grid.partition_point(|&x| x <= t) - 1)
+        }
+    } else {
+        (last, grid.len() - 2)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::Vec3;
+
+    #[test]
+    fn ensemble() {
+        let curves: Vec<_> = (1..=5)
+            .map(|n| {
+                let segments: Vec<_> = (0..n)
+                    .map(|i| {
+                        let i = i as f32;
+                        [
+                            Vec3::new(i, n as f32, 0.0),
+                            Vec3::new(1.0, -i, 0.5),
+                            Vec3::new(0.0, 0.25, i),
+                            Vec3::new(-0.5, 0.0, 1.0),
+                        ]
+                    })
+                    .collect();
+                let grid: Vec<_> = (0..=n).map(|i| i as f32 * 3.0 / n as f32).collect();
+                PiecewiseCubicCurve::new(segments, grid).unwrap()
+            })
+            .collect();
+        let mut ensemble = CurveEnsemble::new(&curves);
+        assert_eq!(ensemble.len(), 5);
+        let mut output = vec![Vec3::zeros(); 5];
+        let mut expected = output.clone();
+        // Forwards, backwards and jumping around
+        let times = (0..50)
+            .map(|i| -0.5 + i as f32 * 0.1)
+            .chain((0..50).map(|i| 4.0 - i as f32 * 0.1))
+            .chain([2.9, 0.1, 3.0, 1.5, 1.5, 0.0]);
+        for t in times {
+            ensemble.evaluate_into(t, &mut output);
+            evaluate_ensemble(&curves, t, &mut expected);
+            assert_eq!(output, expected);
+        }
+        // Trait objects
+        let boxed: Vec<Box<dyn Spline<Vec3>>> = curves
+            .into_iter()
+            .map(|c| Box::new(c) as Box<dyn Spline<Vec3>>)
+            .collect();
+        evaluate_ensemble(&boxed, 1.25, &mut output);
+        ensemble.evaluate_into(1.25, &mut expected);
+        assert_eq!(output, expected);
+    }
+}
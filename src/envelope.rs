@@ -0,0 +1,381 @@
+//! Envelopes for parameter automation (e.g. gain or filter frequency).
+
+use std::mem::size_of_val;
+
+use crate::math;
+use crate::memory::MemoryUsage;
+use crate::utilities::{check_grid, GridError};
+use crate::{PiecewiseCubicCurve, Spline};
+
+/// Minimum number of cubic pieces used to approximate a curved segment,
+/// stronger curvatures use (roughly) one piece per unit of curvature.
+const SUBDIVISIONS: usize = 4;
+
+/// Larger curvatures would overflow the exponential function.
+pub const MAX_CURVATURE: f32 = 50.0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least two breakpoints")]
+    LessThanTwoBreakpoints,
+    #[error(
+        "index {index}: absolute segment curvature must be at most {MAX_CURVATURE} (not {curvature:?})"
+    )]
+    InvalidCurvature { index: usize, curvature: f32 },
+    #[error("index {index}: breakpoint time and value must be finite")]
+    NonFiniteBreakpoint { index: usize },
+    #[error("{phase} duration must be a positive number (not {duration:?})")]
+    InvalidPhaseDuration { phase: &'static str, duration: f32 },
+    #[error("name {name:?} is already in use")]
+    DuplicateName { name: String },
+    #[error("sample rate must be a positive number (not {sample_rate:?})")]
+    InvalidSampleRate { sample_rate: f32 },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+/// Shape of the segment leading to a breakpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentShape {
+    Linear,
+    /// Slow start, fast end; larger curvature values mean stronger bending.
+    ///
+    /// The absolute value of the curvature is limited to [`MAX_CURVATURE`].
+    Exponential(f32),
+    /// Fast start, slow end; larger curvature values mean stronger bending.
+    ///
+    /// The absolute value of the curvature is limited to [`MAX_CURVATURE`].
+    Logarithmic(f32),
+}
+
+impl SegmentShape {
+    fn curvature(&self) -> f32 {
+        match *self {
+            SegmentShape::Linear => 0.0,
+            SegmentShape::Exponential(curvature) => curvature,
+            SegmentShape::Logarithmic(curvature) => -curvature,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub time: f32,
+    pub value: f32,
+    /// Ignored for the first breakpoint.
+    pub shape: SegmentShape,
+    pub name: Option<String>,
+}
+
+impl Breakpoint {
+    #[must_use]
+    pub fn new(time: f32, value: f32) -> Breakpoint {
+        Breakpoint {
+            time,
+            value,
+            shape: SegmentShape::Linear,
+            name: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_shape(mut self, shape: SegmentShape) -> Breakpoint {
+        self.shape = shape;
+        self
+    }
+
+    #[must_use]
+    pub fn named(mut self, name: impl Into<String>) -> Breakpoint {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+pub struct Envelope {
+    curve: PiecewiseCubicCurve<f32>,
+    breakpoints: Box<[Breakpoint]>,
+}
+
+impl Envelope {
+    pub fn new(breakpoints: impl Into<Vec<Breakpoint>>) -> Result<Envelope, Error> {
+        use Error::*;
+        let breakpoints = breakpoints.into();
+        if breakpoints.len() < 2 {
+            return Err(LessThanTwoBreakpoints);
+        }
+        let times: Vec<_> = breakpoints.iter().map(|b| b.time).collect();
+        check_grid(&times)?;
+        for (index, b) in breakpoints.iter().enumerate() {
+            if !b.time.is_finite() || !b.value.is_finite() {
+                return Err(NonFiniteBreakpoint { index });
+            }
+            let curvature = b.shape.curvature();
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if index > 0 && !(curvature.abs() <= MAX_CURVATURE) {
+                return Err(InvalidCurvature { index, curvature });
+            }
+            if let Some(name) = &b.name {
+                if breakpoints[..index]
+                    .iter()
+                    .any(|other| other.name.as_ref() == Some(name))
+                {
+                    return Err(DuplicateName { name: name.clone() });
+                }
+            }
+        }
+        let mut positions = vec![breakpoints[0].value];
+        let mut tangents = Vec::new();
+        let mut grid = vec![breakpoints[0].time];
+        for pair in breakpoints.windows(2) {
+            let (begin, end) = (&pair[0], &pair[1]);
+            let duration = end.time - begin.time;
+            let delta = end.value - begin.value;
+            let curvature = end.shape.curvature();
+            if curvature == 0.0 {
+                let slope = delta / duration;
+                positions.push(end.value);
+                tangents.extend([slope, slope]);
+                grid.push(end.time);
+                continue;
+            }
+            // Normalized shape: (exp(c * x) - 1) / (exp(c) - 1)
+            let scale = 1.0 / math::exp_m1(curvature);
+            let shape = |x: f32| math::exp_m1(curvature * x) * scale;
+            let slope = |x: f32| curvature * math::exp(curvature * x) * scale * delta / duration;
+            let subdivisions = (curvature.abs().ceil() as usize).max(SUBDIVISIONS);
+            for i in 1..=subdivisions {
+                let x0 = (i - 1) as f32 / subdivisions as f32;
+                let x1 = i as f32 / subdivisions as f32;
+                if i == subdivisions {
+                    positions.push(end.value);
+                    grid.push(end.time);
+                } else {
+                    positions.push(begin.value + delta * shape(x1));
+                    grid.push(begin.time + duration * x1);
+                }
+                tangents.extend([slope(x0), slope(x1)]);
+            }
+        }
+        let curve =
+            PiecewiseCubicCurve::new_hermite(&positions, &tangents, &grid).map_err(|err| {
+                use crate::cubichermitespline::Error as E;
+                match err {
+                    E::LessThanTwoPositions => unreachable!(),
+                    E::TangentsVsSegments { .. } => unreachable!(),
+                    E::GridVsPositions { .. } => unreachable!(),
+                    // Sub-divided grid might be non-ascending due to rounding
+                    E::FromGridError(e) => e,
+                }
+            })?;
+        Ok(Envelope {
+            curve,
+            breakpoints: breakpoints.into(),
+        })
+    }
+
+    /// Linear attack/decay/release, starting (and ending) at zero.
+    ///
+    /// Breakpoints are named `"start"`, `"attack"`, `"decay"`, `"release"` and `"end"`.
+    /// All durations must be positive, because the breakpoint times must be ascending.
+    pub fn adsr(
+        attack: f32,
+        decay: f32,
+        sustain_level: f32,
+        sustain_duration: f32,
+        release: f32,
+    ) -> Result<Envelope, Error> {
+        for (phase, duration) in [
+            ("attack", attack),
+            ("decay", decay),
+            ("sustain", sustain_duration),
+            ("release", release),
+        ] {
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(duration > 0.0) || duration.is_infinite() {
+                return Err(Error::InvalidPhaseDuration { phase, duration });
+            }
+        }
+        let attack_end = attack;
+        let decay_end = attack_end + decay;
+        let release_begin = decay_end + sustain_duration;
+        let release_end = release_begin + release;
+        Envelope::new(vec![
+            Breakpoint::new(0.0, 0.0).named("start"),
+            Breakpoint::new(attack_end, 1.0).named("attack"),
+            Breakpoint::new(decay_end, sustain_level).named("decay"),
+            Breakpoint::new(release_begin, sustain_level).named("release"),
+            Breakpoint::new(release_end, 0.0).named("end"),
+        ])
+    }
+
+    #[must_use]
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    #[must_use]
+    pub fn breakpoint(&self, name: &str) -> Option<&Breakpoint> {
+        self.breakpoints
+            .iter()
+            .find(|b| b.name.as_deref() == Some(name))
+    }
+
+    #[must_use]
+    pub fn curve(&self) -> &PiecewiseCubicCurve<f32> {
+        &self.curve
+    }
+
+    /// Samples the whole envelope, including the last breakpoint
+    /// if it falls onto a sample.
+    pub fn render(&self, sample_rate: f32) -> Result<Vec<f32>, Error> {
+        let start = self.breakpoints.first().unwrap().time;
+        let end = self.breakpoints.last().unwrap().time;
+        check_sample_rate(sample_rate)?;
+        let mut buffer = vec![0.0; ((end - start) * sample_rate).floor() as usize + 1];
+        self.render_into(start, sample_rate, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Fills `buffer` with samples, beginning at time `start`.
+    pub fn render_into(
+        &self,
+        start: f32,
+        sample_rate: f32,
+        buffer: &mut [f32],
+    ) -> Result<(), Error> {
+        check_sample_rate(sample_rate)?;
+        for (i, sample) in buffer.iter_mut().enumerate() {
+            *sample = self.curve.evaluate(start + i as f32 / sample_rate);
+        }
+        Ok(())
+    }
+}
+
+fn check_sample_rate(sample_rate: f32) -> Result<(), Error> {
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(sample_rate > 0.0) || sample_rate.is_infinite() {
+        return Err(Error::InvalidSampleRate { sample_rate });
+    }
+    Ok(())
+}
+
+impl Spline<f32> for Envelope {
+    fn evaluate(&self, t: f32) -> f32 {
+        self.curve.evaluate(t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.curve.grid()
+    }
+}
+
+impl MemoryUsage for Envelope {
+    fn memory_usage(&self) -> usize {
+        let names: usize = self
+            .breakpoints
+            .iter()
+            .filter_map(|b| b.name.as_ref())
+            .map(String::capacity)
+            .sum();
+        self.curve.memory_usage() + size_of_val(&*self.breakpoints) + names
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adsr() {
+        let env = Envelope::adsr(0.5, 0.5, 0.5, 1.0, 1.0).unwrap();
+        assert_eq!(env.breakpoint("release").unwrap().time, 2.0);
+        assert_eq!(env.evaluate(0.25), 0.5);
+        assert_eq!(env.evaluate(1.5), 0.5);
+        assert_eq!(env.evaluate(2.5), 0.25);
+        let samples = env.render(4.0).unwrap();
+        assert_eq!(samples.len(), 13);
+        assert_eq!(samples[2], 1.0);
+        assert_eq!(samples[12], 0.0);
+        assert!(matches!(
+            env.render(0.0),
+            Err(Error::InvalidSampleRate { .. })
+        ));
+        assert!(matches!(
+            Envelope::adsr(0.0, 0.5, 0.5, 1.0, 1.0),
+            Err(Error::InvalidPhaseDuration {
+                phase: "attack",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Envelope::adsr(0.1, 0.1, f32::NAN, 1.0, 0.2),
+            Err(Error::NonFiniteBreakpoint { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn shapes() {
+        let exp = Envelope::new(vec![
+            Breakpoint::new(0.0, 0.0),
+            Breakpoint::new(1.0, 1.0).with_shape(SegmentShape::Exponential(4.0)),
+        ])
+        .unwrap();
+        let log = Envelope::new(vec![
+            Breakpoint::new(0.0, 0.0),
+            Breakpoint::new(1.0, 1.0).with_shape(SegmentShape::Logarithmic(4.0)),
+        ])
+        .unwrap();
+        let expected = math::exp_m1(2.0) / math::exp_m1(4.0);
+        assert!((exp.evaluate(0.5) - expected).abs() < 1e-3);
+        assert!((log.evaluate(0.5) - (1.0 - expected)).abs() < 1e-3);
+        assert!((exp.evaluate(1.0) - 1.0).abs() < 1e-6);
+        let samples = exp.render(100.0).unwrap();
+        assert!(samples.windows(2).all(|w| w[0] <= w[1]));
+        for shape in [
+            SegmentShape::Exponential(MAX_CURVATURE),
+            SegmentShape::Logarithmic(MAX_CURVATURE),
+        ] {
+            let steep = Envelope::new(vec![
+                Breakpoint::new(0.0, 0.0),
+                Breakpoint::new(1.0, 1.0).with_shape(shape),
+            ])
+            .unwrap();
+            let samples = steep.render(1000.0).unwrap();
+            assert!(samples.iter().all(|x| (-1e-6..=1.0 + 1e-6).contains(x)));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let result = Envelope::new(vec![
+            Breakpoint::new(0.0, 0.0).named("a"),
+            Breakpoint::new(1.0, 1.0).named("a"),
+        ]);
+        assert!(matches!(result, Err(Error::DuplicateName { .. })));
+        let result = Envelope::new(vec![Breakpoint::new(1.0, 0.0), Breakpoint::new(1.0, 1.0)]);
+        assert!(matches!(result, Err(Error::FromGridError(_))));
+        let result = Envelope::new(vec![
+            Breakpoint::new(0.0, 0.0),
+            Breakpoint::new(f32::INFINITY, 1.0),
+        ]);
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteBreakpoint { index: 1 })
+        ));
+        let result = Envelope::new(vec![Breakpoint::new(0.0, 0.0)]);
+        assert!(matches!(result, Err(Error::LessThanTwoBreakpoints)));
+        for shape in [
+            SegmentShape::Exponential(f32::NAN),
+            SegmentShape::Exponential(f32::INFINITY),
+            SegmentShape::Exponential(2.0 * MAX_CURVATURE),
+            SegmentShape::Logarithmic(-2.0 * MAX_CURVATURE),
+        ] {
+            let result = Envelope::new(vec![
+                Breakpoint::new(0.0, 0.0),
+                Breakpoint::new(1.0, 1.0).with_shape(shape),
+            ]);
+            assert!(matches!(result, Err(Error::InvalidCurvature { .. })));
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Content hashes of splines, e.g. for invalidating cached data.
+//!
+//! Fingerprints only depend on the bit patterns of grid values and coefficients,
+//! they are the same across program runs and platforms.
+//!
+//! [`Fingerprint`] is implemented for all splines that own their data,
+//! and for the adapters used in [`AsdfRotSpline`](crate::AsdfRotSpline).
+//! It is not implemented for [`Timeline`](crate::timeline::Timeline)
+//! (which contains arbitrary boxed splines) and for the other adapters.
+
+use nalgebra::{SVector, UnitDualQuaternion};
+
+use crate::distancegain::{GainDomain, GainSpline};
+use crate::envelope::Envelope;
+use crate::multispline::MultiSpline;
+use crate::quaternion::{UnitQuaternion, Vec3};
+use crate::{
+    AsdfPosSpline, AsdfPoseSpline, MonotoneCubicSpline, NormWrapper, PiecewiseCubicCurve, Spline,
+    Vector,
+};
+
+/// 64-bit FNV-1a hash.
+///
+/// <http://www.isthe.com/chongo/tech/comp/fnv/>
+pub struct FingerprintHasher(u64);
+
+impl FingerprintHasher {
+    #[must_use]
+    pub fn new() -> FingerprintHasher {
+        FingerprintHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    #[must_use]
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for FingerprintHasher {
+    fn default() -> Self {
+        FingerprintHasher::new()
+    }
+}
+
+pub trait Fingerprint {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher);
+
+    #[must_use]
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = FingerprintHasher::new();
+        self.write_fingerprint(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Fingerprint for f32 {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        hasher.write_u32(self.to_bits());
+    }
+}
+
+impl Fingerprint for bool {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        hasher.write_bytes(&[*self as u8]);
+    }
+}
+
+impl<const D: usize> Fingerprint for SVector<f32, D> {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.as_slice().write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for UnitQuaternion {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.coords.write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for UnitDualQuaternion<f32> {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.as_ref().real.coords.write_fingerprint(hasher);
+        self.as_ref().dual.coords.write_fingerprint(hasher);
+    }
+}
+
+impl<T: Fingerprint> Fingerprint for Option<T> {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        match self {
+            Some(value) => {
+                true.write_fingerprint(hasher);
+                value.write_fingerprint(hasher);
+            }
+            None => false.write_fingerprint(hasher),
+        }
+    }
+}
+
+impl<T: Fingerprint, const N: usize> Fingerprint for [T; N] {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        for value in self {
+            value.write_fingerprint(hasher);
+        }
+    }
+}
+
+/// The length is included.
+impl<T: Fingerprint> Fingerprint for [T] {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        hasher.write_u64(self.len() as u64);
+        for value in self {
+            value.write_fingerprint(hasher);
+        }
+    }
+}
+
+impl<V: Vector + Fingerprint> Fingerprint for PiecewiseCubicCurve<V> {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.segments().write_fingerprint(hasher);
+        self.grid().write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for MonotoneCubicSpline {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.inner_ref().write_fingerprint(hasher);
+    }
+}
+
+impl<V, U> Fingerprint for AsdfPosSpline<V, U>
+where
+    V: Vector + NormWrapper<U> + Fingerprint,
+{
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.positions().write_fingerprint(hasher);
+        self.times().write_fingerprint(hasher);
+        self.speeds().write_fingerprint(hasher);
+        self.waits().write_fingerprint(hasher);
+        self.tcb().write_fingerprint(hasher);
+        self.closed().write_fingerprint(hasher);
+    }
+}
+
+impl<U> Fingerprint for AsdfPoseSpline<U>
+where
+    Vec3: NormWrapper<U>,
+{
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.position().write_fingerprint(hasher);
+        self.rotation().write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for MultiSpline {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        hasher.write_u64(self.channels() as u64);
+        self.coefficients().write_fingerprint(hasher);
+        self.grid().write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for Envelope {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.curve().write_fingerprint(hasher);
+    }
+}
+
+impl Fingerprint for GainSpline {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.curve().write_fingerprint(hasher);
+        (self.domain() == GainDomain::Decibel).write_fingerprint(hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, Vec3};
+    use crate::{AsdfPosSpline1, AsdfRotSpline};
+
+    #[test]
+    fn stable() {
+        assert_eq!(FingerprintHasher::new().finish(), 0xcbf2_9ce4_8422_2325);
+        let mut hasher = FingerprintHasher::new();
+        hasher.write_bytes(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn changes() {
+        let a = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 1.0]).unwrap();
+        let b = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 1.0]).unwrap();
+        let c = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+
+        let v = |x| Vec3::new(x, 0.0, 0.0);
+        let d = PiecewiseCubicCurve::new([[v(0.0), v(1.0), v(0.0), v(0.0)]], [0.0, 1.0]).unwrap();
+        let e = PiecewiseCubicCurve::new([[v(0.0), v(1.5), v(0.0), v(0.0)]], [0.0, 1.0]).unwrap();
+        assert_ne!(d.fingerprint(), e.fingerprint());
+
+        let p = |speed| {
+            AsdfPosSpline1::new([1.0, 2.0], [Some(0.0), Some(3.0)], [None, speed], [], false)
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(p(None), p(None));
+        assert_ne!(p(None), p(Some(0.5)));
+
+        let r = |angle| {
+            AsdfRotSpline::new(
+                [angles2quat(0.0, 0.0, 0.0), angles2quat(angle, 0.0, 0.0)],
+                [Some(0.0), Some(1.0)],
+                [],
+                false,
+            )
+            .unwrap()
+            .fingerprint()
+        };
+        assert_eq!(r(90.0), r(90.0));
+        assert_ne!(r(90.0), r(45.0));
+    }
+
+    #[test]
+    fn keyframe_splines() {
+        use crate::quaternion::piecewiseslerp::PiecewiseSlerp;
+        use crate::quaternion::squad::Squad;
+        use crate::quaternion::Isometry3;
+        use crate::screwspline::ScrewSpline;
+
+        let quaternions = |angle| {
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(angle, 0.0, 0.0),
+                angles2quat(angle, 30.0, 0.0),
+            ]
+        };
+        let grid = |end| [0.0, 1.0, end];
+        let slerp = |angle, end| {
+            PiecewiseSlerp::new(quaternions(angle), grid(end), false)
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(slerp(90.0, 2.0), slerp(90.0, 2.0));
+        assert_ne!(slerp(90.0, 2.0), slerp(45.0, 2.0));
+        assert_ne!(slerp(90.0, 2.0), slerp(90.0, 3.0));
+        let squad = |angle, end| {
+            Squad::new(quaternions(angle), grid(end), false)
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(squad(90.0, 2.0), squad(90.0, 2.0));
+        assert_ne!(squad(90.0, 2.0), squad(45.0, 2.0));
+        assert_ne!(squad(90.0, 2.0), squad(90.0, 3.0));
+        let screw = |x, end| {
+            let poses =
+                quaternions(90.0).map(|q| Isometry3::from_parts(Vec3::new(x, 0.0, 0.0).into(), q));
+            ScrewSpline::new(poses, grid(end), false)
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(screw(1.0, 2.0), screw(1.0, 2.0));
+        assert_ne!(screw(1.0, 2.0), screw(2.0, 2.0));
+        assert_ne!(screw(1.0, 2.0), screw(1.0, 3.0));
+    }
+}
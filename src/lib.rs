@@ -9,22 +9,53 @@ use std::ops::{Add, Div, DivAssign, Mul, Sub};
 use superslice::Ext; // for slice::upper_bound_by()
 
 pub mod adapters;
+pub mod asdfposespline;
 pub mod asdfposspline;
 pub mod asdfrotspline;
 pub mod centripetalkochanekbartelsspline;
+pub mod continuity;
+pub mod conventions;
 pub mod cubichermitespline;
+pub mod deviation;
+pub mod distancegain;
+pub mod ensemble;
+pub mod envelope;
+pub mod fingerprint;
+pub mod lookat;
+mod math;
+pub mod memory;
 pub mod monotonecubicspline;
+pub mod morph;
+pub mod motionlimits;
+pub mod multispline;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod piecewisecubiccurve;
 pub mod piecewisemonotonecubicspline;
+pub mod planar;
+pub mod presets;
 pub mod quaternion;
+#[cfg(feature = "reference")]
+pub mod reference;
+pub mod rollfree;
+pub mod roombounds;
+pub mod rotationminimizingframes;
+pub mod samplegrid;
+pub mod screwspline;
+pub mod spherical;
+pub mod tempomap;
+pub mod timeline;
+pub mod trajectoryset;
 pub mod utilities;
+pub mod wobble;
 
-pub use crate::asdfposspline::AsdfPosSpline;
-pub use crate::asdfrotspline::AsdfRotSpline;
+pub use crate::asdfposespline::AsdfPoseSpline;
+pub use crate::asdfposspline::{AsdfPosSpline, AsdfPosSpline1, AsdfPosSplineBuilder};
+pub use crate::asdfrotspline::{AsdfLogRotSpline, AsdfRotSpline};
 pub use crate::monotonecubicspline::MonotoneCubicSpline;
 pub use crate::piecewisecubiccurve::PiecewiseCubicCurve;
 
-use crate::utilities::gauss_legendre13;
+use crate::utilities::Quadrature;
 
 /// A trait that is automatically implemented for all types that can be used as positions,
 /// polynomial coefficients, tangent vectors etc.
@@ -68,6 +99,43 @@ pub trait Spline<Value> {
             (last, self.grid().len() - 2)
         }
     }
+
+    /// Evaluates at the times `start + i * step` and writes the results into `output`.
+    ///
+    /// This doesn't allocate, which makes it suitable for real-time block processing.
+    /// The exceptions are:
+    ///
+    /// * lazily computed arc lengths and lookup tables,
+    ///   see [`ConstantSpeedAdapter::prepare()`](adapters::ConstantSpeedAdapter::prepare)
+    /// * values that own heap memory, like the `Vec<f32>` of
+    ///   [`MultiSpline`](multispline::MultiSpline)
+    ///   (use [`MultiSpline::evaluate_into()`](multispline::MultiSpline::evaluate_into) instead)
+    fn sample_range_into(&self, start: f32, step: f32, output: &mut [Value]) {
+        for (i, o) in output.iter_mut().enumerate() {
+            *o = self.evaluate(start + i as f32 * step);
+        }
+    }
+}
+
+impl<Value, S> Spline<Value> for Box<S>
+where
+    S: Spline<Value> + ?Sized,
+{
+    fn evaluate(&self, t: f32) -> Value {
+        (**self).evaluate(t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        (**self).grid()
+    }
+
+    fn clamp_parameter_and_find_index(&self, t: f32) -> (f32, usize) {
+        (**self).clamp_parameter_and_find_index(t)
+    }
+
+    fn sample_range_into(&self, start: f32, step: f32, output: &mut [Value]) {
+        (**self).sample_range_into(start, step, output);
+    }
 }
 
 /// To work around Rust's orphan rules, see <https://blog.mgattozzi.dev/orphan-rules/>
@@ -82,12 +150,21 @@ where
     fn evaluate_velocity(&self, t: f32) -> Velocity;
 
     fn integrated_speed<U>(&self, index: usize, a: f32, b: f32) -> f32
+    where
+        Velocity: NormWrapper<U>,
+    {
+        self.integrated_speed_with(index, a, b, Quadrature::default())
+    }
+
+    /// Like [`integrated_speed()`](Self::integrated_speed),
+    /// but with the given integration method.
+    fn integrated_speed_with<U>(&self, index: usize, a: f32, b: f32, quadrature: Quadrature) -> f32
     where
         Velocity: NormWrapper<U>,
     {
         assert!(a <= b);
         assert!(self.grid()[index] <= a);
         assert!(b <= self.grid()[index + 1]);
-        gauss_legendre13(|t| self.evaluate_velocity(t).norm(), a, b)
+        quadrature.integrate(|t| self.evaluate_velocity(t).norm(), a, b)
     }
 }
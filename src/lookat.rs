@@ -0,0 +1,286 @@
+//! Orientations derived from position splines.
+//!
+//! The forward direction is the y axis and there is no roll,
+//! see [`angles2quat()`](crate::quaternion::angles2quat).
+
+use crate::quaternion::{angles2quat, CubicDeCasteljau, UnitQuaternion, Vec3};
+use crate::spherical::Spherical;
+use crate::utilities::subdivide_grid;
+use crate::{AsdfRotSpline, Spline, SplineWithVelocity};
+
+/// Smallest angle (in radians) between consecutive keyframes.
+const MIN_ANGLE: f32 = 1e-3;
+
+pub enum LookAtTarget {
+    /// Face along the direction of movement.
+    Velocity,
+    Fixed(Vec3),
+    Moving(Box<dyn Spline<Vec3>>),
+}
+
+/// Rotation that turns the y axis towards `direction` (without roll).
+///
+/// For a zero vector, the identity rotation is returned.
+#[must_use]
+pub fn look_along(direction: Vec3) -> UnitQuaternion {
+    let s = Spherical::from_cartesian(direction);
+    angles2quat(s.azimuth, s.elevation, 0.0)
+}
+
+/// Skips samples that are (nearly) equal to their predecessor.
+///
+/// The last sample of each skipped run is kept,
+/// which makes the following rotation start at the right time.
+/// If it is equal to its predecessor, this leads to a "hold" segment,
+/// see [`keyframe_spline()`].
+pub(crate) fn thin_out_keyframes(
+    samples: impl IntoIterator<Item = (f32, UnitQuaternion)>,
+) -> (Vec<UnitQuaternion>, Vec<Option<f32>>) {
+    let mut quaternions: Vec<UnitQuaternion> = Vec::new();
+    let mut times = Vec::new();
+    let mut skipped = None;
+    for (t, q) in samples {
+        match quaternions.last() {
+            Some(previous) if previous.angle_to(&q) < MIN_ANGLE => {
+                skipped = Some((t, q));
+                continue;
+            }
+            _ => {}
+        }
+        if let Some((t, q)) = skipped.take() {
+            quaternions.push(q);
+            times.push(Some(t));
+        }
+        quaternions.push(q);
+        times.push(Some(t));
+    }
+    if let Some((t, q)) = skipped {
+        quaternions.push(q);
+        times.push(Some(t));
+    }
+    (quaternions, times)
+}
+
+/// Creates a rotation spline from the result of [`thin_out_keyframes()`].
+///
+/// Consecutive equal quaternions (which are not allowed in [`AsdfRotSpline::new()`])
+/// lead to a "hold" segment, the remaining parts are interpolated separately.
+pub(crate) fn keyframe_spline(
+    quaternions: Vec<UnitQuaternion>,
+    times: Vec<Option<f32>>,
+) -> Result<AsdfRotSpline, crate::asdfrotspline::Error> {
+    use crate::asdfrotspline::Error::*;
+    let is_hold = |i: usize| quaternions[i - 1].angle_to(&quaternions[i]) == 0.0;
+    if !(1..quaternions.len()).any(|i| !is_hold(i)) {
+        return Err(LessThanTwoQuaternions);
+    }
+    let mut control_polygon = vec![quaternions[0]];
+    let mut grid = vec![0.0];
+    let mut start = 0;
+    for end in 1..=quaternions.len() {
+        let hold = end < quaternions.len() && is_hold(end);
+        if !hold && end < quaternions.len() {
+            continue;
+        }
+        if end - start > 1 {
+            // Continue from the (possibly negated) previous control point
+            let run: Vec<_> = std::iter::once(*control_polygon.last().unwrap())
+                .chain(quaternions[start + 1..end].iter().copied())
+                .collect();
+            let tcb = vec![[0.0; 3]; run.len() - 2];
+            let spline = CubicDeCasteljau::new_centripetal_kochanek_bartels(run, &tcb, false)
+                .map_err(|e| {
+                    use crate::quaternion::centripetalkochanekbartelsspline::Error as E;
+                    match e {
+                        E::RepeatedQuaternion { index } => RepeatedQuaternion {
+                            index: start + index,
+                        },
+                        E::LessThanTwoQuaternions => unreachable!(),
+                        E::TcbVsQuaternions { .. } => unreachable!(),
+                        E::LongRotation { .. } => unreachable!(),
+                        E::TangentsVsQuaternions { .. } => unreachable!(),
+                        E::InvalidExponent { .. } => unreachable!(),
+                        E::WindingsVsQuaternions { .. } => unreachable!(),
+                        E::TooLongRotation { index } => TooLongRotation {
+                            index: start + index,
+                        },
+                    }
+                })?;
+            let (points, run_grid) = spline.into_parts();
+            let offset = *grid.last().unwrap();
+            control_polygon.extend_from_slice(&points[1..]);
+            grid.extend(run_grid[1..].iter().map(|t| offset + t));
+        }
+        if hold {
+            let q = *control_polygon.last().unwrap();
+            control_polygon.extend_from_slice(&[q, q, q]);
+            grid.push(*grid.last().unwrap() + 1.0);
+        }
+        start = end;
+    }
+    let path = CubicDeCasteljau::new(control_polygon, grid).map_err(|e| {
+        use crate::quaternion::cubicdecasteljau::Error as E;
+        match e {
+            E::GridTooShort => unreachable!(),
+            E::GridVsControlPolygon { .. } => unreachable!(),
+            E::FromGridError(_) => unreachable!(),
+        }
+    })?;
+    AsdfRotSpline::from_path(path, &times)
+}
+
+pub struct LookAtAdapter<Inner> {
+    inner: Inner,
+    target: LookAtTarget,
+}
+
+impl<Inner> LookAtAdapter<Inner>
+where
+    Inner: SplineWithVelocity<Vec3, Vec3>,
+{
+    pub fn adapt(inner: Inner, target: LookAtTarget) -> LookAtAdapter<Inner> {
+        LookAtAdapter { inner, target }
+    }
+
+    #[must_use]
+    pub fn target(&self) -> &LookAtTarget {
+        &self.target
+    }
+
+    fn direction(&self, t: f32) -> Vec3 {
+        match &self.target {
+            LookAtTarget::Velocity => self.inner.evaluate_velocity(t),
+            LookAtTarget::Fixed(target) => target - self.inner.evaluate(t),
+            LookAtTarget::Moving(target) => target.evaluate(t) - self.inner.evaluate(t),
+        }
+    }
+
+    /// Samples orientations, see [`to_rotation_spline()`](Self::to_rotation_spline).
+    ///
+    /// Each segment is divided into `samples_per_segment` parts.
+    /// Where the direction is undefined (e.g. when not moving),
+    /// the previous orientation is kept.
+    /// Samples (nearly) equal to their predecessor are skipped,
+    /// except for the last one before the orientation changes again.
+    /// Therefore, the result may contain repeated quaternions
+    /// (which are not allowed in [`AsdfRotSpline::new()`]).
+    #[must_use]
+    pub fn keyframes(&self, samples_per_segment: usize) -> (Vec<UnitQuaternion>, Vec<Option<f32>>) {
+        let times = subdivide_grid(self.inner.grid(), samples_per_segment);
+        let mut current = UnitQuaternion::identity();
+        let samples = times.into_iter().map(|t| {
+            let direction = self.direction(t);
+            if direction != Vec3::zeros() {
+                current = look_along(direction);
+            }
+            (t, current)
+        });
+        thin_out_keyframes(samples)
+    }
+
+    /// Creates a rotation spline from [`keyframes()`](Self::keyframes).
+    ///
+    /// Repeated quaternions lead to "hold" segments.
+    ///
+    /// If the orientation never changes, this fails with
+    /// [`LessThanTwoQuaternions`](crate::asdfrotspline::Error::LessThanTwoQuaternions).
+    pub fn to_rotation_spline(
+        &self,
+        samples_per_segment: usize,
+    ) -> Result<AsdfRotSpline, crate::asdfrotspline::Error> {
+        let (quaternions, times) = self.keyframes(samples_per_segment);
+        keyframe_spline(quaternions, times)
+    }
+}
+
+impl<Inner> Spline<UnitQuaternion> for LookAtAdapter<Inner>
+where
+    Inner: SplineWithVelocity<Vec3, Vec3>,
+{
+    /// Where the direction is undefined, the identity rotation is returned.
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        look_along(self.direction(t))
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    fn quarter_circle() -> PiecewiseCubicCurve<Vec3> {
+        PiecewiseCubicCurve::new_hermite(
+            &[Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)],
+            &[Vec3::new(0.0, 1.0, 0.0), Vec3::new(-1.0, 0.0, 0.0)],
+            &[0.0, 1.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn velocity() {
+        let s = LookAtAdapter::adapt(quarter_circle(), LookAtTarget::Velocity);
+        assert!(s.evaluate(0.0).angle() < 1e-6);
+        let end = s.evaluate(1.0);
+        assert!(end.angle_to(&angles2quat(90.0, 0.0, 0.0)) < 1e-5);
+        let rot = s.to_rotation_spline(4).unwrap();
+        assert!(rot.evaluate(1.0).angle_to(&end) < 1e-4);
+    }
+
+    #[test]
+    fn fixed_target() {
+        let s = LookAtAdapter::adapt(quarter_circle(), LookAtTarget::Fixed(Vec3::zeros()));
+        assert!(s.evaluate(0.0).angle_to(&angles2quat(90.0, 0.0, 0.0)) < 1e-5);
+        assert!(s.evaluate(1.0).angle_to(&angles2quat(180.0, 0.0, 0.0)) < 1e-5);
+        let up = LookAtAdapter::adapt(
+            quarter_circle(),
+            LookAtTarget::Fixed(Vec3::new(1.0, 0.0, 1.0)),
+        );
+        assert!(up.evaluate(0.0).angle_to(&angles2quat(0.0, 90.0, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn keyframes_of_straight_line() {
+        let zero = Vec3::zeros();
+        let line =
+            PiecewiseCubicCurve::new([[zero, Vec3::new(1.0, 0.0, 0.0), zero, zero]], [0.0, 2.0])
+                .unwrap();
+        let s = LookAtAdapter::adapt(line, LookAtTarget::Velocity);
+        let (quaternions, times) = s.keyframes(8);
+        assert_eq!(quaternions.len(), 2);
+        assert_eq!(quaternions[0], quaternions[1]);
+        assert_eq!(times, [Some(0.0), Some(2.0)]);
+        assert!(matches!(
+            s.to_rotation_spline(8),
+            Err(crate::asdfrotspline::Error::LessThanTwoQuaternions)
+        ));
+    }
+
+    /// Straight line along the y axis from `t = 0` to `t = 9`, then a left turn until `t = 10`.
+    fn straight_then_turn() -> PiecewiseCubicCurve<Vec3> {
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let x = Vec3::new(-1.0, 0.0, 0.0);
+        PiecewiseCubicCurve::new_hermite(
+            &[Vec3::zeros(), y * 9.0, y * 10.0 + x],
+            &[y, y, y, x],
+            &[0.0, 9.0, 10.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn straight_then_turn_keyframes() {
+        let s = LookAtAdapter::adapt(straight_then_turn(), LookAtTarget::Velocity);
+        let rot = s.to_rotation_spline(4).unwrap();
+        for t in [0.0, 3.0, 6.0, 8.5, 9.0] {
+            assert!(rot.evaluate(t).angle() < 1e-4);
+        }
+        assert!(rot.evaluate(10.0).angle_to(&s.evaluate(10.0)) < 1e-4);
+        assert!(s.evaluate(10.0).angle_to(&angles2quat(90.0, 0.0, 0.0)) < 1e-4);
+    }
+}
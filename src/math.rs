@@ -0,0 +1,75 @@
+//! Transcendental functions, optionally using `libm` (see the `libm` feature).
+//!
+//! With `libm`, results are the same on all platforms,
+//! otherwise the platform's implementations are used (via `std`).
+//! The same feature is also enabled in `nalgebra`.
+//!
+//! The `std` functions are disallowed elsewhere, see `.clippy.toml`.
+
+#![allow(clippy::disallowed_methods)]
+
+macro_rules! unary {
+    ($($name:ident => $libm:ident,)*) => {
+        $(
+            #[cfg(feature = "libm")]
+            #[inline]
+            pub(crate) fn $name(x: f32) -> f32 {
+                libm::$libm(x)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            #[inline]
+            pub(crate) fn $name(x: f32) -> f32 {
+                x.$name()
+            }
+        )*
+    };
+}
+
+macro_rules! binary {
+    ($($name:ident => $libm:ident,)*) => {
+        $(
+            #[cfg(feature = "libm")]
+            #[inline]
+            pub(crate) fn $name(x: f32, y: f32) -> f32 {
+                libm::$libm(x, y)
+            }
+
+            #[cfg(not(feature = "libm"))]
+            #[inline]
+            pub(crate) fn $name(x: f32, y: f32) -> f32 {
+                x.$name(y)
+            }
+        )*
+    };
+}
+
+unary! {
+    sqrt => sqrtf,
+    sin => sinf,
+    cos => cosf,
+    tanh => tanhf,
+    exp => expf,
+    exp_m1 => expm1f,
+    log10 => log10f,
+}
+
+binary! {
+    atan2 => atan2f,
+    hypot => hypotf,
+    powf => powf,
+}
+
+/// Returns `(sin(x), cos(x))`.
+#[cfg(feature = "libm")]
+#[inline]
+pub(crate) fn sin_cos(x: f32) -> (f32, f32) {
+    libm::sincosf(x)
+}
+
+/// Returns `(sin(x), cos(x))`.
+#[cfg(not(feature = "libm"))]
+#[inline]
+pub(crate) fn sin_cos(x: f32) -> (f32, f32) {
+    x.sin_cos()
+}
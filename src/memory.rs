@@ -0,0 +1,74 @@
+//! Heap memory usage of splines, e.g. for scenes with thousands of trajectories.
+//!
+//! Only memory allocated by the splines is counted,
+//! the size of the spline objects themselves (see [`std::mem::size_of()`]) is not included.
+//! Each spline type implements [`MemoryUsage`] in its own module.
+//!
+//! Splines store their data in boxed slices without excess capacity
+//! (including the ones created with [`AsdfPosSpline::builder()`](crate::AsdfPosSpline::builder)),
+//! therefore they don't have a `shrink_to_fit()` method.
+//! Only containers that grow incrementally have one, see
+//! [`TrajectorySet::shrink_to_fit()`](crate::trajectoryset::TrajectorySet::shrink_to_fit) and
+//! [`CurveEnsemble::shrink_to_fit()`](crate::ensemble::CurveEnsemble::shrink_to_fit).
+
+pub trait MemoryUsage {
+    /// Number of bytes allocated on the heap.
+    fn memory_usage(&self) -> usize;
+}
+
+impl<T: MemoryUsage> MemoryUsage for Option<T> {
+    fn memory_usage(&self) -> usize {
+        self.as_ref().map_or(0, MemoryUsage::memory_usage)
+    }
+}
+
+/// Only the heap memory of the elements, not the slice itself.
+impl<T: MemoryUsage> MemoryUsage for [T] {
+    fn memory_usage(&self) -> usize {
+        self.iter().map(MemoryUsage::memory_usage).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ensemble::CurveEnsemble;
+    use crate::{AsdfPosSpline1, AsdfRotSpline, PiecewiseCubicCurve, Spline};
+
+    #[test]
+    fn memory_usage() {
+        let grid: Vec<_> = (0..=10).map(|i| i as f32).collect();
+        let curve = PiecewiseCubicCurve::new([[1.0f32, 2.0, 3.0, 4.0]; 10], grid).unwrap();
+        // Segments, grid and inverse durations
+        let plain = 10 * 16 + 11 * 4 + 10 * 4;
+        assert_eq!(curve.memory_usage(), plain);
+        let curve = curve.with_soa_layout();
+        assert_eq!(curve.memory_usage(), plain + 10 * 16);
+
+        let spline = AsdfPosSpline1::builder()
+            .position(0.0)
+            .at_time(0.0)
+            .position(1.0)
+            .position(3.0)
+            .at_time(3.0)
+            .build()
+            .unwrap();
+        // At least the copies of positions, times, speeds, waits and TCB values
+        assert!(spline.memory_usage() > 3 * (4 + 8 + 8 + 4) + 12);
+        let rotation = AsdfRotSpline::from_angles(
+            [(0.0, 0.0, 0.0), (90.0, 0.0, 0.0)],
+            [Some(0.0), Some(1.0)],
+            [],
+            false,
+        )
+        .unwrap();
+        assert!(rotation.memory_usage() > rotation.grid().len() * 4);
+
+        let mut ensemble = CurveEnsemble::new([&curve, &curve, &curve]);
+        let before = ensemble.memory_usage();
+        ensemble.shrink_to_fit();
+        assert!(ensemble.memory_usage() <= before);
+        assert!(ensemble.memory_usage() >= 3 * plain);
+    }
+}
@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::mem::size_of_val;
 
 use superslice::Ext; // for slice::equal_range_by()
 
-use crate::utilities::{bisect, check_grid, GridError};
+use crate::memory::MemoryUsage;
+use crate::utilities::{brent, check_grid, GridError};
 use crate::PiecewiseCubicCurve;
 use crate::Spline;
 
@@ -41,6 +43,15 @@ pub enum MonotoneWithSlopesError {
     },
 }
 
+/// Where the previous search ended, see [`MonotoneCubicSpline::get_times()`].
+#[derive(Default)]
+struct Cursor {
+    /// Smallest index into the values that can still match.
+    start: usize,
+    /// Segment index and normalized time of the previous solution.
+    segment: Option<(usize, f32)>,
+}
+
 /// ... monotonically *increasing* ...
 pub struct MonotoneCubicSpline {
     inner: PiecewiseCubicCurve<f32>,
@@ -183,13 +194,46 @@ impl MonotoneCubicSpline {
     // TODO: rename to something with "solve"?
     #[must_use]
     pub fn get_time(&self, value: f32) -> Option<f32> {
+        self.solve(value, &mut Cursor::default())
+    }
+
+    /// Like [`get_time()`](Self::get_time) for all `values`, writing into `out`.
+    ///
+    /// If the solution is not unique (or if the value is NaN), the result is NaN.
+    /// The values are processed in ascending order,
+    /// each search continues where the previous one ended.
+    /// This is fastest if `values` are already sorted.
+    ///
+    /// # Panics
+    ///
+    /// If `values` and `out` have different lengths.
+    pub fn get_times(&self, values: &[f32], out: &mut [f32]) {
+        assert_eq!(values.len(), out.len());
+        let mut order = Vec::with_capacity(values.len());
+        for (i, value) in values.iter().enumerate() {
+            if value.is_nan() {
+                out[i] = f32::NAN;
+            } else {
+                order.push(i);
+            }
+        }
+        order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap());
+        let mut cursor = Cursor::default();
+        for i in order {
+            out[i] = self.solve(values[i], &mut cursor).unwrap_or(f32::NAN);
+        }
+    }
+
+    /// Values must be given in ascending order (when re-using the `cursor`).
+    fn solve(&self, value: f32, cursor: &mut Cursor) -> Option<f32> {
         // NB: If initially given values are monotone (which we checked above!),
         // repetitions (i.e. a plateau) can only occur at those exact values.
 
         // NB: This doesn't work if a value is NaN (but we've checked for that already
-        let range = self
-            .values
-            .equal_range_by(|x| x.partial_cmp(&value).unwrap());
+        let start = cursor.start;
+        let range = self.values[start..].equal_range_by(|x| x.partial_cmp(&value).unwrap());
+        let range = start + range.start..start + range.end;
+        cursor.start = range.start;
 
         if range.end == 0 {
             // Value too small
@@ -210,15 +254,21 @@ impl MonotoneCubicSpline {
             let mut a = self.inner.segments()[idx];
             a[0] -= value;
 
-            let time = bisect(
+            let lower = match cursor.segment {
+                Some((segment, time)) if segment == idx => time,
+                _ => 0.0,
+            };
+            let time = brent(
                 |t| ((a[3] * t + a[2]) * t + a[1]) * t + a[0],
-                0.0,
+                lower,
                 1.0,
-                // TODO: proper tolerance value
+                // Relative to the segment duration
                 0.0001,
                 500,
-            );
+            )
+            .x;
             assert!((0.0..=1.0).contains(&time));
+            cursor.segment = Some((idx, time));
             let t0 = self.inner.grid()[idx];
             let t1 = self.inner.grid()[idx + 1];
             Some(time * (t1 - t0) + t0)
@@ -226,6 +276,12 @@ impl MonotoneCubicSpline {
     }
 }
 
+impl MemoryUsage for MonotoneCubicSpline {
+    fn memory_usage(&self) -> usize {
+        self.inner.memory_usage() + size_of_val(&*self.values)
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::float_cmp)]
 mod tests {
@@ -252,4 +308,26 @@ mod tests {
         let spline = MonotoneCubicSpline::new(values, grid, cyclic).unwrap();
         assert_eq!(spline.get_time(2.0), None);
     }
+
+    #[test]
+    fn get_times() {
+        let values = [1.0, 2.0, 2.0, 3.0, 7.0].to_vec();
+        let grid = [4.0, 5.0, 6.0, 7.0, 9.0].to_vec();
+        let spline = MonotoneCubicSpline::new(values, grid, false).unwrap();
+        let queries: Vec<f32> = [6.5, 0.0, 1.5, 2.0, f32::NAN, 4.0, 1.2, 8.0, 3.0, 1.7]
+            .into_iter()
+            .chain((0..50).map(|i| 7.0 - i as f32 * 0.1))
+            .collect();
+        let mut out = vec![0.0; queries.len()];
+        spline.get_times(&queries, &mut out);
+        assert!(out[4].is_nan());
+        for (&value, &time) in queries.iter().zip(&out).filter(|(v, _)| !v.is_nan()) {
+            match spline.get_time(value) {
+                Some(expected) => assert!((time - expected).abs() < 1e-3),
+                None => assert!(time.is_nan()),
+            }
+        }
+        assert_eq!(out[1], 4.0);
+        assert!(out[3].is_nan());
+    }
 }
@@ -0,0 +1,242 @@
+use std::marker::PhantomData;
+
+use crate::quaternion::UnitQuaternion;
+use crate::{Spline, SplineWithVelocity, Vector};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(
+        "time ranges must be the same ({first_start:?} to {first_end:?} vs. {second_start:?} to {second_end:?})"
+    )]
+    DomainMismatch {
+        first_start: f32,
+        first_end: f32,
+        second_start: f32,
+        second_end: f32,
+    },
+}
+
+/// Checks that both grids have the same start and end, returns the union of both.
+fn merged_grid(a: &[f32], b: &[f32]) -> Result<Box<[f32]>, Error> {
+    let first_start = *a.first().unwrap();
+    let first_end = *a.last().unwrap();
+    let second_start = *b.first().unwrap();
+    let second_end = *b.last().unwrap();
+    if first_start != second_start || first_end != second_end {
+        return Err(Error::DomainMismatch {
+            first_start,
+            first_end,
+            second_start,
+            second_end,
+        });
+    }
+    let mut grid: Vec<_> = a.iter().chain(b).copied().collect();
+    grid.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    grid.dedup();
+    Ok(grid.into())
+}
+
+/// Time-varying blend between two splines.
+///
+/// A weight of 0 means only `first`, a weight of 1 means only `second`.
+/// Weights outside of this range extrapolate.
+pub struct Morph<V, First, Second, Weight> {
+    first: First,
+    second: Second,
+    weight: Weight,
+    grid: Box<[f32]>,
+    _phantom: PhantomData<V>,
+}
+
+impl<V, First, Second, Weight> Morph<V, First, Second, Weight>
+where
+    V: Vector,
+    First: Spline<V>,
+    Second: Spline<V>,
+    Weight: Spline<f32>,
+{
+    /// `first` and `second` must start and end at the same time.
+    ///
+    /// The resulting grid contains the grid values of both.
+    pub fn new(
+        first: First,
+        second: Second,
+        weight: Weight,
+    ) -> Result<Morph<V, First, Second, Weight>, Error> {
+        let grid = merged_grid(first.grid(), second.grid())?;
+        Ok(Morph {
+            first,
+            second,
+            weight,
+            grid,
+            _phantom: PhantomData,
+        })
+    }
+
+    #[must_use]
+    pub fn first(&self) -> &First {
+        &self.first
+    }
+
+    #[must_use]
+    pub fn second(&self) -> &Second {
+        &self.second
+    }
+
+    #[must_use]
+    pub fn weight(&self) -> &Weight {
+        &self.weight
+    }
+}
+
+impl<V, First, Second, Weight> Spline<V> for Morph<V, First, Second, Weight>
+where
+    V: Vector,
+    First: Spline<V>,
+    Second: Spline<V>,
+    Weight: Spline<f32>,
+{
+    fn evaluate(&self, t: f32) -> V {
+        let w = self.weight.evaluate(t);
+        self.first.evaluate(t) * (1.0 - w) + self.second.evaluate(t) * w
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+impl<V, First, Second, Weight> SplineWithVelocity<V, V> for Morph<V, First, Second, Weight>
+where
+    V: Vector,
+    First: SplineWithVelocity<V, V>,
+    Second: SplineWithVelocity<V, V>,
+    Weight: SplineWithVelocity<f32, f32>,
+{
+    fn evaluate_velocity(&self, t: f32) -> V {
+        let w = self.weight.evaluate(t);
+        let dw = self.weight.evaluate_velocity(t);
+        self.first.evaluate_velocity(t) * (1.0 - w)
+            + self.second.evaluate_velocity(t) * w
+            + (self.second.evaluate(t) - self.first.evaluate(t)) * dw
+    }
+}
+
+/// Time-varying blend between two rotation splines.
+///
+/// Like [`Morph`], but the rotations are interpolated with Slerp
+/// (along the shorter path).
+pub struct RotationMorph<First, Second, Weight> {
+    first: First,
+    second: Second,
+    weight: Weight,
+    grid: Box<[f32]>,
+}
+
+impl<First, Second, Weight> RotationMorph<First, Second, Weight>
+where
+    First: Spline<UnitQuaternion>,
+    Second: Spline<UnitQuaternion>,
+    Weight: Spline<f32>,
+{
+    /// See [`Morph::new()`].
+    pub fn new(
+        first: First,
+        second: Second,
+        weight: Weight,
+    ) -> Result<RotationMorph<First, Second, Weight>, Error> {
+        let grid = merged_grid(first.grid(), second.grid())?;
+        Ok(RotationMorph {
+            first,
+            second,
+            weight,
+            grid,
+        })
+    }
+
+    #[must_use]
+    pub fn first(&self) -> &First {
+        &self.first
+    }
+
+    #[must_use]
+    pub fn second(&self) -> &Second {
+        &self.second
+    }
+
+    #[must_use]
+    pub fn weight(&self) -> &Weight {
+        &self.weight
+    }
+}
+
+impl<First, Second, Weight> Spline<UnitQuaternion> for RotationMorph<First, Second, Weight>
+where
+    First: Spline<UnitQuaternion>,
+    Second: Spline<UnitQuaternion>,
+    Weight: Spline<f32>,
+{
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        let w = self.weight.evaluate(t);
+        let first = self.first.evaluate(t);
+        let second = self.second.evaluate(t);
+        // Unlike slerp(), this doesn't panic for opposite rotations and allows extrapolation
+        first * UnitQuaternion::from_scaled_axis((first.inverse() * second).scaled_axis() * w)
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    #[test]
+    fn blend() {
+        let first = PiecewiseCubicCurve::new([[0.0f32, 0.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        let second =
+            PiecewiseCubicCurve::new([[2.0f32, 0.0, 0.0, 0.0]; 2], [0.0, 1.0, 2.0]).unwrap();
+        let weight = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        let morph = Morph::new(first, second, weight).unwrap();
+        assert_eq!(morph.grid(), [0.0, 1.0, 2.0]);
+        assert_eq!(morph.evaluate(0.0), 0.0);
+        assert_eq!(morph.evaluate(1.0), 1.0);
+        assert_eq!(morph.evaluate(2.0), 2.0);
+        assert_eq!(morph.evaluate_velocity(1.0), 1.0);
+        let short = PiecewiseCubicCurve::new([[0.0f32, 0.0, 0.0, 0.0]], [0.0, 1.0]).unwrap();
+        let long = PiecewiseCubicCurve::new([[0.0f32, 0.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        let weight = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        assert!(matches!(
+            Morph::new(short, long, weight),
+            Err(Error::DomainMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn rotation_blend() {
+        use crate::quaternion::{angles2quat, PiecewiseSlerp};
+        let facing = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(0.0, 0.0, 0.0)],
+            [0.0, 2.0],
+            false,
+        )
+        .unwrap();
+        let turning = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(80.0, 0.0, 0.0)],
+            [0.0, 1.0, 2.0],
+            true,
+        )
+        .unwrap();
+        let weight = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        let morph = RotationMorph::new(facing, turning, weight).unwrap();
+        assert_eq!(morph.grid(), [0.0, 1.0, 2.0]);
+        assert!(morph.evaluate(0.0).angle_to(&angles2quat(0.0, 0.0, 0.0)) < 1e-6);
+        assert!(morph.evaluate(1.0).angle_to(&angles2quat(40.0, 0.0, 0.0)) < 1e-5);
+        assert!(morph.evaluate(1.5).angle_to(&angles2quat(30.0, 0.0, 0.0)) < 1e-5);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{NormWrapper, Spline, Vector};
+
+/// Limits for [`check_motion_limits()`], `None` means "unlimited".
+#[derive(Debug, Clone, Copy)]
+pub struct MotionLimits {
+    pub speed: Option<f32>,
+    pub acceleration: Option<f32>,
+    pub jerk: Option<f32>,
+    /// Number of sampling intervals between neighboring grid values
+    pub samples_per_segment: usize,
+}
+
+impl Default for MotionLimits {
+    fn default() -> Self {
+        MotionLimits {
+            speed: None,
+            acceleration: None,
+            jerk: None,
+            samples_per_segment: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionQuantity {
+    Speed,
+    Acceleration,
+    Jerk,
+}
+
+/// A time range where a [`MotionQuantity`] exceeds its limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimitViolation {
+    pub quantity: MotionQuantity,
+    pub begin: f32,
+    pub end: f32,
+    pub peak_time: f32,
+    pub peak: f32,
+}
+
+/// Scans the spline for violations of the given limits.
+///
+/// Derivatives are approximated with finite differences within each segment
+/// (i.e. never across grid values, where acceleration and jerk are typically discontinuous).
+/// Violations are sorted by quantity and then by time.
+pub fn check_motion_limits<S, V, U>(spline: &S, limits: &MotionLimits) -> Vec<LimitViolation>
+where
+    S: Spline<V>,
+    V: Vector + NormWrapper<U>,
+{
+    let n = limits.samples_per_segment.max(3);
+    let mut speeds = Vec::new();
+    let mut accelerations = Vec::new();
+    let mut jerks = Vec::new();
+    for ts in spline.grid().windows(2) {
+        if let [t0, t1] = *ts {
+            let h = (t1 - t0) / n as f32;
+            let x: Vec<_> = (0..=n)
+                .map(|k| spline.evaluate(t0 + k as f32 * h))
+                .collect();
+            for k in 0..n {
+                let speed = (x[k + 1] - x[k]).norm() / h;
+                speeds.push((t0 + (k as f32 + 0.5) * h, speed));
+            }
+            for k in 1..n {
+                let acceleration = (x[k + 1] - x[k] * 2.0 + x[k - 1]).norm() / (h * h);
+                accelerations.push((t0 + k as f32 * h, acceleration));
+            }
+            for k in 1..n - 1 {
+                let jerk = (x[k + 2] - x[k + 1] * 3.0 + x[k] * 3.0 - x[k - 1]).norm() / (h * h * h);
+                jerks.push((t0 + (k as f32 + 0.5) * h, jerk));
+            }
+        } else {
+            unreachable!();
+        }
+    }
+    let mut violations = Vec::new();
+    for (quantity, limit, samples) in [
+        (MotionQuantity::Speed, limits.speed, speeds),
+        (
+            MotionQuantity::Acceleration,
+            limits.acceleration,
+            accelerations,
+        ),
+        (MotionQuantity::Jerk, limits.jerk, jerks),
+    ] {
+        if let Some(limit) = limit {
+            collect_violations(quantity, limit, &samples, &mut violations);
+        }
+    }
+    violations
+}
+
+fn collect_violations(
+    quantity: MotionQuantity,
+    limit: f32,
+    samples: &[(f32, f32)],
+    violations: &mut Vec<LimitViolation>,
+) {
+    let mut current: Option<LimitViolation> = None;
+    for &(time, value) in samples {
+        if value > limit {
+            if let Some(ref mut v) = current {
+                v.end = time;
+                if value > v.peak {
+                    v.peak = value;
+                    v.peak_time = time;
+                }
+            } else {
+                current = Some(LimitViolation {
+                    quantity,
+                    begin: time,
+                    end: time,
+                    peak_time: time,
+                    peak: value,
+                });
+            }
+        } else if let Some(v) = current.take() {
+            violations.push(v);
+        }
+    }
+    violations.extend(current);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::asdfposspline::NormF32;
+    use crate::PiecewiseCubicCurve;
+
+    #[test]
+    fn speed() {
+        // x(t) = t^2 for t in [0, 1]
+        let curve = PiecewiseCubicCurve::new([[0.0f32, 0.0, 1.0, 0.0]], [0.0, 1.0]).unwrap();
+        let limits = MotionLimits {
+            speed: Some(1.0),
+            ..Default::default()
+        };
+        let violations = check_motion_limits::<_, _, NormF32>(&curve, &limits);
+        assert_eq!(violations.len(), 1);
+        let v = violations[0];
+        assert_eq!(v.quantity, MotionQuantity::Speed);
+        assert!((v.begin - 0.5).abs() < 0.05);
+        assert!((v.peak - 2.0).abs() < 0.05);
+        assert!((v.peak_time - 1.0).abs() < 0.05);
+
+        let limits = MotionLimits {
+            speed: Some(2.5),
+            acceleration: Some(2.5),
+            ..Default::default()
+        };
+        assert!(check_motion_limits::<_, _, NormF32>(&curve, &limits).is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+use std::mem::size_of_val;
+
+use crate::memory::MemoryUsage;
+use crate::utilities::{check_grid, GridError};
+use crate::{PiecewiseCubicCurve, Spline};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least one channel")]
+    ZeroChannels,
+    #[error("there must be at least one segment")]
+    ZeroSegments,
+    #[error(
+        "number of coefficients ({coefficients}) must be 4 * {channels} channels * {segments} segments"
+    )]
+    CoefficientsVsSegments {
+        coefficients: usize,
+        channels: usize,
+        segments: usize,
+    },
+    #[error("channel {channel}: grid must be the same as in channel 0")]
+    GridMismatch { channel: usize },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+/// Multiple scalar channels with a shared grid.
+///
+/// Coefficients are stored segment by segment, and within a segment,
+/// the `k`-th coefficients of all channels are stored contiguously
+/// (structure of arrays).
+pub struct MultiSpline {
+    channels: usize,
+    coefficients: Box<[f32]>,
+    grid: Box<[f32]>,
+}
+
+impl MultiSpline {
+    /// See [`MultiSpline`] for the memory layout of `coefficients`.
+    ///
+    /// Within a segment, coefficients are normalized like in [`PiecewiseCubicCurve`].
+    pub fn new(
+        channels: usize,
+        coefficients: impl Into<Box<[f32]>>,
+        grid: impl Into<Box<[f32]>>,
+    ) -> Result<MultiSpline, Error> {
+        use Error::*;
+        let coefficients = coefficients.into();
+        let grid = grid.into();
+        if channels == 0 {
+            return Err(ZeroChannels);
+        }
+        if grid.len() < 2 {
+            return Err(ZeroSegments);
+        }
+        let segments = grid.len() - 1;
+        if coefficients.len() != 4 * channels * segments {
+            return Err(CoefficientsVsSegments {
+                coefficients: coefficients.len(),
+                channels,
+                segments,
+            });
+        }
+        check_grid(&grid)?;
+        Ok(MultiSpline {
+            channels,
+            coefficients,
+            grid,
+        })
+    }
+
+    /// All curves must have the same grid.
+    pub fn from_curves(curves: &[PiecewiseCubicCurve<f32>]) -> Result<MultiSpline, Error> {
+        use Error::*;
+        let first = curves.first().ok_or(ZeroChannels)?;
+        let channels = curves.len();
+        let segments = first.segments().len();
+        for (channel, curve) in curves.iter().enumerate() {
+            if curve.grid() != first.grid() {
+                return Err(GridMismatch { channel });
+            }
+        }
+        let mut coefficients = vec![0.0; 4 * channels * segments];
+        for (channel, curve) in curves.iter().enumerate() {
+            for (i, segment) in curve.segments().iter().enumerate() {
+                for (k, &a) in segment.iter().enumerate() {
+                    coefficients[(i * 4 + k) * channels + channel] = a;
+                }
+            }
+        }
+        MultiSpline::new(channels, coefficients, first.grid())
+    }
+
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    #[must_use]
+    pub fn coefficients(&self) -> &[f32] {
+        &self.coefficients
+    }
+
+    /// Evaluates all channels at once.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `values` is not the number of channels.
+    pub fn evaluate_into(&self, t: f32, values: &mut [f32]) {
+        assert_eq!(values.len(), self.channels);
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let t1 = self.grid[idx + 1];
+        let t = (t - t0) / (t1 - t0);
+        let n = self.channels;
+        let segment = &self.coefficients[idx * 4 * n..(idx + 1) * 4 * n];
+        let (a0, rest) = segment.split_at(n);
+        let (a1, rest) = rest.split_at(n);
+        let (a2, a3) = rest.split_at(n);
+        for (c, value) in values.iter_mut().enumerate() {
+            *value = ((a3[c] * t + a2[c]) * t + a1[c]) * t + a0[c];
+        }
+    }
+
+    /// Evaluates a single channel.
+    ///
+    /// # Panics
+    ///
+    /// If `channel` is not smaller than the number of channels.
+    #[must_use]
+    pub fn evaluate_channel(&self, t: f32, channel: usize) -> f32 {
+        assert!(channel < self.channels);
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let t1 = self.grid[idx + 1];
+        let t = (t - t0) / (t1 - t0);
+        let a = |k: usize| self.coefficients[(idx * 4 + k) * self.channels + channel];
+        ((a(3) * t + a(2)) * t + a(1)) * t + a(0)
+    }
+}
+
+impl Spline<Vec<f32>> for MultiSpline {
+    fn evaluate(&self, t: f32) -> Vec<f32> {
+        let mut values = vec![0.0; self.channels];
+        self.evaluate_into(t, &mut values);
+        values
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+impl MemoryUsage for MultiSpline {
+    fn memory_usage(&self) -> usize {
+        size_of_val(&*self.coefficients) + size_of_val(&*self.grid)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_curves() {
+        let grid = [0.0, 1.0, 3.0];
+        let a =
+            PiecewiseCubicCurve::new([[0.0, 1.0, 0.0, 0.0], [1.0, 2.0, 0.0, 0.0]], grid).unwrap();
+        let b =
+            PiecewiseCubicCurve::new([[5.0, 0.0, 1.0, 0.0], [6.0, 0.0, 0.0, 1.0]], grid).unwrap();
+        let multi = MultiSpline::from_curves(&[a, b]).unwrap();
+        assert_eq!(multi.channels(), 2);
+        let mut values = [0.0; 2];
+        for t in [-1.0, 0.5, 1.0, 2.0, 4.0] {
+            multi.evaluate_into(t, &mut values);
+            assert_eq!(values[1], multi.evaluate_channel(t, 1));
+        }
+        assert_eq!(multi.evaluate(0.5), [0.5, 5.25]);
+        assert_eq!(multi.evaluate(2.0), [2.0, 6.125]);
+        let c = PiecewiseCubicCurve::new([[0.0, 1.0, 0.0, 0.0]], [0.0, 1.0]).unwrap();
+        assert!(matches!(
+            MultiSpline::from_curves(&[zeros(), c]),
+            Err(Error::GridMismatch { channel: 1 })
+        ));
+    }
+
+    fn zeros() -> PiecewiseCubicCurve<f32> {
+        PiecewiseCubicCurve::new([[0.0, 0.0, 0.0, 0.0]; 2], [0.0, 1.0, 3.0]).unwrap()
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            MultiSpline::new(2, vec![0.0; 7], [0.0, 1.0]),
+            Err(Error::CoefficientsVsSegments { .. })
+        ));
+        assert!(matches!(
+            MultiSpline::new(0, vec![], [0.0, 1.0]),
+            Err(Error::ZeroChannels)
+        ));
+        assert!(matches!(
+            MultiSpline::from_curves(&[]),
+            Err(Error::ZeroChannels)
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_channel() {
+        let multi = MultiSpline::from_curves(&[zeros(), zeros()]).unwrap();
+        let _ = multi.evaluate_channel(0.0, 2);
+    }
+}
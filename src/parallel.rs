@@ -0,0 +1,111 @@
+//! Parallel evaluation of many time instants (requires the `rayon` feature).
+//!
+//! This is meant for offline processing (e.g. baking long trajectories),
+//! for real-time use the single-threaded functions are more appropriate.
+
+use rayon::prelude::*;
+
+use crate::piecewisecubiccurve::Components;
+use crate::{PiecewiseCubicCurve, Spline, Vector};
+
+/// Number of time instants processed by each task.
+const CHUNK_SIZE: usize = 1024;
+
+/// Evaluation on multiple threads.
+pub trait ParallelEvaluate<Value>: Spline<Value> + Sync
+where
+    Value: Send,
+{
+    /// Like [`evaluate()`](Spline::evaluate) for all `times`, writing into `output`.
+    ///
+    /// # Panics
+    ///
+    /// If `times` and `output` have different lengths.
+    fn par_evaluate_into(&self, times: &[f32], output: &mut [Value]) {
+        assert_eq!(times.len(), output.len());
+        times
+            .par_iter()
+            .zip(output.par_iter_mut())
+            .with_min_len(CHUNK_SIZE)
+            .for_each(|(&t, o)| *o = self.evaluate(t));
+    }
+
+    /// Like [`evaluate()`](Spline::evaluate) for all `times`.
+    fn par_evaluate(&self, times: &[f32]) -> Vec<Value> {
+        times
+            .par_iter()
+            .with_min_len(CHUNK_SIZE)
+            .map(|&t| self.evaluate(t))
+            .collect()
+    }
+}
+
+impl<T, Value> ParallelEvaluate<Value> for T
+where
+    T: Spline<Value> + Sync + ?Sized,
+    Value: Send,
+{
+}
+
+impl<V> PiecewiseCubicCurve<V>
+where
+    V: Vector + Components + Send + Sync,
+{
+    /// Like [`evaluate_into()`](Self::evaluate_into), but on multiple threads.
+    ///
+    /// This is faster than [`ParallelEvaluate::par_evaluate_into()`].
+    pub fn par_evaluate_into(&self, times: &[f32], output: &mut [V]) {
+        assert_eq!(times.len(), output.len());
+        times
+            .par_chunks(CHUNK_SIZE)
+            .zip(output.par_chunks_mut(CHUNK_SIZE))
+            .for_each(|(times, output)| self.evaluate_into(times, output));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, PiecewiseSlerp, Vec3};
+
+    #[test]
+    fn parallel() {
+        let zero = Vec3::zeros();
+        let curve = PiecewiseCubicCurve::new(
+            [
+                [
+                    zero,
+                    Vec3::new(1.0, 2.0, 0.0),
+                    Vec3::new(0.0, 1.0, 1.0),
+                    zero,
+                ],
+                [
+                    Vec3::new(1.0, 3.0, 1.0),
+                    zero,
+                    zero,
+                    Vec3::new(2.0, 0.0, 0.0),
+                ],
+            ],
+            [0.0, 1.0, 3.0],
+        )
+        .unwrap();
+        let times: Vec<f32> = (0..10_000).map(|i| i as f32 * 0.0003).collect();
+        let mut output = vec![zero; times.len()];
+        curve.par_evaluate_into(&times, &mut output);
+        let generic = ParallelEvaluate::par_evaluate(&curve, &times);
+        for ((&t, a), b) in times.iter().zip(&output).zip(&generic) {
+            assert_eq!(*a, curve.evaluate(t));
+            assert_eq!(*b, curve.evaluate(t));
+        }
+        let slerp = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(90.0, 0.0, 0.0)],
+            [0.0, 3.0],
+            false,
+        )
+        .unwrap();
+        let mut rotations = vec![angles2quat(0.0, 0.0, 0.0); times.len()];
+        slerp.par_evaluate_into(&times, &mut rotations);
+        assert_eq!(rotations[5000], slerp.evaluate(times[5000]));
+    }
+}
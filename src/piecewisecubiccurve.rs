@@ -1,9 +1,24 @@
-use crate::utilities::{check_grid, GridError};
+use std::mem::size_of_val;
+
+use nalgebra::SVector;
+use superslice::Ext; // for slice::lower_bound_by() and slice::upper_bound_by()
+
+use crate::math;
+use crate::memory::MemoryUsage;
+use crate::utilities::{
+    check_grid_with_jumps, evaluate_polynomial, polynomial_roots, BucketIndex, GridError,
+};
 use crate::{Spline, SplineWithVelocity, Vector};
 
 pub struct PiecewiseCubicCurve<V> {
     segments: Box<[[V; 4]]>,
     grid: Box<[f32]>,
+    /// `1 / (t1 - t0)` for each segment (infinite for zero-length segments).
+    inverse_durations: Box<[f32]>,
+    /// Optional copy of the coefficients, see [`with_soa_layout()`](Self::with_soa_layout).
+    soa: Option<[Box<[f32]>; 4]>,
+    /// See [`with_bucket_index()`](Self::with_bucket_index).
+    index: Option<BucketIndex>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -20,11 +35,25 @@ impl<V: Vector> PiecewiseCubicCurve<V> {
     pub fn new(
         segments: impl Into<Box<[[V; 4]]>>,
         grid: impl Into<Box<[f32]>>,
+    ) -> Result<PiecewiseCubicCurve<V>, Error> {
+        PiecewiseCubicCurve::with_jumps(segments, grid, &[])
+    }
+
+    /// Segments at the `jumps` indices may have zero length,
+    /// leading to a discontinuity.
+    ///
+    /// Zero-length segments are never evaluated.
+    /// At a jump, [`evaluate()`](Spline::evaluate) returns the value on the right side,
+    /// [`evaluate_left()`](Self::evaluate_left) returns the value on the left side.
+    pub fn with_jumps(
+        segments: impl Into<Box<[[V; 4]]>>,
+        grid: impl Into<Box<[f32]>>,
+        jumps: &[usize],
     ) -> Result<PiecewiseCubicCurve<V>, Error> {
         let segments = segments.into();
         let grid = grid.into();
         use Error::*;
-        if segments.len() < 1 {
+        if segments.is_empty() {
             return Err(ZeroSegments);
         }
         if segments.len() + 1 != grid.len() {
@@ -33,8 +62,63 @@ impl<V: Vector> PiecewiseCubicCurve<V> {
                 segments: segments.len(),
             });
         }
-        check_grid(&grid)?;
-        Ok(PiecewiseCubicCurve { segments, grid })
+        check_grid_with_jumps(&grid, jumps)?;
+        let inverse_durations = inverse_durations(&grid);
+        Ok(PiecewiseCubicCurve {
+            segments,
+            grid,
+            inverse_durations,
+            soa: None,
+            index: None,
+        })
+    }
+
+    /// Indices of zero-length segments, see [`with_jumps()`](Self::with_jumps).
+    #[must_use]
+    pub fn jumps(&self) -> Vec<usize> {
+        (0..self.segments.len())
+            .filter(|&i| self.grid[i] == self.grid[i + 1])
+            .collect()
+    }
+
+    /// Like [`evaluate()`](Spline::evaluate), but taking the limit from the left.
+    ///
+    /// This only makes a difference at jumps.
+    #[must_use]
+    pub fn evaluate_left(&self, t: f32) -> V {
+        let first = *self.grid.first().unwrap();
+        let last = *self.grid.last().unwrap();
+        let (t, idx) = if t <= first {
+            (first, 0)
+        } else if t <= last {
+            // NB: This doesn't work if a value is NaN
+            (
+                t,
+                self.grid.lower_bound_by(|x| x.partial_cmp(&t).unwrap()) - 1,
+            )
+        } else {
+            (last, self.grid.len() - 2)
+        };
+        let a = &self.segments[idx];
+        let t = (t - self.grid[idx]) * self.inverse_durations[idx];
+        ((a[3] * t + a[2]) * t + a[1]) * t + a[0]
+    }
+
+    /// Builds an index of uniform time buckets (one per segment)
+    /// to speed up finding the segment for a given time.
+    ///
+    /// This is only worth it for curves with many segments,
+    /// the results of all methods are not affected.
+    #[must_use]
+    pub fn with_bucket_index(mut self) -> Self {
+        self.index = Some(BucketIndex::new(&self.grid, self.segments.len()));
+        self
+    }
+
+    /// Whether [`with_bucket_index()`](Self::with_bucket_index) has been used.
+    #[must_use]
+    pub fn has_bucket_index(&self) -> bool {
+        self.index.is_some()
     }
 
     #[must_use]
@@ -42,23 +126,419 @@ impl<V: Vector> PiecewiseCubicCurve<V> {
         &self.segments
     }
 
-    // If t is out of bounds, it is trimmed to the smallest/largest possible value
-    fn get_segment(&self, t: f32) -> (f32, f32, f32, &[V; 4]) {
+    /// Bézier control points of segment `idx`.
+    #[must_use]
+    pub fn segment_control_points(&self, idx: usize) -> [V; 4] {
+        let [a0, a1, a2, a3] = self.segments[idx];
+        let p1 = a0 + a1 / 3.0;
+        let p2 = p1 + (a1 + a2) / 3.0;
+        [a0, p1, p2, a0 + a1 + a2 + a3]
+    }
+
+    // If t is out of bounds, it is trimmed to the smallest/largest possible value.
+    // Returns the normalized time (from 0 to 1) and the inverse segment duration.
+    fn get_segment(&self, t: f32) -> (f32, f32, &[V; 4]) {
         let (t, idx) = self.clamp_parameter_and_find_index(t);
-        (t, self.grid[idx], self.grid[idx + 1], &self.segments[idx])
+        let inverse_duration = self.inverse_durations[idx];
+        (
+            (t - self.grid[idx]) * inverse_duration,
+            inverse_duration,
+            &self.segments[idx],
+        )
+    }
+
+    fn nearest_time_by(&self, point: V, dot: impl Fn(V, V) -> f32) -> (f32, f32) {
+        let mut nearest = (self.grid[0], f32::INFINITY);
+        for (a, t) in self.segments.iter().zip(self.grid.windows(2)) {
+            let (t0, t1) = (t[0], t[1]);
+            if t0 == t1 {
+                continue;
+            }
+            let c = [a[0] - point, a[1], a[2], a[3]];
+            let dc = [a[1], a[2] * 2.0, a[3] * 3.0];
+            // Derivative of the squared distance (divided by two)
+            let mut derivative = [0.0; 6];
+            for (i, &ci) in c.iter().enumerate() {
+                for (j, &dcj) in dc.iter().enumerate() {
+                    derivative[i + j] += dot(ci, dcj);
+                }
+            }
+            let mut candidates = vec![0.0, 1.0];
+            candidates.extend(polynomial_roots(&derivative, 0.0, 1.0));
+            for x in candidates {
+                let offset = ((c[3] * x + c[2]) * x + c[1]) * x + c[0];
+                let distance = math::sqrt(dot(offset, offset).max(0.0));
+                if distance < nearest.1 {
+                    nearest = (t0 + x * (t1 - t0), distance);
+                }
+            }
+        }
+        nearest
+    }
+
+    /// `polynomial` turns a segment into the coefficients of a function
+    /// that changes its sign at the crossings.
+    ///
+    /// If `closed`, the end is connected to the start.
+    pub(crate) fn crossings_by(
+        &self,
+        polynomial: impl Fn(&[V; 4]) -> Vec<f32>,
+        closed: bool,
+    ) -> Vec<Crossing> {
+        let mut crossings = Vec::new();
+        let mut first = None;
+        let mut positive = None;
+        for (a, t) in self.segments.iter().zip(self.grid.windows(2)) {
+            let (t0, t1) = (t[0], t[1]);
+            if t0 == t1 {
+                continue;
+            }
+            let coefficients = polynomial(a);
+            let mut bounds = vec![0.0];
+            bounds.extend(polynomial_roots(&coefficients, 0.0, 1.0));
+            bounds.push(1.0);
+            for w in bounds.windows(2) {
+                if w[0] >= w[1] {
+                    continue;
+                }
+                let value = evaluate_polynomial(&coefficients, (w[0] + w[1]) / 2.0);
+                if value == 0.0 {
+                    continue;
+                }
+                let entering = value > 0.0;
+                if positive == Some(!entering) {
+                    crossings.push(Crossing {
+                        time: t0 + w[0] * (t1 - t0),
+                        entering,
+                    });
+                }
+                positive = Some(entering);
+                first = first.or(positive);
+            }
+        }
+        if let (true, Some(entering)) = (closed, first) {
+            if positive == Some(!entering) {
+                crossings.insert(
+                    0,
+                    Crossing {
+                        time: self.grid[0],
+                        entering,
+                    },
+                );
+            }
+        }
+        crossings
+    }
+}
+
+/// Access to the scalar components of curve values, see
+/// [`PiecewiseCubicCurve::evaluate_into()`].
+pub trait Components: Copy {
+    const COUNT: usize;
+
+    fn component(&self, index: usize) -> f32;
+
+    fn set_component(&mut self, index: usize, value: f32);
+}
+
+impl Components for f32 {
+    const COUNT: usize = 1;
+
+    fn component(&self, _index: usize) -> f32 {
+        *self
+    }
+
+    fn set_component(&mut self, _index: usize, value: f32) {
+        *self = value;
+    }
+}
+
+impl<const D: usize> Components for SVector<f32, D> {
+    const COUNT: usize = D;
+
+    fn component(&self, index: usize) -> f32 {
+        self[index]
+    }
+
+    fn set_component(&mut self, index: usize, value: f32) {
+        self[index] = value;
+    }
+}
+
+/// Number of time instants that are evaluated at once.
+const LANES: usize = 8;
+
+/// Evaluates a cubic polynomial (starting with the constant term) at multiple points.
+#[cfg(feature = "simd")]
+fn evaluate_lanes(a: [f32; 4], t: [f32; LANES]) -> [f32; LANES] {
+    use wide::f32x8;
+    let t = f32x8::from(t);
+    let result = ((f32x8::splat(a[3]) * t + f32x8::splat(a[2])) * t + f32x8::splat(a[1])) * t
+        + f32x8::splat(a[0]);
+    result.to_array()
+}
+
+/// Evaluates a cubic polynomial (starting with the constant term) at multiple points.
+#[cfg(not(feature = "simd"))]
+fn evaluate_lanes(a: [f32; 4], t: [f32; LANES]) -> [f32; LANES] {
+    // This is simple enough to be auto-vectorized
+    let mut result = [0.0; LANES];
+    for (r, t) in result.iter_mut().zip(t) {
+        *r = ((a[3] * t + a[2]) * t + a[1]) * t + a[0];
+    }
+    result
+}
+
+impl<V: Vector + Components> PiecewiseCubicCurve<V> {
+    /// Additionally stores the coefficients in a "structure of arrays" layout,
+    /// i.e. one array for each of the four coefficients,
+    /// with all components of all segments stored contiguously.
+    ///
+    /// This is used by [`evaluate_into()`](Self::evaluate_into),
+    /// the results are the same, but it might be faster due to better cache behavior.
+    /// All other methods are not affected.
+    #[must_use]
+    pub fn with_soa_layout(mut self) -> Self {
+        let coefficient = |k: usize| -> Box<[f32]> {
+            self.segments
+                .iter()
+                .flat_map(|a| (0..V::COUNT).map(move |c| a[k].component(c)))
+                .collect()
+        };
+        self.soa = Some([
+            coefficient(0),
+            coefficient(1),
+            coefficient(2),
+            coefficient(3),
+        ]);
+        self
+    }
+
+    /// Whether [`with_soa_layout()`](Self::with_soa_layout) has been used.
+    #[must_use]
+    pub fn has_soa_layout(&self) -> bool {
+        self.soa.is_some()
+    }
+
+    /// Per-component `(minimum, maximum)` of the control points of segment `idx`,
+    /// see [`segment_control_points()`](Self::segment_control_points).
+    ///
+    /// The segment lies within the convex hull of its control points,
+    /// therefore these bounds are conservative (up to rounding errors).
+    /// They are cheap to compute, but they might be wider than the actual extrema.
+    #[must_use]
+    pub fn segment_bounds(&self, idx: usize) -> (V, V) {
+        let points = self.segment_control_points(idx);
+        let (mut min, mut max) = (points[0], points[0]);
+        for point in &points[1..] {
+            for c in 0..V::COUNT {
+                let value = point.component(c);
+                if value < min.component(c) {
+                    min.set_component(c, value);
+                }
+                if value > max.component(c) {
+                    max.set_component(c, value);
+                }
+            }
+        }
+        (min, max)
+    }
+
+    /// Coefficients (starting with the constant term) of component `c` of segment `idx`.
+    fn component_coefficients(&self, idx: usize, c: usize) -> [f32; 4] {
+        if let Some(soa) = &self.soa {
+            let i = idx * V::COUNT + c;
+            [soa[0][i], soa[1][i], soa[2][i], soa[3][i]]
+        } else {
+            let a = &self.segments[idx];
+            [
+                a[0].component(c),
+                a[1].component(c),
+                a[2].component(c),
+                a[3].component(c),
+            ]
+        }
+    }
+
+    /// Evaluates the curve at all `times` and writes the results into `output`.
+    ///
+    /// This gives the same results as [`evaluate()`](Spline::evaluate),
+    /// but consecutive times within the same segment are evaluated together,
+    /// which is much faster for densely sampled (e.g. audio-rate) time instants.
+    /// With the `simd` feature, SIMD instructions are used explicitly.
+    ///
+    /// # Panics
+    ///
+    /// If `times` and `output` have different lengths.
+    pub fn evaluate_into(&self, times: &[f32], output: &mut [V]) {
+        assert_eq!(times.len(), output.len());
+        let mut i = 0;
+        while i < times.len() {
+            let (t, idx) = self.clamp_parameter_and_find_index(times[i]);
+            let (t0, t1) = (self.grid[idx], self.grid[idx + 1]);
+            let inverse_duration = self.inverse_durations[idx];
+            let mut normalized = [0.0; LANES];
+            normalized[0] = (t - t0) * inverse_duration;
+            let mut count = 1;
+            while count < LANES && i + count < times.len() {
+                let t = times[i + count];
+                // The last grid value is handled in the next iteration
+                if t0 <= t && t < t1 {
+                    normalized[count] = (t - t0) * inverse_duration;
+                    count += 1;
+                } else {
+                    break;
+                }
+            }
+            let output = &mut output[i..i + count];
+            for c in 0..V::COUNT {
+                let values = evaluate_lanes(self.component_coefficients(idx, c), normalized);
+                for (o, value) in output.iter_mut().zip(values) {
+                    o.set_component(c, value);
+                }
+            }
+            i += count;
+        }
+    }
+}
+
+fn inverse_durations(grid: &[f32]) -> Box<[f32]> {
+    grid.windows(2).map(|t| 1.0 / (t[1] - t[0])).collect()
+}
+
+/// See [`PiecewiseCubicCurve::plane_crossings()`]
+/// and [`PiecewiseCubicCurve::sphere_crossings()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crossing {
+    pub time: f32,
+    pub entering: bool,
+}
+
+impl PiecewiseCubicCurve<f32> {
+    /// Time and distance of the point on the curve that's closest to `point`.
+    ///
+    /// If there are multiple closest points, one of them is returned.
+    #[must_use]
+    pub fn nearest_time(&self, point: f32) -> (f32, f32) {
+        self.nearest_time_by(point, |a, b| a * b)
+    }
+}
+
+impl<const D: usize> PiecewiseCubicCurve<SVector<f32, D>> {
+    /// Time and distance of the point on the curve that's closest to `point`.
+    ///
+    /// If there are multiple closest points, one of them is returned.
+    #[must_use]
+    pub fn nearest_time(&self, point: SVector<f32, D>) -> (f32, f32) {
+        self.nearest_time_by(point, |a, b| a.dot(&b))
+    }
+
+    /// Times at which the curve crosses the plane through `origin`
+    /// with the given `normal` (which doesn't have to be normalized).
+    ///
+    /// Crossings into the half-space `normal` points to are "entering".
+    /// Touching the plane without crossing doesn't count.
+    /// At jumps, the time of the jump is used.
+    #[must_use]
+    pub fn plane_crossings(
+        &self,
+        origin: SVector<f32, D>,
+        normal: SVector<f32, D>,
+    ) -> Vec<Crossing> {
+        self.crossings_by(
+            |a| {
+                vec![
+                    (a[0] - origin).dot(&normal),
+                    a[1].dot(&normal),
+                    a[2].dot(&normal),
+                    a[3].dot(&normal),
+                ]
+            },
+            false,
+        )
+    }
+
+    /// Times at which the curve enters or leaves the sphere
+    /// around `center` with the given `radius`.
+    ///
+    /// At jumps, the time of the jump is used.
+    #[must_use]
+    pub fn sphere_crossings(&self, center: SVector<f32, D>, radius: f32) -> Vec<Crossing> {
+        self.crossings_by(
+            |a| {
+                let c = [a[0] - center, a[1], a[2], a[3]];
+                // Squared radius minus squared distance
+                let mut coefficients = vec![0.0; 7];
+                coefficients[0] = radius * radius;
+                for (i, ci) in c.iter().enumerate() {
+                    for (j, cj) in c.iter().enumerate() {
+                        coefficients[i + j] -= ci.dot(cj);
+                    }
+                }
+                coefficients
+            },
+            false,
+        )
     }
 }
 
 impl<V: Vector> Spline<V> for PiecewiseCubicCurve<V> {
     fn evaluate(&self, t: f32) -> V {
-        let (t, t0, t1, a) = self.get_segment(t);
-        let t = (t - t0) / (t1 - t0);
+        let (t, _, a) = self.get_segment(t);
         ((a[3] * t + a[2]) * t + a[1]) * t + a[0]
     }
 
     fn grid(&self) -> &[f32] {
         &self.grid
     }
+
+    fn clamp_parameter_and_find_index(&self, t: f32) -> (f32, usize) {
+        let first = *self.grid.first().unwrap();
+        let last = *self.grid.last().unwrap();
+        if t < first {
+            (first, 0)
+        } else if t < last {
+            let idx = match &self.index {
+                Some(index) => index.find_index(&self.grid, t),
+                // NB: This doesn't work if a value is NaN
+                None => self.grid.upper_bound_by(|x| x.partial_cmp(&t).unwrap()) - 1,
+            };
+            (t, idx)
+        } else {
+            (last, self.grid.len() - 2)
+        }
+    }
+
+    fn sample_range_into(&self, start: f32, step: f32, output: &mut [V]) {
+        let mut idx = 0;
+        for (i, o) in output.iter_mut().enumerate() {
+            let t = start + i as f32 * step;
+            // Consecutive times are typically in the same segment
+            let (t, found) = if self.grid[idx] <= t && t < self.grid[idx + 1] {
+                (t, idx)
+            } else {
+                self.clamp_parameter_and_find_index(t)
+            };
+            idx = found;
+            let a = &self.segments[idx];
+            let t = (t - self.grid[idx]) * self.inverse_durations[idx];
+            *o = ((a[3] * t + a[2]) * t + a[1]) * t + a[0];
+        }
+    }
+}
+
+impl<V> MemoryUsage for PiecewiseCubicCurve<V> {
+    fn memory_usage(&self) -> usize {
+        let soa = self
+            .soa
+            .as_ref()
+            .map_or(0, |soa| soa.iter().map(|a| size_of_val(&**a)).sum());
+        size_of_val(&*self.segments)
+            + size_of_val(&*self.grid)
+            + size_of_val(&*self.inverse_durations)
+            + soa
+            + self.index.as_ref().map_or(0, BucketIndex::memory_usage)
+    }
 }
 
 impl<V> SplineWithVelocity<V, V> for PiecewiseCubicCurve<V>
@@ -66,9 +546,8 @@ where
     V: Vector,
 {
     fn evaluate_velocity(&self, t: f32) -> V {
-        let (t, t0, t1, a) = self.get_segment(t);
-        let t = (t - t0) / (t1 - t0);
-        ((a[3] * 3.0 * t + a[2] * 2.0) * t + a[1]) / (t1 - t0)
+        let (t, inverse_duration, a) = self.get_segment(t);
+        ((a[3] * 3.0 * t + a[2] * 2.0) * t + a[1]) * inverse_duration
     }
 }
 
@@ -91,6 +570,9 @@ mod tests {
         PiecewiseCubicCurve {
             segments: Box::new([[1.0, 2.5, 3.0, 4.0]]),
             grid: Box::new([5.0, 6.0]),
+            inverse_durations: Box::new([1.0]),
+            soa: None,
+            index: None,
         }
     }
 
@@ -119,6 +601,34 @@ mod tests {
         assert_eq!(curve.integrated_speed::<NormF32>(0, 5.0, 5.5), 2.5);
     }
 
+    #[test]
+    fn segment_length_cusp() {
+        use crate::utilities::gauss_legendre13;
+        // x(t) = (t - 0.3)^2, the speed has a kink where the velocity is zero
+        let curve = PiecewiseCubicCurve::new([[0.09, -0.6, 1.0, 0.0]], [0.0, 1.0]).unwrap();
+        let expected = 0.09 + 0.49;
+        let length = curve.integrated_speed_with::<NormF32>(
+            0,
+            0.0,
+            1.0,
+            crate::utilities::Quadrature::AdaptiveGaussKronrod,
+        );
+        assert!((length - expected).abs() < 1e-5);
+        let fixed = gauss_legendre13(|t| curve.evaluate_velocity(t).abs(), 0.0, 1.0);
+        assert!((fixed - expected).abs() > (length - expected).abs());
+        let (romberg, difference) =
+            crate::utilities::romberg(|t| curve.evaluate_velocity(t).abs(), 0.0, 1.0, 1e-5, 20);
+        assert!((romberg - expected).abs() < 1e-4);
+        assert!(difference <= 1e-5 * romberg);
+        // Limits beyond the fixed-size buffers are clamped
+        let speed = |t| curve.evaluate_velocity(t).abs();
+        let (length, _) =
+            crate::utilities::adaptive_gauss_kronrod(speed, 0.0, 1.0, 0.0, usize::MAX);
+        assert!((length - expected).abs() < 1e-5);
+        let (romberg, _) = crate::utilities::romberg(speed, 0.0, 1.0, 0.0, usize::MAX);
+        assert!((romberg - expected).abs() < 1e-4);
+    }
+
     #[test]
     #[should_panic(expected = "assertion failed")]
     fn segment_length_early_begin() {
@@ -133,6 +643,251 @@ mod tests {
         curve.integrated_speed::<NormF32>(0, 5.1, 6.1);
     }
 
+    #[test]
+    fn evaluate_into() {
+        use nalgebra::Vector3;
+        let v = Vector3::new;
+        let curve = PiecewiseCubicCurve::new(
+            [
+                [
+                    v(1.0, 2.0, 0.5),
+                    v(2.0, 0.0, 1.0),
+                    v(-1.0, 3.0, 0.5),
+                    v(4.0, 0.0, 0.0),
+                ],
+                [
+                    v(6.0, 7.0, 2.0),
+                    v(0.5, 1.0, -2.0),
+                    v(1.0, 0.0, 0.0),
+                    v(0.0, 1.0, 3.0),
+                ],
+            ],
+            [0.0, 1.5, 4.0],
+        )
+        .unwrap();
+        // Sorted (with some values outside of the grid), then in reverse
+        let mut times: Vec<f32> = (-3..45).map(|i| i as f32 * 0.1).collect();
+        times.extend(times.clone().iter().rev());
+        let mut output = vec![Vector3::zeros(); times.len()];
+        curve.evaluate_into(&times, &mut output);
+        for (&t, value) in times.iter().zip(&output) {
+            assert_eq!(*value, curve.evaluate(t));
+        }
+        let soa = curve.with_soa_layout();
+        assert!(soa.has_soa_layout());
+        let mut soa_output = vec![Vector3::zeros(); times.len()];
+        soa.evaluate_into(&times, &mut soa_output);
+        assert_eq!(soa_output, output);
+        let scalar = make_simple_curve();
+        let mut output = [0.0; 3];
+        scalar.evaluate_into(&[5.0, 5.5, 6.0], &mut output);
+        assert_eq!(output, [1.0, 3.5, 10.5]);
+    }
+
+    #[test]
+    fn segment_bounds() {
+        use nalgebra::Vector3;
+        let scalar = make_simple_curve();
+        let points = scalar.segment_control_points(0);
+        assert_eq!(points[0], scalar.evaluate(5.0));
+        assert_eq!(points[3], scalar.evaluate(6.0));
+        assert_eq!(scalar.segment_bounds(0), (1.0, 10.5));
+        let v = Vector3::new;
+        let curve = PiecewiseCubicCurve::new(
+            [[
+                v(1.0, 2.0, 0.5),
+                v(2.0, 0.0, 1.0),
+                v(-1.0, 3.0, 0.5),
+                v(4.0, -6.0, 0.0),
+            ]],
+            [0.0, 2.0],
+        )
+        .unwrap();
+        let (min, max) = curve.segment_bounds(0);
+        for i in 0..=100 {
+            let value = curve.evaluate(i as f32 * 0.02);
+            for c in 0..3 {
+                assert!(min[c] - 1e-6 <= value[c] && value[c] <= max[c] + 1e-6);
+            }
+        }
+        // Not tight: the actual maximum of the y component is about 2.11
+        assert_eq!(max[1], 3.0);
+    }
+
+    #[test]
+    fn bucket_index() {
+        // Irregular grid with a jump
+        let mut grid = vec![0.0f32];
+        for i in 1..300 {
+            let step = if i % 7 == 0 {
+                2.5
+            } else {
+                0.01 * (i % 5) as f32 + 0.1
+            };
+            grid.push(grid[i - 1] + step);
+        }
+        grid.insert(150, grid[149]);
+        let segments: Vec<_> = (1..grid.len())
+            .map(|i| [i as f32, 1.0, 0.0, -0.5])
+            .collect();
+        let curve =
+            PiecewiseCubicCurve::with_jumps(segments.clone(), grid.clone(), &[149]).unwrap();
+        assert!(!curve.has_bucket_index());
+        let indexed = PiecewiseCubicCurve::with_jumps(segments, grid.clone(), &[149])
+            .unwrap()
+            .with_bucket_index();
+        assert!(indexed.has_bucket_index());
+        let end = *grid.last().unwrap();
+        let times = (0..5000)
+            .map(|i| -1.0 + (end + 2.0) * i as f32 / 4999.0)
+            .chain(grid.iter().copied());
+        for t in times {
+            assert_eq!(
+                indexed.clamp_parameter_and_find_index(t),
+                curve.clamp_parameter_and_find_index(t)
+            );
+            assert_eq!(indexed.evaluate(t), curve.evaluate(t));
+        }
+    }
+
+    #[test]
+    fn sample_range_into() {
+        let curve = PiecewiseCubicCurve::with_jumps(
+            [
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0],
+                [5.0, 1.0, -2.0, 0.5],
+                [1.0, 0.0, 3.0, 0.0],
+            ],
+            [0.0, 1.0, 1.0, 2.0, 2.5],
+            &[1],
+        )
+        .unwrap();
+        let mut output = [0.0; 40];
+        for (start, step) in [(-0.3, 0.1), (2.7, -0.1), (0.0, 0.0), (1.0, 0.5)] {
+            curve.sample_range_into(start, step, &mut output);
+            for (i, &value) in output.iter().enumerate() {
+                assert_eq!(value, curve.evaluate(start + i as f32 * step));
+            }
+        }
+        // Through a trait object
+        let boxed: Box<dyn Spline<f32>> = Box::new(curve);
+        let mut expected = output;
+        boxed.sample_range_into(0.05, 0.1, &mut output);
+        for (i, e) in expected.iter_mut().enumerate() {
+            *e = boxed.evaluate(0.05 + i as f32 * 0.1);
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn jump() {
+        let curve = PiecewiseCubicCurve::with_jumps(
+            [
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0],
+                [5.0, 1.0, 0.0, 0.0],
+            ],
+            [0.0, 1.0, 1.0, 2.0],
+            &[1],
+        )
+        .unwrap();
+        assert_eq!(curve.jumps(), [1]);
+        assert_eq!(curve.evaluate(0.5), 0.5);
+        assert_eq!(curve.evaluate_left(0.5), 0.5);
+        assert_eq!(curve.evaluate(1.0), 5.0);
+        assert_eq!(curve.evaluate_left(1.0), 1.0);
+        assert_eq!(curve.evaluate(2.0), 6.0);
+        assert_eq!(curve.evaluate_left(2.0), 6.0);
+        assert_eq!(curve.evaluate_left(0.0), 0.0);
+        assert_eq!(curve.evaluate_velocity(1.0), 1.0);
+        // Jumps are not allowed at the ends
+        assert!(PiecewiseCubicCurve::with_jumps(
+            [[0.0f32, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0]],
+            [0.0, 0.0, 1.0],
+            &[0],
+        )
+        .is_err());
+        // Equal grid values need a flag
+        assert!(PiecewiseCubicCurve::new(
+            [
+                [0.0f32, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0],
+                [5.0, 1.0, 0.0, 0.0],
+            ],
+            [0.0, 1.0, 1.0, 2.0],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn nearest_time() {
+        let curve = make_simple_curve();
+        let (t, distance) = curve.nearest_time(3.5);
+        assert!((t - 5.5).abs() < 1e-6 && distance < 1e-6);
+        assert_eq!(curve.nearest_time(20.0), (6.0, 9.5));
+        assert_eq!(curve.nearest_time(0.0), (5.0, 1.0));
+        type Vec2 = nalgebra::Vector2<f32>;
+        // Parabola y = x^2 for x from -1 to 1
+        let parabola = PiecewiseCubicCurve::new(
+            [[
+                Vec2::new(-1.0, 1.0),
+                Vec2::new(2.0, -4.0),
+                Vec2::new(0.0, 4.0),
+                Vec2::zeros(),
+            ]],
+            [0.0, 2.0],
+        )
+        .unwrap();
+        let (t, distance) = parabola.nearest_time(Vec2::new(0.0, -1.0));
+        assert!((t - 1.0).abs() < 1e-4);
+        assert!((distance - 1.0).abs() < 1e-6);
+        let (t, distance) = parabola.nearest_time(Vec2::new(1.0, 0.5));
+        let x = -1.0 + t;
+        assert!((distance - (Vec2::new(1.0, 0.5) - Vec2::new(x, x * x)).norm()).abs() < 1e-6);
+        // Distance to the parabola is minimal where the offset is perpendicular to the tangent
+        assert!((Vec2::new(1.0 - x, 0.5 - x * x).dot(&Vec2::new(1.0, 2.0 * x))).abs() < 1e-4);
+    }
+
+    #[test]
+    fn crossings() {
+        use crate::quaternion::Vec3;
+        let zero = Vec3::zeros();
+        // Straight line along x from -2 to 2, then back to 0
+        let curve = PiecewiseCubicCurve::new(
+            [
+                [
+                    Vec3::new(-2.0, 0.0, 0.0),
+                    Vec3::new(4.0, 0.0, 0.0),
+                    zero,
+                    zero,
+                ],
+                [
+                    Vec3::new(2.0, 0.0, 0.0),
+                    Vec3::new(-2.0, 0.0, 0.0),
+                    zero,
+                    zero,
+                ],
+            ],
+            [0.0, 4.0, 6.0],
+        )
+        .unwrap();
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let plane = curve.plane_crossings(Vec3::new(1.0, 5.0, 5.0), x);
+        assert_eq!(plane.len(), 2);
+        assert!((plane[0].time - 3.0).abs() < 1e-5 && plane[0].entering);
+        assert!((plane[1].time - 5.0).abs() < 1e-5 && !plane[1].entering);
+        // Touching at a grid value isn't a crossing
+        let plane = curve.plane_crossings(Vec3::new(2.0, 0.0, 0.0), -x);
+        assert_eq!(plane.len(), 0);
+        let sphere = curve.sphere_crossings(zero, 1.0);
+        assert_eq!(sphere.len(), 3);
+        assert!((sphere[0].time - 1.0).abs() < 1e-4 && sphere[0].entering);
+        assert!((sphere[1].time - 3.0).abs() < 1e-4 && !sphere[1].entering);
+        assert!((sphere[2].time - 5.0).abs() < 1e-4 && sphere[2].entering);
+        assert_eq!(curve.sphere_crossings(zero, 3.0), []);
+    }
+
     #[test]
     fn grid() {
         let curve = make_simple_curve();
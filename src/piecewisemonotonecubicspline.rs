@@ -62,7 +62,7 @@ impl PiecewiseCubicCurve<f32> {
         use PiecewiseMonotoneWithSlopesError::*;
         let mut values = values.into();
         let optional_slopes = optional_slopes.as_ref();
-        let mut grid = grid.into();
+        let grid = grid.into();
         if values.len() < 2 {
             return Err(LessThanTwoValues.into());
         }
@@ -81,60 +81,53 @@ impl PiecewiseCubicCurve<f32> {
             .into());
         }
         check_grid(&grid).map_err(FromGridError)?;
+        let segments_len = grid.len() - 1;
         if closed {
-            values.reserve_exact(2);
-            grid.reserve_exact(1);
             // Closing the curve:
+            values.reserve_exact(1);
             values.push(values[0]);
-            // One additional (temporary) value to calculate slopes:
-            values.push(values[1]);
-            grid.push(*grid.last().unwrap() + grid[1] - grid[0]);
         }
-        let mut slopes = Vec::new();
-        for i in 0..values.len() - 2 {
-            if let (&[x_1, x0, x1, ..], &[t_1, t0, t1, ..]) = (&values[i..], &grid[i..]) {
-                let left = (x0 - x_1) / (t0 - t_1);
-                let right = (x1 - x0) / (t1 - t0);
-                let slope = match optional_slopes[(i + 1) % optional_slopes.len()] {
-                    Some(slope) => verify_slope(slope, left, right, i + 1)?,
-                    None => fix_slope(catmull_rom_slope([x_1, x0, x1], [t_1, t0, t1]), left, right),
-                };
-                slopes.push(slope); // incoming
-                slopes.push(slope); // outgoing
+        // Incoming and outgoing slope for each segment:
+        let mut slopes = vec![0.0; 2 * segments_len];
+        // Inner values (for closed curves, this includes the last one):
+        for i in 1..values.len() - !closed as usize {
+            let (x1, t1) = if i < segments_len {
+                (values[i + 1], grid[i + 1])
             } else {
-                unreachable!();
-            }
+                // Wrap around to the second value:
+                (values[1], grid[i] + grid[1] - grid[0])
+            };
+            let (x_1, x0) = (values[i - 1], values[i]);
+            let (t_1, t0) = (grid[i - 1], grid[i]);
+            let left = (x0 - x_1) / (t0 - t_1);
+            let right = (x1 - x0) / (t1 - t0);
+            let slope = match optional_slopes[i % optional_slopes.len()] {
+                Some(slope) => verify_slope(slope, left, right, i)?,
+                None => fix_slope(catmull_rom_slope([x_1, x0, x1], [t_1, t0, t1]), left, right),
+            };
+            slopes[2 * i - 1] = slope; // incoming
+            slopes[(2 * i) % (2 * segments_len)] = slope; // outgoing
         }
-        if closed {
-            // Move last (outgoing) tangent to the beginning:
-            slopes.rotate_right(1);
-            // Remove temporary values:
-            values.pop();
-            grid.pop();
-        } else if optional_slopes.len() == 2 {
-            assert!(slopes.is_empty());
-            let chord = (values[1] - values[0]) / (grid[1] - grid[0]);
-            let one = optional_slopes[0];
-            let two = optional_slopes[1];
-            slopes.push(calculate_slope(one, two, chord, 0)?);
-            slopes.push(calculate_slope(two, one, chord, 1)?);
-        } else {
-            slopes.insert(
+        if !closed {
+            let last = slopes.len() - 1;
+            let (first_inner, last_inner) = if segments_len == 1 {
+                (optional_slopes[1], optional_slopes[0])
+            } else {
+                (Some(slopes[1]), Some(slopes[last - 1]))
+            };
+            slopes[0] = calculate_slope(
+                optional_slopes[0],
+                first_inner,
+                (values[1] - values[0]) / (grid[1] - grid[0]),
                 0,
-                calculate_slope(
-                    *optional_slopes.first().unwrap(),
-                    slopes.first().copied(),
-                    (values[1] - values[0]) / (grid[1] - grid[0]),
-                    0,
-                )?,
-            );
-            slopes.push(calculate_slope(
-                *optional_slopes.last().unwrap(),
-                slopes.last().copied(),
-                (values[values.len() - 1] - values[values.len() - 2])
-                    / (grid[grid.len() - 1] - grid[grid.len() - 2]),
-                slopes.len(),
-            )?);
+            )?;
+            slopes[last] = calculate_slope(
+                optional_slopes[segments_len],
+                last_inner,
+                (values[segments_len] - values[segments_len - 1])
+                    / (grid[segments_len] - grid[segments_len - 1]),
+                segments_len,
+            )?;
         }
         PiecewiseCubicCurve::new_hermite(&values, &slopes, &grid).map_err(|e| {
             use crate::cubichermitespline::Error as E;
@@ -0,0 +1,156 @@
+//! Analysis of two-dimensional curves, e.g. horizontal trajectories.
+//!
+//! Positive angles and areas are counter-clockwise (with y pointing "up").
+
+use nalgebra::Vector2;
+
+use crate::math;
+use crate::{PiecewiseCubicCurve, Spline};
+
+pub type Vec2 = Vector2<f32>;
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+impl PiecewiseCubicCurve<Vec2> {
+    /// Positive when turning left, negative when turning right.
+    ///
+    /// Returns `None` where the velocity is zero (e.g. at cusps)
+    /// or where the curvature is too large to be represented.
+    #[must_use]
+    pub fn signed_curvature(&self, t: f32) -> Option<f32> {
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let (t0, t1) = (self.grid()[idx], self.grid()[idx + 1]);
+        let a = &self.segments()[idx];
+        let t = (t - t0) / (t1 - t0);
+        // The scaling with the segment duration cancels out
+        let velocity = (a[3] * 3.0 * t + a[2] * 2.0) * t + a[1];
+        let acceleration = a[3] * 6.0 * t + a[2] * 2.0;
+        let speed = math::hypot(velocity.x, velocity.y);
+        // Dividing early avoids overflow of the cross product and underflow of speed^3
+        let curvature = cross(velocity / speed, acceleration / speed) / speed;
+        curvature.is_finite().then(|| curvature)
+    }
+
+    /// Signed area enclosed by the curve, positive if counter-clockwise.
+    ///
+    /// The curve is assumed to be closed, i.e. to end where it started.
+    /// Self-intersecting curves add up the areas of their loops
+    /// (weighted by their winding numbers).
+    #[must_use]
+    pub fn enclosed_area(&self) -> f32 {
+        // Green's theorem: half the integral of x dy - y dx
+        let mut area = 0.0;
+        for a in self.segments() {
+            for (i, ai) in a.iter().enumerate() {
+                for (j, aj) in a.iter().enumerate().skip(1) {
+                    // Integral of s^i * j s^(j-1) from 0 to 1
+                    area += cross(*ai, *aj) * j as f32 / (i + j) as f32;
+                }
+            }
+        }
+        area / 2.0
+    }
+
+    /// How often the curve goes around `point` counter-clockwise.
+    ///
+    /// The curve is assumed to be closed, i.e. to end where it started.
+    /// The result for points on the curve is unspecified.
+    #[must_use]
+    pub fn winding_number(&self, point: Vec2) -> i32 {
+        // Crossings of a ray from `point` in x direction
+        self.crossings_by(|a| vec![a[0].y - point.y, a[1].y, a[2].y, a[3].y], true)
+            .iter()
+            .filter(|crossing| self.evaluate(crossing.time).x > point.x)
+            .map(|crossing| if crossing.entering { 1 } else { -1 })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::presets::circle;
+
+    fn polygon(corners: &[Vec2]) -> PiecewiseCubicCurve<Vec2> {
+        let zero = Vec2::zeros();
+        let n = corners.len();
+        let segments: Vec<_> = (0..n)
+            .map(|i| {
+                let (a, b) = (corners[i], corners[(i + 1) % n]);
+                [a, b - a, zero, zero]
+            })
+            .collect();
+        let grid: Vec<_> = (0..=n).map(|i| i as f32).collect();
+        PiecewiseCubicCurve::new(segments, grid).unwrap()
+    }
+
+    #[test]
+    fn area_and_winding() {
+        let s = polygon(&[
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ]);
+        assert_eq!(s.enclosed_area(), 4.0);
+        assert_eq!(s.winding_number(Vec2::new(1.0, 1.0)), 1);
+        // Ray along an edge
+        assert_eq!(s.winding_number(Vec2::new(-1.0, 2.0)), 0);
+        assert_eq!(s.winding_number(Vec2::new(-1.0, 0.0)), 0);
+        assert_eq!(s.winding_number(Vec2::new(-1.0, 1.0)), 0);
+        assert_eq!(s.winding_number(Vec2::new(3.0, 1.0)), 0);
+        // Clockwise, ray through the start
+        let d = polygon(&[
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, -1.0),
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+        ]);
+        assert_eq!(d.enclosed_area(), -2.0);
+        assert_eq!(d.winding_number(Vec2::zeros()), -1);
+        let c = circle(crate::quaternion::Vec3::zeros(), 2.0, 1.0, 1e-5).unwrap();
+        let segments: Vec<_> = c
+            .segments()
+            .iter()
+            .map(|a| [a[0].xy(), a[1].xy(), a[2].xy(), a[3].xy()])
+            .collect();
+        let c = PiecewiseCubicCurve::new(segments, c.grid()).unwrap();
+        assert!((c.enclosed_area() - std::f32::consts::PI * 4.0).abs() < 1e-3);
+        assert_eq!(c.winding_number(Vec2::new(0.0, 0.0)), 1);
+        assert_eq!(c.winding_number(Vec2::new(1.0, 1.9)), 0);
+        assert!((c.signed_curvature(0.3).unwrap() - 0.5).abs() < 1e-3);
+        assert_eq!(s.signed_curvature(0.5), Some(0.0));
+    }
+
+    #[test]
+    fn curvature_limits() {
+        // Velocity (scale, 0) and acceleration (0, scale) at the start,
+        // i.e. the curvature is 1 / scale
+        let curve = |scale: f32| {
+            let zero = Vec2::zeros();
+            let a = [
+                zero,
+                Vec2::new(scale, 0.0),
+                Vec2::new(0.0, scale / 2.0),
+                zero,
+            ];
+            PiecewiseCubicCurve::new([a], [0.0, 1.0]).unwrap()
+        };
+        assert_eq!(curve(1.0).signed_curvature(0.0), Some(1.0));
+        assert_eq!(curve(1e-15).signed_curvature(0.0), Some(1e15));
+        assert_eq!(curve(1e20).signed_curvature(0.0), Some(1e-20));
+        assert_eq!(curve(0.0).signed_curvature(0.0), None);
+        assert_eq!(curve(1e-30).signed_curvature(0.0), Some(1e30));
+        // Too large for f32
+        assert_eq!(curve(1e-39).signed_curvature(0.0), None);
+        // Cusp of a semicubical parabola
+        let zero = Vec2::zeros();
+        let a = [zero, zero, Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0)];
+        let cusp = PiecewiseCubicCurve::new([a], [0.0, 1.0]).unwrap();
+        assert_eq!(cusp.signed_curvature(0.0), None);
+        assert!(cusp.signed_curvature(0.5).unwrap() > 0.0);
+    }
+}
@@ -0,0 +1,287 @@
+//! Piecewise cubic approximations of common trajectories.
+//!
+//! All trajectories start at time 0.
+//! Circular movements are counter-clockwise (seen from above),
+//! starting in front of the center (i.e. in y direction).
+//!
+//! The number of segments is doubled until the deviation from the exact trajectory
+//! (checked at a few points within each segment) is within the given tolerance.
+
+use std::f32::consts::TAU;
+
+use crate::math::{cos, sin};
+use crate::quaternion::Vec3;
+use crate::{PiecewiseCubicCurve, Spline};
+
+const MAX_SEGMENTS: usize = 1 << 14;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("duration must be a positive number (not {duration:?})")]
+    InvalidDuration { duration: f32 },
+    #[error("tolerance must be a positive number (not {tolerance:?})")]
+    InvalidTolerance { tolerance: f32 },
+    #[error("{parameter} must be finite (not {value:?})")]
+    NonFinite { parameter: &'static str, value: f32 },
+    #[error("tolerance not reached with {segments} segments (error: {error:?})")]
+    ToleranceNotReached { segments: usize, error: f32 },
+}
+
+fn fit(
+    position: impl Fn(f32) -> Vec3,
+    velocity: impl Fn(f32) -> Vec3,
+    duration: f32,
+    initial_segments: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    use Error::*;
+    let mut segments = initial_segments.ceil().max(1.0).min(MAX_SEGMENTS as f32) as usize;
+    check_duration(duration)?;
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(tolerance > 0.0) {
+        return Err(InvalidTolerance { tolerance });
+    }
+    loop {
+        let grid: Vec<_> = (0..=segments)
+            .map(|i| duration * i as f32 / segments as f32)
+            .collect();
+        let positions: Vec<_> = grid.iter().map(|&t| position(t)).collect();
+        let tangents: Vec<_> = grid
+            .windows(2)
+            .flat_map(|w| [velocity(w[0]), velocity(w[1])])
+            .collect();
+        let curve = PiecewiseCubicCurve::new_hermite(&positions, &tangents, &grid)
+            .expect("grid is ascending");
+        let mut error: f32 = 0.0;
+        for w in grid.windows(2) {
+            for x in [0.25, 0.5, 0.75] {
+                let t = w[0] + (w[1] - w[0]) * x;
+                let deviation = (curve.evaluate(t) - position(t)).norm();
+                // NB: f32::max() would ignore NaN
+                if deviation > error || deviation.is_nan() {
+                    error = deviation;
+                }
+            }
+        }
+        if error <= tolerance {
+            return Ok(curve);
+        }
+        // More segments won't help against overflow
+        if segments >= MAX_SEGMENTS || error.is_nan() {
+            return Err(ToleranceNotReached { segments, error });
+        }
+        segments *= 2;
+    }
+}
+
+fn check_duration(duration: f32) -> Result<(), Error> {
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(duration > 0.0) || duration.is_infinite() {
+        return Err(Error::InvalidDuration { duration });
+    }
+    Ok(())
+}
+
+fn check_finite(parameter: &'static str, values: &[f32]) -> Result<(), Error> {
+    match values.iter().find(|x| !x.is_finite()) {
+        Some(&value) => Err(Error::NonFinite { parameter, value }),
+        None => Ok(()),
+    }
+}
+
+/// Circle in the horizontal plane, one revolution per `period`.
+pub fn circle(
+    center: Vec3,
+    radius: f32,
+    period: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    ellipse(center, radius, radius, period, tolerance)
+}
+
+/// Ellipse in the horizontal plane, one revolution per `period`.
+pub fn ellipse(
+    center: Vec3,
+    radius_x: f32,
+    radius_y: f32,
+    period: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    let w = TAU / period;
+    check_finite("center", center.as_slice())?;
+    check_finite("radius_x", &[radius_x])?;
+    check_finite("radius_y", &[radius_y])?;
+    check_duration(period)?;
+    check_finite("2π / period", &[w])?;
+    fit(
+        |t| center + Vec3::new(-radius_x * sin(w * t), radius_y * cos(w * t), 0.0),
+        |t| Vec3::new(-radius_x * w * cos(w * t), -radius_y * w * sin(w * t), 0.0),
+        period,
+        4.0,
+        tolerance,
+    )
+}
+
+/// Circular movement with constant upwards speed, starting at `center`'s height.
+pub fn helix(
+    center: Vec3,
+    radius: f32,
+    period: f32,
+    rise_per_turn: f32,
+    turns: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    let w = TAU / period;
+    let rise = rise_per_turn / period;
+    check_finite("center", center.as_slice())?;
+    check_finite("radius", &[radius])?;
+    check_duration(period)?;
+    check_finite("2π / period", &[w])?;
+    check_finite("rise_per_turn / period", &[rise])?;
+    fit(
+        |t| center + Vec3::new(-radius * sin(w * t), radius * cos(w * t), rise * t),
+        |t| Vec3::new(-radius * w * cos(w * t), -radius * w * sin(w * t), rise),
+        period * turns,
+        4.0 * turns,
+        tolerance,
+    )
+}
+
+/// Independent sinusoidal movement along each axis.
+///
+/// `frequencies` are in Hertz, `phases` in radians.
+pub fn lissajous(
+    center: Vec3,
+    amplitudes: Vec3,
+    frequencies: Vec3,
+    phases: Vec3,
+    duration: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    let arg = move |t: f32| frequencies * (TAU * t) + phases;
+    let max_frequency = frequencies.abs().max();
+    check_finite("center", center.as_slice())?;
+    check_finite("amplitudes", amplitudes.as_slice())?;
+    check_finite("frequencies", frequencies.as_slice())?;
+    check_finite("phases", phases.as_slice())?;
+    fit(
+        |t| center + amplitudes.component_mul(&arg(t).map(sin)),
+        |t| {
+            amplitudes
+                .component_mul(&frequencies)
+                .component_mul(&arg(t).map(cos))
+                * TAU
+        },
+        duration,
+        4.0 * max_frequency * duration,
+        tolerance,
+    )
+}
+
+/// Horizontal figure-eight, crossing `center` at the start
+/// and then turning left, with the given total width (x) and length (y).
+pub fn figure_eight(
+    center: Vec3,
+    width: f32,
+    length: f32,
+    period: f32,
+    tolerance: f32,
+) -> Result<PiecewiseCubicCurve<Vec3>, Error> {
+    let f = 1.0 / period;
+    lissajous(
+        center,
+        Vec3::new(-width / 2.0, length / 2.0, 0.0),
+        Vec3::new(2.0 * f, f, 0.0),
+        Vec3::zeros(),
+        period,
+        tolerance,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_accuracy() {
+        let c = Vec3::new(1.0, 2.0, 3.0);
+        for tolerance in [1e-2, 1e-4] {
+            let curve = circle(c, 2.0, 4.0, tolerance).unwrap();
+            assert_eq!(curve.grid().last(), Some(&4.0));
+            assert!((curve.evaluate(0.0) - Vec3::new(1.0, 4.0, 3.0)).norm() < 1e-6);
+            assert!((curve.evaluate(1.0) - Vec3::new(-1.0, 2.0, 3.0)).norm() < 1e-5);
+            for i in 0..100 {
+                let t = i as f32 * 0.04;
+                let r = (curve.evaluate(t) - c).norm();
+                assert!((r - 2.0).abs() <= tolerance * 1.1);
+            }
+        }
+        let coarse = circle(c, 2.0, 4.0, 1e-2).unwrap();
+        let fine = circle(c, 2.0, 4.0, 1e-4).unwrap();
+        assert!(coarse.segments().len() < fine.segments().len());
+    }
+
+    #[test]
+    fn helix_and_figure_eight() {
+        let h = helix(Vec3::zeros(), 1.0, 2.0, 0.5, 3.0, 1e-3).unwrap();
+        assert_eq!(h.grid().last(), Some(&6.0));
+        assert!((h.evaluate(6.0) - Vec3::new(0.0, 1.0, 1.5)).norm() < 1e-4);
+        let e = figure_eight(Vec3::zeros(), 2.0, 4.0, 8.0, 1e-3).unwrap();
+        assert!(e.evaluate(0.0).norm() < 1e-6);
+        assert!(e.evaluate(4.0).norm() < 1e-4);
+        assert!((e.evaluate(2.0) - Vec3::new(0.0, 2.0, 0.0)).norm() < 1e-4);
+        assert!(e.evaluate(1.0).x < 0.0);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            circle(Vec3::zeros(), 1.0, 0.0, 1e-3),
+            Err(Error::InvalidDuration { .. })
+        ));
+        assert!(matches!(
+            circle(Vec3::zeros(), 1.0, 1.0, 0.0),
+            Err(Error::InvalidTolerance { .. })
+        ));
+        assert!(matches!(
+            circle(Vec3::zeros(), f32::NAN, 1.0, 1e-3),
+            Err(Error::NonFinite {
+                parameter: "radius_x",
+                ..
+            })
+        ));
+        assert!(matches!(
+            circle(Vec3::zeros(), 1.0, 1e-44, 1e-3),
+            Err(Error::NonFinite {
+                parameter: "2π / period",
+                ..
+            })
+        ));
+        assert!(matches!(
+            lissajous(
+                Vec3::zeros(),
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(f32::INFINITY, 1.0, 1.0),
+                Vec3::zeros(),
+                1.0,
+                1e-3,
+            ),
+            Err(Error::NonFinite {
+                parameter: "frequencies",
+                ..
+            })
+        ));
+        // Finite parameters, but the velocity overflows
+        assert!(matches!(
+            lissajous(
+                Vec3::zeros(),
+                Vec3::new(1e30, 1.0, 1.0),
+                Vec3::new(1e10, 1.0, 1.0),
+                Vec3::zeros(),
+                1.0,
+                1e-3,
+            ),
+            Err(Error::ToleranceNotReached { .. })
+        ));
+    }
+}
@@ -1,4 +1,6 @@
-use super::{canonicalize, negate, CubicDeCasteljau, UnitQuaternion};
+use crate::math;
+
+use super::{canonicalize, CubicDeCasteljau, UnitQuaternion, Vec3};
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -16,25 +18,67 @@ pub enum Error {
     },
     #[error("repeated quaternion (at index {index}) is not allowed")]
     RepeatedQuaternion { index: usize },
+    #[error("index {index}: rotation from previous quaternion is longer than 180 degrees")]
+    LongRotation { index: usize },
+    #[error("number of tangents ({tangents}) and quaternions ({quaternions}) must be the same")]
+    TangentsVsQuaternions { tangents: usize, quaternions: usize },
+    #[error("parameterization exponent must be a non-negative number (not {exponent:?})")]
+    InvalidExponent { exponent: f32 },
+    #[error("number of windings ({windings}) must be {} number of quaternions ({quaternions})", if *.closed {
+        "the same as"
+    } else {
+        "one less than"
+    })]
+    WindingsVsQuaternions {
+        windings: usize,
+        quaternions: usize,
+        closed: bool,
+    },
+    #[error("index {index}: rotation from previous quaternion is too long for a single segment")]
+    TooLongRotation { index: usize },
+}
+
+/// Spline parameter difference between neighboring quaternions,
+/// depending on the rotation angle between them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameterization {
+    /// The same for all segments.
+    Uniform,
+    /// Square root of the angle.
+    Centripetal,
+    /// Proportional to the angle.
+    Chordal,
+    /// Angle<sup>exponent</sup>
+    Exponent(f32),
 }
 
-fn calculate_control_quaternions(
-    qs: &[UnitQuaternion],
-    ts: &[f32],
-    tcb: &[f32],
-) -> (UnitQuaternion, UnitQuaternion) {
+impl Parameterization {
+    fn exponent(&self) -> f32 {
+        match *self {
+            Parameterization::Uniform => 0.0,
+            Parameterization::Centripetal => 0.5,
+            Parameterization::Chordal => 1.0,
+            Parameterization::Exponent(exponent) => exponent,
+        }
+    }
+}
+
+/// Rotation vectors from the quaternion to its incoming and outgoing control quaternions.
+///
+/// The incoming control quaternion is `from_scaled_axis(-incoming) * q0`,
+/// the outgoing one is `from_scaled_axis(outgoing) * q0`.
+/// `rotations` are the (world frame) rotation vectors of the incoming and outgoing segments.
+fn calculate_control_steps(rotations: [Vec3; 2], ts: &[f32], tcb: &[f32]) -> (Vec3, Vec3) {
     #[allow(non_snake_case)]
-    if let ([q_1, q0, q1], [t_1, t0, t1], [T, C, B]) = (qs, ts, tcb) {
+    if let ([r_in, r_out], [t_1, t0, t1], [T, C, B]) = (rotations, ts, tcb) {
         let a = (1.0 - T) * (1.0 + C) * (1.0 + B);
         let b = (1.0 - T) * (1.0 - C) * (1.0 - B);
         let c = (1.0 - T) * (1.0 - C) * (1.0 + B);
         let d = (1.0 - T) * (1.0 + C) * (1.0 - B);
 
-        let q_in = q_1.rotation_to(q0);
-        let q_out = q0.rotation_to(q1);
         // w means omega (i.e. the angular velocity vector)
-        let w_in = q_in.scaled_axis() / (t0 - t_1);
-        let w_out = q_out.scaled_axis() / (t1 - t0);
+        let w_in = r_in / (t0 - t_1);
+        let w_out = r_out / (t1 - t0);
 
         let w0 = |weight_in, weight_out| {
             (weight_in * (t1 - t0) * w_in + weight_out * (t0 - t_1) * w_out) / (t1 - t_1)
@@ -42,17 +86,178 @@ fn calculate_control_quaternions(
 
         let degree = 3.0;
         (
-            UnitQuaternion::from_scaled_axis(-w0(c, d) * (t0 - t_1) / degree) * q0,
-            UnitQuaternion::from_scaled_axis(w0(a, b) * (t1 - t0) / degree) * q0,
+            w0(c, d) * (t0 - t_1) / degree,
+            w0(a, b) * (t1 - t0) / degree,
         )
     } else {
         unreachable!();
     }
 }
 
-/// Calculates second control quaternion given first and third.
-fn natural_control_quaternion(first: &UnitQuaternion, third: &UnitQuaternion) -> UnitQuaternion {
-    first.rotation_to(third).powf(0.5) * first
+/// How the spline behaves at the first and last quaternion (if not closed).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EndCondition {
+    /// Zero angular acceleration.
+    Natural,
+    /// Given angular velocity, in radians per unit of the (centripetal) spline parameter.
+    Clamped(Vec3),
+    /// Zero angular velocity.
+    Rest,
+}
+
+impl EndCondition {
+    /// Rotation vector to the second control quaternion, counting from the end at `first`.
+    ///
+    /// `third` is the natural neighbor and `delta` the (signed) length of the segment.
+    /// If given, `intended` is the (possibly long) rotation vector from `first` to `third`.
+    fn control_step(
+        self,
+        first: &UnitQuaternion,
+        third: &UnitQuaternion,
+        delta: f32,
+        intended: Option<Vec3>,
+    ) -> Vec3 {
+        match self {
+            EndCondition::Natural => {
+                intended.unwrap_or_else(|| first.rotation_to(third).scaled_axis()) / 2.0
+            }
+            EndCondition::Clamped(velocity) => velocity * delta / 3.0,
+            EndCondition::Rest => Vec3::zeros(),
+        }
+    }
+}
+
+/// How to rotate from one quaternion to the next one.
+///
+/// By default, the shorter arc (up to 180 degrees) is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Winding {
+    /// Rotate along the longer arc (in the opposite direction).
+    pub long_way: bool,
+    /// Additional full turns (in the direction of rotation).
+    pub extra_turns: u32,
+}
+
+impl Winding {
+    /// Rotation vector, given the one of the shorter arc.
+    ///
+    /// Returns `None` if the direction is undefined.
+    fn apply(self, shortest: Vec3) -> Option<Vec3> {
+        if self == Winding::default() {
+            return Some(shortest);
+        }
+        let angle = shortest.norm();
+        if angle == 0.0 {
+            return None;
+        }
+        let axis = shortest / angle;
+        let angle = if self.long_way {
+            angle - std::f32::consts::TAU
+        } else {
+            angle
+        };
+        let turns = self.extra_turns as f32 * std::f32::consts::TAU;
+        Some(axis * (angle + angle.signum() * turns))
+    }
+}
+
+/// Angular velocities overriding the ones calculated from TCB values,
+/// in radians per unit of the (centripetal) spline parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AngularTangents {
+    pub incoming: Option<Vec3>,
+    pub outgoing: Option<Vec3>,
+}
+
+impl AngularTangents {
+    /// The same angular velocity on both sides.
+    #[must_use]
+    pub fn both(velocity: Vec3) -> AngularTangents {
+        AngularTangents {
+            incoming: Some(velocity),
+            outgoing: Some(velocity),
+        }
+    }
+}
+
+/// Options for [`CubicDeCasteljau::new_kochanek_bartels_with_options()`].
+///
+/// The defaults are the ones of
+/// [`CubicDeCasteljau::new_centripetal_kochanek_bartels()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    start: EndCondition,
+    end: EndCondition,
+    canonical: bool,
+    parameterization: Parameterization,
+    windings: Option<Vec<Winding>>,
+    tangents: Option<Vec<AngularTangents>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            start: EndCondition::Natural,
+            end: EndCondition::Natural,
+            canonical: true,
+            parameterization: Parameterization::Centripetal,
+            windings: None,
+            tangents: None,
+        }
+    }
+}
+
+impl Options {
+    #[must_use]
+    pub fn new() -> Options {
+        Options::default()
+    }
+
+    /// Behavior at the `start` and `end` (ignored for closed splines).
+    #[must_use]
+    pub fn end_conditions(mut self, start: EndCondition, end: EndCondition) -> Options {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// Don't negate quaternions (see [`canonicalize()`](super::canonicalize)).
+    ///
+    /// Neighboring quaternions must have a non-negative dot product,
+    /// since rotations longer than 180 degrees are not possible
+    /// (except with [`windings()`](Self::windings)).
+    #[must_use]
+    pub fn without_canonicalization(mut self) -> Options {
+        self.canonical = false;
+        self
+    }
+
+    /// E.g. to match the one of a companion position spline.
+    #[must_use]
+    pub fn parameterization(mut self, parameterization: Parameterization) -> Options {
+        self.parameterization = parameterization;
+        self
+    }
+
+    /// One [`Winding`] per segment (i.e. one less than quaternions, unless closed).
+    ///
+    /// Each segment can rotate by less than 540 degrees.
+    /// For more turns, intermediate quaternions have to be added.
+    #[must_use]
+    pub fn windings(mut self, windings: impl Into<Vec<Winding>>) -> Options {
+        self.windings = Some(windings.into());
+        self
+    }
+
+    /// One (possibly empty) override per quaternion.
+    ///
+    /// If not closed, the incoming tangent of the first quaternion
+    /// and the outgoing tangent of the last quaternion are ignored.
+    #[must_use]
+    pub fn tangents(mut self, tangents: impl Into<Vec<AngularTangents>>) -> Options {
+        self.tangents = Some(tangents.into());
+        self
+    }
 }
 
 impl CubicDeCasteljau {
@@ -60,8 +265,33 @@ impl CubicDeCasteljau {
         quaternions: impl Into<Vec<UnitQuaternion>>,
         tcb: &[[f32; 3]],
         closed: bool,
+    ) -> Result<CubicDeCasteljau, Error> {
+        Self::new_kochanek_bartels_with_options(quaternions, tcb, closed, Options::default())
+    }
+
+    /// Like [`new_centripetal_kochanek_bartels()`](Self::new_centripetal_kochanek_bartels),
+    /// but with the given [`Options`].
+    pub fn new_kochanek_bartels_with_options(
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        tcb: &[[f32; 3]],
+        closed: bool,
+        options: Options,
     ) -> Result<CubicDeCasteljau, Error> {
         use Error::*;
+        let Options {
+            start,
+            end,
+            canonical,
+            parameterization,
+            windings,
+            tangents,
+        } = options;
+        let windings = windings.as_deref();
+        let exponent = parameterization.exponent();
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(exponent >= 0.0) || exponent.is_infinite() {
+            return Err(InvalidExponent { exponent });
+        }
         let mut quaternions = quaternions.into();
         if quaternions.len() < 2 {
             return Err(LessThanTwoQuaternions);
@@ -73,98 +303,159 @@ impl CubicDeCasteljau {
                 closed,
             });
         }
+        if let Some(windings) = windings {
+            if windings.len() + !closed as usize != quaternions.len() {
+                return Err(WindingsVsQuaternions {
+                    windings: windings.len(),
+                    quaternions: quaternions.len(),
+                    closed,
+                });
+            }
+        }
+        if let Some(tangents) = &tangents {
+            if tangents.len() != quaternions.len() {
+                return Err(TangentsVsQuaternions {
+                    tangents: tangents.len(),
+                    quaternions: quaternions.len(),
+                });
+            }
+        }
         if closed {
             quaternions.push(quaternions[0]);
         }
 
-        canonicalize(&mut quaternions);
+        if canonical {
+            canonicalize(&mut quaternions);
+        } else if let Some(index) = quaternions.windows(2).position(|w| w[0].dot(&w[1]) < 0.0) {
+            return Err(LongRotation { index: index + 1 });
+        }
+
+        // Rotation vectors of all segments (in the world frame)
+
+        let mut rotations = Vec::with_capacity(quaternions.len() - 1);
+        for (i, w) in quaternions.windows(2).enumerate() {
+            let shortest = w[0].rotation_to(&w[1]).scaled_axis();
+            let winding = windings.map_or_else(Winding::default, |windings| windings[i]);
+            match winding.apply(shortest) {
+                Some(rotation) if rotation.norm() > 0.0 => rotations.push(rotation),
+                _ => return Err(RepeatedQuaternion { index: i + 1 }),
+            }
+        }
 
-        // Create grid with centripetal parameterization
+        // Create grid with the given (by default centripetal) parameterization
 
         let mut grid = Vec::with_capacity(quaternions.len() + 2 * closed as usize);
         grid.push(0.0);
-        for i in 0..quaternions.len() - 1 {
-            if let [q0, q1] = &quaternions[i..i + 2] {
-                let delta = q0.rotation_to(q1).angle().sqrt();
-                if delta == 0.0 {
-                    return Err(RepeatedQuaternion { index: i + 1 });
-                }
-                grid.push(*grid.last().unwrap() + delta);
-            } else {
-                unreachable!();
-            }
+        for rotation in &rotations {
+            let delta = math::powf(rotation.norm(), exponent);
+            grid.push(*grid.last().unwrap() + delta);
         }
 
+        let last = quaternions.len() - 1;
+        let wound = |i: usize| windings.map_or(false, |windings| windings[i] != Winding::default());
+        // Incoming and outgoing rotation vectors, see calculate_control_steps()
+        let mut incoming = vec![Vec3::zeros(); quaternions.len()];
+        let mut outgoing = vec![Vec3::zeros(); quaternions.len()];
         if closed {
-            if let (&[first, mut second, ..], &[.., mut penultimate, last]) =
-                (&quaternions[..], &quaternions[..])
-            {
-                if penultimate.dot(&first) < 0.0 {
-                    negate(&mut penultimate);
-                }
-                if last.dot(&second) < 0.0 {
-                    negate(&mut second);
-                }
-                quaternions.insert(0, penultimate);
-                quaternions.push(second);
-            } else {
-                unreachable!();
-            }
             if let (&[first, second, ..], &[.., penultimate, last]) = (&grid[..], &grid[..]) {
                 grid.insert(0, first - (last - penultimate));
                 grid.push(last + (second - first));
             } else {
                 unreachable!();
             }
-        }
-
-        let mut control_polygon = Vec::new();
-        assert!(quaternions.len() == grid.len());
-        for i in 0..quaternions.len() - 2 {
-            let (incoming, outgoing) = calculate_control_quaternions(
-                &quaternions[i..i + 3],
-                &grid[i..i + 3],
-                &tcb[i % tcb.len()],
-            );
-            control_polygon.push(incoming);
-            control_polygon.push(quaternions[i + 1]);
-            control_polygon.push(outgoing);
-        }
-        if closed {
-            let _ = control_polygon.remove(0);
-            let _ = control_polygon.pop();
+            for i in 0..=last {
+                // The grid has one additional element at the beginning
+                let r_in = rotations[(i + last - 1) % last];
+                let r_out = rotations[i % last];
+                let (a, b) =
+                    calculate_control_steps([r_in, r_out], &grid[i..i + 3], &tcb[i % tcb.len()]);
+                incoming[i] = a;
+                outgoing[i] = b;
+            }
             let _ = grid.remove(0);
             let _ = grid.pop();
-        } else if control_polygon.is_empty() {
-            // spherical linear interpolation between two quaternions
-            assert_eq!(grid.len(), 2);
+        } else if last == 1 {
+            // spherical linear interpolation between two quaternions (if natural)
             assert!(tcb.is_empty());
-            if let [q0, q1] = quaternions[..] {
-                let offset = (q1 * q0.inverse()).powf(1.0 / 3.0); // "cubic" spline, degree 3
-                control_polygon.push(q0);
-                control_polygon.push(offset * q0);
-                control_polygon.push(offset.inverse() * q1);
-                control_polygon.push(q1);
-            } else {
-                unreachable!();
-            }
+            let delta = grid[1] - grid[0];
+            let offset = rotations[0] / 3.0; // "cubic" spline, degree 3
+            let [q0, q1] = [quaternions[0], quaternions[1]];
+            outgoing[0] = match start {
+                EndCondition::Natural => offset,
+                _ => start.control_step(&q0, &q1, delta, None),
+            };
+            incoming[1] = match end {
+                EndCondition::Natural => offset,
+                _ => -end.control_step(&q1, &q0, -delta, None),
+            };
         } else {
-            if let ([first, ..], [third, ..]) = (&quaternions[..], &control_polygon[..]) {
-                let second = natural_control_quaternion(first, third);
-                control_polygon.insert(0, second);
-                control_polygon.insert(0, *first);
-            } else {
-                unreachable!();
+            for i in 1..last {
+                let (a, b) = calculate_control_steps(
+                    [rotations[i - 1], rotations[i]],
+                    &grid[i - 1..i + 2],
+                    &tcb[i - 1],
+                );
+                incoming[i] = a;
+                outgoing[i] = b;
             }
+            let third = UnitQuaternion::from_scaled_axis(-incoming[1]) * quaternions[1];
+            outgoing[0] = start.control_step(
+                &quaternions[0],
+                &third,
+                grid[1] - grid[0],
+                Some(rotations[0] - incoming[1]).filter(|_| wound(0)),
+            );
             // Now counting from the end ...
-            if let ([.., third], [.., first]) = (&control_polygon[..], &quaternions[..]) {
-                let second = natural_control_quaternion(first, third);
-                control_polygon.push(second);
-                control_polygon.push(*first);
-            } else {
-                unreachable!();
+            let third =
+                UnitQuaternion::from_scaled_axis(outgoing[last - 1]) * quaternions[last - 1];
+            incoming[last] = -end.control_step(
+                &quaternions[last],
+                &third,
+                grid[last - 1] - grid[last],
+                Some(outgoing[last - 1] - rotations[last - 1]).filter(|_| wound(last - 1)),
+            );
+        }
+
+        if let Some(tangents) = &tangents {
+            let degree = 3.0;
+            for (i, tangent) in tangents.iter().enumerate() {
+                if let Some(velocity) = tangent.outgoing {
+                    if i < last {
+                        outgoing[i] = velocity * (grid[i + 1] - grid[i]) / degree;
+                    }
+                }
+                if let Some(velocity) = tangent.incoming {
+                    // In closed splines, the first quaternion is also the last one
+                    let j = if i == 0 && closed { last } else { i };
+                    if j > 0 {
+                        incoming[j] = velocity * (grid[j] - grid[j - 1]) / degree;
+                    }
+                }
+            }
+        }
+
+        // Large TCB values, tangents or windings can lead to steps that
+        // cannot be represented by the control quaternions
+        for i in 0..last {
+            // This is only an approximation if the rotation axes are not parallel
+            let middle = rotations[i] - outgoing[i] - incoming[i + 1];
+            if [outgoing[i], middle, incoming[i + 1]]
+                .iter()
+                .any(|step| step.norm() >= std::f32::consts::PI)
+            {
+                return Err(TooLongRotation { index: i + 1 });
             }
         }
+
+        let mut control_polygon = Vec::with_capacity(3 * last + 1);
+        for i in 0..last {
+            let q = quaternions[i];
+            control_polygon.push(q);
+            control_polygon.push(UnitQuaternion::from_scaled_axis(outgoing[i]) * q);
+            let next = quaternions[i + 1];
+            control_polygon.push(UnitQuaternion::from_scaled_axis(-incoming[i + 1]) * next);
+        }
+        control_polygon.push(quaternions[last]);
         CubicDeCasteljau::new(control_polygon, grid).map_err(|e| {
             use super::cubicdecasteljau::Error as E;
             match e {
@@ -175,3 +466,276 @@ impl CubicDeCasteljau {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::angles2quat;
+    use crate::{Spline, SplineWithVelocity};
+
+    #[test]
+    fn end_conditions() {
+        let quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 45.0, 0.0),
+        ];
+        let tcb = [[0.0; 3]];
+        let rest = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &tcb,
+            false,
+            Options::new().end_conditions(EndCondition::Rest, EndCondition::Rest),
+        )
+        .unwrap();
+        let end = *rest.grid().last().unwrap();
+        assert!(rest.evaluate_velocity(0.0).norm() < 1e-6);
+        assert!(rest.segment_velocity(1, 1.0).norm() < 1e-6);
+        assert!(rest.evaluate(end).angle_to(&quaternions[2]) < 1e-5);
+        let velocity = Vec3::new(0.0, 0.0, 0.5);
+        let clamped = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &tcb,
+            false,
+            Options::new().end_conditions(
+                EndCondition::Clamped(velocity),
+                EndCondition::Clamped(velocity),
+            ),
+        )
+        .unwrap();
+        assert!((clamped.evaluate_velocity(0.0) - velocity).norm() < 1e-5);
+        assert!((clamped.segment_velocity(1, 1.0) - velocity).norm() < 1e-5);
+        let two = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            &quaternions[..2],
+            &[],
+            false,
+            Options::new().end_conditions(EndCondition::Natural, EndCondition::Rest),
+        )
+        .unwrap();
+        assert!(two.segment_velocity(0, 1.0).norm() < 1e-6);
+        assert!(two.evaluate_velocity(0.0).norm() > 0.1);
+    }
+
+    #[test]
+    fn without_canonicalization() {
+        let quaternions = [angles2quat(0.0, 0.0, 0.0), angles2quat(200.0, 0.0, 0.0)];
+        assert!(
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(quaternions, &[], false).is_ok()
+        );
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[],
+                false,
+                Options::new().without_canonicalization(),
+            ),
+            Err(Error::LongRotation { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn tangents() {
+        let quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 45.0, 0.0),
+        ];
+        let velocity = Vec3::new(0.1, 0.2, 0.3);
+        let tangents = [
+            AngularTangents::both(velocity),
+            AngularTangents {
+                incoming: None,
+                outgoing: Some(velocity),
+            },
+            AngularTangents::default(),
+        ];
+        let plain =
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(quaternions, &[[0.0; 3]], false)
+                .unwrap();
+        let s = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &[[0.0; 3]],
+            false,
+            Options::new().tangents(tangents),
+        )
+        .unwrap();
+        assert_eq!(s.grid(), plain.grid());
+        assert!((s.evaluate_velocity(0.0) - velocity).norm() < 1e-5);
+        assert!((s.evaluate_velocity(s.grid()[1]) - velocity).norm() < 1e-5);
+        assert!((s.segment_velocity(0, 1.0) - plain.segment_velocity(0, 1.0)).norm() < 1e-5);
+        let closed = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &[[0.0; 3]; 3],
+            true,
+            Options::new().tangents(tangents),
+        )
+        .unwrap();
+        assert!((closed.segment_velocity(2, 1.0) - velocity).norm() < 1e-5);
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().tangents(&tangents[..2])
+            ),
+            Err(Error::TangentsVsQuaternions { .. })
+        ));
+    }
+
+    #[test]
+    fn parameterization() {
+        let quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 10.0, 0.0),
+        ];
+        let grid = |p| {
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().parameterization(p),
+            )
+            .unwrap()
+            .grid()
+            .to_vec()
+        };
+        let (quarter, tenth) = (90.0f32.to_radians(), 10.0f32.to_radians());
+        assert_eq!(grid(Parameterization::Uniform), [0.0, 1.0, 2.0]);
+        assert_eq!(
+            grid(Parameterization::Centripetal),
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(quaternions, &[[0.0; 3]], false)
+                .unwrap()
+                .grid()
+        );
+        let chordal = grid(Parameterization::Chordal);
+        assert!((chordal[1] - quarter).abs() < 1e-5);
+        assert!((chordal[2] - quarter - tenth).abs() < 1e-5);
+        let custom = grid(Parameterization::Exponent(0.25));
+        assert!((custom[1] - crate::math::powf(quarter, 0.25)).abs() < 1e-5);
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().parameterization(Parameterization::Exponent(-1.0))
+            ),
+            Err(Error::InvalidExponent { .. })
+        ));
+    }
+
+    #[test]
+    fn windings() {
+        use crate::quaternion::TotalRotation;
+        let quaternions = [angles2quat(0.0, 0.0, 0.0), angles2quat(-90.0, 0.0, 0.0)];
+        let total = |s: &CubicDeCasteljau| s.total_rotation(0.0, *s.grid().last().unwrap());
+        let short =
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(quaternions, &[], false).unwrap();
+        assert!((total(&short) - 90.0).abs() < 1e-2);
+        let long_way = Winding {
+            long_way: true,
+            extra_turns: 0,
+        };
+        let long = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &[],
+            false,
+            Options::new().windings([long_way]),
+        )
+        .unwrap();
+        assert!((total(&long) - 270.0).abs() < 1e-2);
+        assert!(long.evaluate_velocity(0.0).z > 0.0);
+        assert!(long.evaluate(long.grid()[1]).angle_to(&quaternions[1]) < 1e-5);
+        let extra_turn = Winding {
+            long_way: false,
+            extra_turns: 1,
+        };
+        let quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(30.0, 0.0, 0.0),
+            angles2quat(30.0, 45.0, 0.0),
+        ];
+        let s = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &[[0.0; 3]],
+            false,
+            Options::new().windings([extra_turn, Winding::default()]),
+        )
+        .unwrap();
+        let first = s.total_rotation(0.0, s.grid()[1]);
+        // Not exactly 390 degrees, because the rotation axis changes
+        assert!((390.0..400.0).contains(&first));
+        assert!(s.evaluate(s.grid()[1]).angle_to(&quaternions[1]) < 1e-5);
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().windings([extra_turn])
+            ),
+            Err(Error::WindingsVsQuaternions { windings: 1, .. })
+        ));
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().windings([
+                    Winding {
+                        long_way: false,
+                        extra_turns: 2,
+                    },
+                    Winding::default()
+                ])
+            ),
+            Err(Error::TooLongRotation { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn combined_options() {
+        let quaternions = [angles2quat(0.0, 0.0, 0.0), angles2quat(-90.0, 0.0, 0.0)];
+        let long_way = Winding {
+            long_way: true,
+            extra_turns: 0,
+        };
+        let s = CubicDeCasteljau::new_kochanek_bartels_with_options(
+            quaternions,
+            &[],
+            false,
+            Options::new()
+                .windings([long_way])
+                .parameterization(Parameterization::Chordal)
+                .end_conditions(EndCondition::Rest, EndCondition::Natural),
+        )
+        .unwrap();
+        assert!((s.grid()[1] - 270.0f32.to_radians()).abs() < 1e-5);
+        assert!(s.evaluate_velocity(0.0).norm() < 1e-6);
+        assert!(s.segment_velocity(0, 1.0).z > 0.0);
+    }
+
+    #[test]
+    fn too_long_rotation() {
+        let quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 45.0, 0.0),
+        ];
+        // No windings involved
+        let tangents = [
+            AngularTangents::default(),
+            AngularTangents::both(Vec3::new(0.0, 0.0, 10.0)),
+            AngularTangents::default(),
+        ];
+        assert!(matches!(
+            CubicDeCasteljau::new_kochanek_bartels_with_options(
+                quaternions,
+                &[[0.0; 3]],
+                false,
+                Options::new().tangents(tangents),
+            ),
+            Err(Error::TooLongRotation { index: 1 })
+        ));
+    }
+}
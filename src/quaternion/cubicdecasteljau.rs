@@ -1,4 +1,8 @@
-use crate::utilities::{check_grid, GridError};
+use std::mem::size_of_val;
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::memory::MemoryUsage;
+use crate::utilities::{check_grid, golden_section_maximum, GridError};
 use crate::{Spline, SplineWithVelocity};
 
 use super::{UnitQuaternion, Vec3};
@@ -16,8 +20,17 @@ pub enum Error {
     FromGridError(#[from] GridError),
 }
 
+/// Spherical cubic Bezier curves, evaluated with the cumulative form of
+/// Kim, Kim and Shin (1995), <https://doi.org/10.1145/218380.218486>.
+///
+/// This is not exactly the same curve as the one obtained by
+/// De Casteljau's algorithm (see [`evaluate_bezier()`]),
+/// but it has the same values and angular velocities at the grid values
+/// and it is cheaper to evaluate.
 pub struct CubicDeCasteljau {
     control_polygon: Box<[UnitQuaternion]>,
+    /// Rotation vectors between consecutive control quaternions (in the local frame).
+    logarithms: Box<[[Vec3; 3]]>,
     grid: Box<[f32]>,
 }
 
@@ -39,40 +52,209 @@ impl CubicDeCasteljau {
             });
         }
         check_grid(&grid)?;
+        let logarithms = control_polygon
+            .windows(4)
+            .step_by(3)
+            .map(|p| {
+                let log = |a: &UnitQuaternion, b: UnitQuaternion| (a.inverse() * b).scaled_axis();
+                [log(&p[0], p[1]), log(&p[1], p[2]), log(&p[2], p[3])]
+            })
+            .collect();
         Ok(CubicDeCasteljau {
             control_polygon,
+            logarithms,
             grid,
         })
     }
 
-    /// Applies two levels of Slerp until only two quaternions are left
-    #[allow(clippy::many_single_char_names)]
-    fn partial_de_casteljau(&self, t: f32) -> (UnitQuaternion, UnitQuaternion, f32, f32) {
+    pub(crate) fn into_parts(self) -> (Box<[UnitQuaternion]>, Box<[f32]>) {
+        (self.control_polygon, self.grid)
+    }
+
+    fn find_segment(&self, t: f32) -> (usize, f32) {
         let (t, idx) = self.clamp_parameter_and_find_index(t);
         let t0 = self.grid[idx];
         let t1 = self.grid[idx + 1];
-        let delta_t = t1 - t0;
-        let t = (t - t0) / delta_t;
+        (idx, (t - t0) / (t1 - t0))
+    }
 
-        let a = &self.control_polygon[idx * 3];
-        let b = &self.control_polygon[idx * 3 + 1];
-        let c = &self.control_polygon[idx * 3 + 2];
-        let d = &self.control_polygon[idx * 3 + 3];
+    /// The control polygon, starting with the first grid value.
+    ///
+    /// Each segment has four control quaternions,
+    /// neighboring segments share their first/last control quaternion.
+    #[must_use]
+    pub fn control_polygon(&self) -> &[UnitQuaternion] {
+        &self.control_polygon
+    }
 
-        // NB: slerp() panics if angle is 180 degrees!
+    #[must_use]
+    pub fn segment_count(&self) -> usize {
+        self.grid.len() - 1
+    }
 
-        let ab = a.slerp(b, t);
-        let bc = b.slerp(c, t);
-        let cd = c.slerp(d, t);
+    /// The four control quaternions of segment `idx`, see [`evaluate_bezier()`].
+    #[must_use]
+    pub fn segment(&self, idx: usize) -> [UnitQuaternion; 4] {
+        let p = &self.control_polygon[idx * 3..idx * 3 + 4];
+        [p[0], p[1], p[2], p[3]]
+    }
 
-        (ab.slerp(&bc, t), bc.slerp(&cd, t), t, delta_t)
+    /// Rotation within segment `idx`, `t` is normalized (from 0 to 1).
+    #[must_use]
+    pub fn segment_evaluate(&self, idx: usize, t: f32) -> UnitQuaternion {
+        let [w1, w2, w3] = &self.logarithms[idx];
+        let [b1, b2, b3] = cumulative_basis(t);
+        self.control_polygon[idx * 3]
+            * UnitQuaternion::from_scaled_axis(w1 * b1)
+            * UnitQuaternion::from_scaled_axis(w2 * b2)
+            * UnitQuaternion::from_scaled_axis(w3 * b3)
     }
+
+    /// Angular velocity within segment `idx`, `t` is normalized (from 0 to 1).
+    ///
+    /// In contrast to [`evaluate_velocity()`](SplineWithVelocity::evaluate_velocity),
+    /// this allows to obtain the velocity at the very end of a segment.
+    #[must_use]
+    pub fn segment_velocity(&self, idx: usize, t: f32) -> Vec3 {
+        let [w1, w2, w3] = &self.logarithms[idx];
+        let [b1, b2, b3] = cumulative_basis(t);
+        let [d1, d2, d3] = cumulative_basis_derivative(t);
+        let delta_t = self.grid[idx + 1] - self.grid[idx];
+        // Each rotation vector is turned into the global frame
+        let r1 = self.control_polygon[idx * 3] * UnitQuaternion::from_scaled_axis(w1 * b1);
+        let r2 = r1 * UnitQuaternion::from_scaled_axis(w2 * b2);
+        let r3 = r2 * UnitQuaternion::from_scaled_axis(w3 * b3);
+        (r1 * w1 * d1 + r2 * w2 * d2 + r3 * w3 * d3) / delta_t
+    }
+
+    /// Local maxima of the angular speed, in ascending order of time.
+    ///
+    /// Each segment is sampled and the maxima are refined with a golden-section search.
+    /// Maxima at grid values (where the speed may jump) are not refined.
+    /// The first and last grid value are included if the speed decreases from there.
+    #[must_use]
+    pub fn angular_speed_maxima(&self) -> Vec<SpeedMaximum> {
+        const SAMPLES: usize = 32;
+        let speed = |idx: usize, u: f32| self.segment_velocity(idx, u).norm();
+        let time = |idx: usize, u: f32| self.grid[idx] + u * (self.grid[idx + 1] - self.grid[idx]);
+        // (segment index, normalized time, speed), with the larger speed at grid values
+        let mut samples = vec![(0, 0.0, speed(0, 0.0))];
+        for idx in 0..self.segment_count() {
+            let (_, _, previous) = samples.last_mut().unwrap();
+            *previous = previous.max(speed(idx, 0.0));
+            for k in 1..=SAMPLES {
+                let u = k as f32 / SAMPLES as f32;
+                samples.push((idx, u, speed(idx, u)));
+            }
+        }
+        let last = samples.len() - 1;
+        (0..=last)
+            .filter(|&i| {
+                let current = samples[i].2;
+                (i == 0 || samples[i - 1].2 < current) && (i == last || current >= samples[i + 1].2)
+            })
+            .map(|i| {
+                let (idx, u, value) = samples[i];
+                if i % SAMPLES == 0 {
+                    // At a grid value
+                    let idx = i / SAMPLES;
+                    return SpeedMaximum {
+                        time: self.grid[idx],
+                        speed: value,
+                    };
+                }
+                let step = 1.0 / SAMPLES as f32;
+                let u = golden_section_maximum(|u| speed(idx, u), u - step, u + step, 1e-5);
+                SpeedMaximum {
+                    time: time(idx, u),
+                    speed: speed(idx, u),
+                }
+            })
+            .collect()
+    }
+
+    /// Time derivative of the angular velocity.
+    ///
+    /// This is approximated by finite differences within the segment,
+    /// at grid values the acceleration of the following segment is returned.
+    #[must_use]
+    pub fn evaluate_angular_acceleration(&self, t: f32) -> Vec3 {
+        // Step size (normalized) balancing truncation and rounding errors
+        const STEP: f32 = 5e-3;
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let delta_t = self.grid[idx + 1] - t0;
+        let t = (t - t0) / delta_t;
+        // Derivative of a quadratic through three equidistant samples,
+        // which becomes a central difference away from the segment ends
+        let start = (t - STEP).clamp(0.0, 1.0 - 2.0 * STEP);
+        let v0 = self.segment_velocity(idx, start);
+        let v1 = self.segment_velocity(idx, start + STEP);
+        let v2 = self.segment_velocity(idx, start + 2.0 * STEP);
+        let u = (t - start) / STEP;
+        ((v1 - v0) + (v2 - v1 * 2.0 + v0) * (u - 0.5)) / (STEP * delta_t)
+    }
+}
+
+/// Local maximum of the angular speed, see [`CubicDeCasteljau::angular_speed_maxima()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedMaximum {
+    pub time: f32,
+    /// Angular speed in radians per time unit
+    pub speed: f32,
+}
+
+/// Cumulative Bernstein polynomials of degree 3 (without the constant first one).
+fn cumulative_basis(t: f32) -> [f32; 3] {
+    let u = 1.0 - t;
+    [1.0 - u * u * u, t * t * (3.0 - 2.0 * t), t * t * t]
+}
+
+fn cumulative_basis_derivative(t: f32) -> [f32; 3] {
+    let u = 1.0 - t;
+    [3.0 * u * u, 6.0 * t * u, 3.0 * t * t]
+}
+
+/// Applies two levels of Slerp until only two quaternions are left
+#[allow(clippy::many_single_char_names)]
+fn partial_de_casteljau(
+    control_points: &[UnitQuaternion; 4],
+    t: f32,
+) -> (UnitQuaternion, UnitQuaternion) {
+    let [a, b, c, d] = control_points;
+
+    // NB: slerp() panics if angle is 180 degrees!
+
+    let ab = a.slerp(b, t);
+    let bc = b.slerp(c, t);
+    let cd = c.slerp(d, t);
+
+    (ab.slerp(&bc, t), bc.slerp(&cd, t))
+}
+
+/// Evaluates a single spherical cubic Bezier segment
+/// with De Casteljau's algorithm, `t` is from 0 to 1.
+///
+/// This uses six Slerps and is therefore slower than
+/// [`CubicDeCasteljau::segment_evaluate()`],
+/// which evaluates a slightly different curve.
+#[must_use]
+pub fn evaluate_bezier(control_points: &[UnitQuaternion; 4], t: f32) -> UnitQuaternion {
+    let (one, two) = partial_de_casteljau(control_points, t);
+    one.slerp(&two, t)
+}
+
+/// Angular velocity of [`evaluate_bezier()`] (assuming a segment duration of 1).
+#[must_use]
+pub fn bezier_velocity(control_points: &[UnitQuaternion; 4], t: f32) -> Vec3 {
+    let (one, two) = partial_de_casteljau(control_points, t);
+    one.rotation_to(&two).scaled_axis() * 3.0 // cubic
 }
 
 impl Spline<UnitQuaternion> for CubicDeCasteljau {
     fn evaluate(&self, t: f32) -> UnitQuaternion {
-        let (one, two, t, _) = self.partial_de_casteljau(t);
-        one.slerp(&two, t)
+        let (idx, t) = self.find_segment(t);
+        self.segment_evaluate(idx, t)
     }
 
     fn grid(&self) -> &[f32] {
@@ -82,8 +264,223 @@ impl Spline<UnitQuaternion> for CubicDeCasteljau {
 
 impl SplineWithVelocity<UnitQuaternion, Vec3> for CubicDeCasteljau {
     fn evaluate_velocity(&self, t: f32) -> Vec3 {
-        let (one, two, _, delta_t) = self.partial_de_casteljau(t);
-        const DEGREE: f32 = 3.0; // cubic
-        one.rotation_to(&two).scaled_axis() * DEGREE / delta_t
+        let (idx, t) = self.find_segment(t);
+        self.segment_velocity(idx, t)
+    }
+}
+
+impl MemoryUsage for CubicDeCasteljau {
+    fn memory_usage(&self) -> usize {
+        size_of_val(&*self.control_polygon)
+            + size_of_val(&*self.logarithms)
+            + size_of_val(&*self.grid)
+    }
+}
+
+impl Fingerprint for CubicDeCasteljau {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.control_polygon.write_fingerprint(hasher);
+        self.grid.write_fingerprint(hasher);
+    }
+}
+
+/// Control quaternions are stored as `[x, y, z, w]`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Representation {
+    control_polygon: Vec<[f32; 4]>,
+    grid: Vec<f32>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CubicDeCasteljau {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Representation {
+            control_polygon: self
+                .control_polygon
+                .iter()
+                .map(|q| q.coords.into())
+                .collect(),
+            grid: self.grid.to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CubicDeCasteljau {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use nalgebra::Quaternion;
+        use serde::de::Error as _;
+        let r = Representation::deserialize(deserializer)?;
+        let control_polygon: Vec<_> = r
+            .control_polygon
+            .into_iter()
+            .map(|[x, y, z, w]| {
+                let q = Quaternion::new(w, x, y, z);
+                // Keep normalized values as they are, to allow lossless round trips
+                if (q.norm() - 1.0).abs() < 1e-6 {
+                    UnitQuaternion::new_unchecked(q)
+                } else {
+                    UnitQuaternion::new_normalize(q)
+                }
+            })
+            .collect();
+        CubicDeCasteljau::new(control_polygon, r.grid).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::angles2quat;
+
+    #[test]
+    fn segments() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(10.0, 0.0, 0.0),
+            angles2quat(20.0, 5.0, 0.0),
+            angles2quat(30.0, 5.0, 0.0),
+            angles2quat(40.0, 0.0, 0.0),
+            angles2quat(50.0, 0.0, 10.0),
+            angles2quat(60.0, 0.0, 10.0),
+        ];
+        let curve = CubicDeCasteljau::new(q, [0.0, 1.0, 3.0]).unwrap();
+        assert_eq!(curve.control_polygon().len(), 7);
+        assert_eq!(curve.segment_count(), 2);
+        let segment = curve.segment(1);
+        assert_eq!(segment, [q[3], q[4], q[5], q[6]]);
+        assert!(
+            curve
+                .evaluate(1.5)
+                .angle_to(&curve.segment_evaluate(1, 0.25))
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[test]
+    fn reference() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(20.0, 10.0, 0.0),
+            angles2quat(60.0, -20.0, 30.0),
+            angles2quat(90.0, 0.0, 45.0),
+        ];
+        let curve = CubicDeCasteljau::new(q, [0.0, 1.0]).unwrap();
+        // Same values and velocities at the ends
+        for t in [0.0, 1.0] {
+            assert!(
+                curve
+                    .segment_evaluate(0, t)
+                    .angle_to(&evaluate_bezier(&q, t))
+                    < 1e-5
+            );
+            let v = curve.segment_velocity(0, t);
+            assert!((v - bezier_velocity(&q, t)).norm() < 1e-4);
+        }
+        // Slightly different in between
+        for i in 1..10 {
+            let t = i as f32 / 10.0;
+            assert!(
+                curve
+                    .segment_evaluate(0, t)
+                    .angle_to(&evaluate_bezier(&q, t))
+                    < 0.02
+            );
+        }
+        // Velocity matches the numerical derivative
+        let h = 1e-3;
+        let numerical = curve
+            .segment_evaluate(0, 0.5 - h)
+            .rotation_to(&curve.segment_evaluate(0, 0.5 + h))
+            .scaled_axis()
+            / (2.0 * h);
+        assert!((curve.segment_velocity(0, 0.5) - numerical).norm() < 1e-2);
+    }
+
+    #[test]
+    fn angular_acceleration() {
+        // Around the z axis only, the angle is a cubic Bezier curve
+        let curve = CubicDeCasteljau::new(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(90.0, 0.0, 0.0),
+                angles2quat(90.0, 0.0, 0.0),
+            ],
+            [0.0, 2.0],
+        )
+        .unwrap();
+        let angle = 90.0f32.to_radians();
+        // angle * (3 x^2 - 2 x^3) with x = t / 2
+        let expected = |t: f32| angle * (6.0 - 6.0 * t) / 4.0;
+        for t in [0.0, 0.5, 1.2, 2.0] {
+            let acceleration = curve.evaluate_angular_acceleration(t);
+            assert!((acceleration - Vec3::new(0.0, 0.0, expected(t))).norm() < 1e-2);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let curve = CubicDeCasteljau::new_centripetal_kochanek_bartels(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(90.0, 10.0, 0.0),
+                angles2quat(90.0, 60.0, 20.0),
+            ],
+            &[[0.0; 3]],
+            false,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&curve).unwrap();
+        let restored: CubicDeCasteljau = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.grid(), curve.grid());
+        assert_eq!(restored.control_polygon(), curve.control_polygon());
+        assert!(serde_json::from_str::<CubicDeCasteljau>(
+            r#"{"control_polygon": [[0, 0, 0, 1]], "grid": [0, 1]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn speed_maxima() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 60.0, 0.0),
+            angles2quat(100.0, 60.0, 0.0),
+        ];
+        let curve =
+            CubicDeCasteljau::new_centripetal_kochanek_bartels(q, &[[0.0; 3]; 2], false).unwrap();
+        let maxima = curve.angular_speed_maxima();
+        assert!(!maxima.is_empty());
+        let end = *curve.grid().last().unwrap();
+        for m in &maxima {
+            let speed = |t: f32| curve.evaluate_velocity(t).norm();
+            assert!(
+                (speed(m.time.min(end)) - m.speed).abs() < 1e-3 * m.speed.max(1.0)
+                    || curve.grid().contains(&m.time)
+            );
+            for t in [m.time - 1e-2, m.time + 1e-2] {
+                if t > 0.0 && t < end {
+                    assert!(speed(t) <= m.speed + 1e-4);
+                }
+            }
+        }
+        // Zero velocity at both ends: one maximum in the middle
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+        ];
+        let curve = CubicDeCasteljau::new(q, [0.0, 2.0]).unwrap();
+        let maxima = curve.angular_speed_maxima();
+        assert_eq!(maxima.len(), 1);
+        assert!((maxima[0].time - 1.0).abs() < 1e-3);
     }
 }
@@ -0,0 +1,221 @@
+//! Spherical Bezier curves of arbitrary degree.
+//!
+//! For the cubic case, [`CubicDeCasteljau`] is faster.
+//! Higher degrees allow constructing orientation splines with
+//! continuous angular acceleration (e.g. quintic curves).
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::utilities::{check_grid, GridError};
+use crate::{Spline, SplineWithVelocity};
+
+use super::{CubicDeCasteljau, UnitQuaternion, Vec3};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("degree must be at least 1")]
+    DegreeZero,
+    #[error("there must be at least two grid elements")]
+    GridTooShort,
+    #[error("there must be a control quaternion for each grid point, \
+        plus {} between each pair of grid points \
+        ({grid} + {} * ({grid} - 1) = {} != {control_polygon})",
+        .degree - 1, .degree - 1, .grid + (.degree - 1) * (.grid - 1))]
+    GridVsControlPolygon {
+        grid: usize,
+        degree: usize,
+        control_polygon: usize,
+    },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+pub struct DeCasteljau {
+    degree: usize,
+    control_polygon: Box<[UnitQuaternion]>,
+    grid: Box<[f32]>,
+}
+
+impl DeCasteljau {
+    pub fn new(
+        degree: usize,
+        control_polygon: impl Into<Box<[UnitQuaternion]>>,
+        grid: impl Into<Box<[f32]>>,
+    ) -> Result<DeCasteljau, Error> {
+        use Error::*;
+        let control_polygon = control_polygon.into();
+        let grid = grid.into();
+        if degree == 0 {
+            return Err(DegreeZero);
+        }
+        if grid.len() < 2 {
+            return Err(GridTooShort);
+        }
+        if grid.len() + (degree - 1) * (grid.len() - 1) != control_polygon.len() {
+            return Err(GridVsControlPolygon {
+                grid: grid.len(),
+                degree,
+                control_polygon: control_polygon.len(),
+            });
+        }
+        check_grid(&grid)?;
+        Ok(DeCasteljau {
+            degree,
+            control_polygon,
+            grid,
+        })
+    }
+
+    #[must_use]
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// See [`CubicDeCasteljau::control_polygon()`].
+    #[must_use]
+    pub fn control_polygon(&self) -> &[UnitQuaternion] {
+        &self.control_polygon
+    }
+
+    #[must_use]
+    pub fn segment_count(&self) -> usize {
+        self.grid.len() - 1
+    }
+
+    /// The `degree + 1` control quaternions of segment `idx`.
+    #[must_use]
+    pub fn segment(&self, idx: usize) -> &[UnitQuaternion] {
+        &self.control_polygon[idx * self.degree..=(idx + 1) * self.degree]
+    }
+
+    /// Applies Slerp until only two quaternions are left
+    fn partial_de_casteljau(&self, idx: usize, t: f32) -> (UnitQuaternion, UnitQuaternion) {
+        let mut points = self.segment(idx).to_vec();
+        while points.len() > 2 {
+            for i in 0..points.len() - 1 {
+                // NB: slerp() panics if angle is 180 degrees!
+                points[i] = points[i].slerp(&points[i + 1], t);
+            }
+            points.pop();
+        }
+        (points[0], points[1])
+    }
+
+    /// Rotation within segment `idx`, `t` is normalized (from 0 to 1).
+    #[must_use]
+    pub fn segment_evaluate(&self, idx: usize, t: f32) -> UnitQuaternion {
+        let (one, two) = self.partial_de_casteljau(idx, t);
+        one.slerp(&two, t)
+    }
+
+    /// Angular velocity within segment `idx`, `t` is normalized (from 0 to 1).
+    #[must_use]
+    pub fn segment_velocity(&self, idx: usize, t: f32) -> Vec3 {
+        let (one, two) = self.partial_de_casteljau(idx, t);
+        let delta_t = self.grid[idx + 1] - self.grid[idx];
+        one.rotation_to(&two).scaled_axis() * self.degree as f32 / delta_t
+    }
+
+    fn find_segment(&self, t: f32) -> (usize, f32) {
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let t1 = self.grid[idx + 1];
+        (idx, (t - t0) / (t1 - t0))
+    }
+}
+
+impl From<CubicDeCasteljau> for DeCasteljau {
+    fn from(curve: CubicDeCasteljau) -> DeCasteljau {
+        let (control_polygon, grid) = curve.into_parts();
+        DeCasteljau {
+            degree: 3,
+            control_polygon,
+            grid,
+        }
+    }
+}
+
+impl Spline<UnitQuaternion> for DeCasteljau {
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        let (idx, t) = self.find_segment(t);
+        self.segment_evaluate(idx, t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+impl SplineWithVelocity<UnitQuaternion, Vec3> for DeCasteljau {
+    fn evaluate_velocity(&self, t: f32) -> Vec3 {
+        let (idx, t) = self.find_segment(t);
+        self.segment_velocity(idx, t)
+    }
+}
+
+impl Fingerprint for DeCasteljau {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        hasher.write_u64(self.degree as u64);
+        self.control_polygon.write_fingerprint(hasher);
+        self.grid.write_fingerprint(hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::cubicdecasteljau::evaluate_bezier;
+    use crate::quaternion::{angles2quat, PiecewiseSlerp};
+
+    #[test]
+    fn degrees() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(20.0, 10.0, 0.0),
+            angles2quat(60.0, -20.0, 30.0),
+            angles2quat(90.0, 0.0, 45.0),
+        ];
+        let linear = DeCasteljau::new(1, q, [0.0, 1.0, 2.0, 4.0]).unwrap();
+        let slerp = PiecewiseSlerp::new(q, [0.0, 1.0, 2.0, 4.0], false).unwrap();
+        assert!(linear.evaluate(2.5).angle_to(&slerp.evaluate(2.5)) < 1e-6);
+        assert!((linear.evaluate_velocity(2.5) - slerp.evaluate_velocity(2.5)).norm() < 1e-5);
+        let cubic = DeCasteljau::new(3, q, [0.0, 2.0]).unwrap();
+        assert!(cubic.evaluate(0.5).angle_to(&evaluate_bezier(&q, 0.25)) < 1e-6);
+        assert!(matches!(
+            DeCasteljau::new(2, q, [0.0, 1.0]),
+            Err(Error::GridVsControlPolygon { degree: 2, .. })
+        ));
+        assert!(matches!(
+            DeCasteljau::new(0, q, [0.0, 1.0]),
+            Err(Error::DegreeZero)
+        ));
+    }
+
+    #[test]
+    fn quintic() {
+        let q: Vec<_> = (0..=5)
+            .map(|i| angles2quat(i as f32 * 10.0, i as f32 * 3.0, 0.0))
+            .collect();
+        let curve = DeCasteljau::new(5, q.clone(), [0.0, 2.0]).unwrap();
+        assert_eq!(curve.segment(0).len(), 6);
+        assert!(curve.evaluate(2.0).angle_to(&q[5]) < 1e-6);
+        // Initial velocity is scaled by the degree
+        let expected = q[0].rotation_to(&q[1]).scaled_axis() * 5.0 / 2.0;
+        assert!((curve.evaluate_velocity(0.0) - expected).norm() < 1e-5);
+    }
+
+    #[test]
+    fn continuity() {
+        use crate::continuity::Continuity;
+        // Uniform rotation around a single axis, split into two segments
+        let q: Vec<_> = (0..=10)
+            .map(|i| angles2quat(i as f32 * 10.0, 0.0, 0.0))
+            .collect();
+        let smooth = DeCasteljau::new(5, q.clone(), [0.0, 1.0, 2.0]).unwrap();
+        assert_eq!(smooth.continuity(1e-2)[0].continuity, Continuity::C2);
+        let mut kinked = q;
+        kinked[7] = angles2quat(75.0, 0.0, 0.0);
+        let kinked = DeCasteljau::new(5, kinked, [0.0, 1.0, 2.0]).unwrap();
+        assert_eq!(kinked.continuity(1e-2)[0].continuity, Continuity::C1);
+    }
+}
@@ -0,0 +1,196 @@
+//! Rotation splines interpolated in the logarithm space (i.e. rotation vectors).
+//!
+//! This is much cheaper to evaluate than [`CubicDeCasteljau`](super::CubicDeCasteljau),
+//! but the curve depends on the choice of the coordinate system
+//! and it is only well-behaved for moderate rotation angles
+//! (i.e. far from 180 degrees away from the identity).
+
+use nalgebra::Matrix3;
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::math;
+use crate::memory::MemoryUsage;
+use crate::{PiecewiseCubicCurve, Spline, SplineWithVelocity};
+
+use super::{UnitQuaternion, Vec3};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least two quaternions")]
+    LessThanTwoQuaternions,
+    #[error("number of quaternions ({quaternions}) must be {} TCB values ({tcb})", if *.closed {
+        "the same as"
+    } else {
+        "two more than"
+    })]
+    TcbVsQuaternions {
+        tcb: usize,
+        quaternions: usize,
+        closed: bool,
+    },
+    #[error("index {index}: rotation to next quaternion is too small")]
+    RotationTooSmall { index: usize },
+}
+
+pub struct LogQuaternionSpline {
+    curve: PiecewiseCubicCurve<Vec3>,
+}
+
+impl LogQuaternionSpline {
+    /// The curve values are rotation vectors, see [`UnitQuaternion::scaled_axis()`].
+    #[must_use]
+    pub fn from_curve(curve: PiecewiseCubicCurve<Vec3>) -> LogQuaternionSpline {
+        LogQuaternionSpline { curve }
+    }
+
+    /// Repeated consecutive quaternions lead to a "hold" segment,
+    /// see [`PiecewiseCubicCurve::new_centripetal_kochanek_bartels()`].
+    pub fn new_centripetal_kochanek_bartels(
+        quaternions: impl AsRef<[UnitQuaternion]>,
+        tcb: &[[f32; 3]],
+        closed: bool,
+    ) -> Result<LogQuaternionSpline, Error> {
+        let logarithms = unwrapped_logarithms(quaternions.as_ref());
+        PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&logarithms, tcb, closed, |v| {
+            v.norm()
+        })
+        .map(LogQuaternionSpline::from_curve)
+        .map_err(|e| {
+            use crate::centripetalkochanekbartelsspline::Error as E;
+            match e {
+                E::LessThanTwoPositions => Error::LessThanTwoQuaternions,
+                E::TcbVsPositions {
+                    tcb,
+                    positions,
+                    closed,
+                } => Error::TcbVsQuaternions {
+                    tcb,
+                    quaternions: positions,
+                    closed,
+                },
+                // Rotation vectors are always finite
+                E::NonFiniteDistance { .. } => unreachable!(),
+                E::DistanceTooSmall { index } => Error::RotationTooSmall { index },
+            }
+        })
+    }
+
+    #[must_use]
+    pub fn curve(&self) -> &PiecewiseCubicCurve<Vec3> {
+        &self.curve
+    }
+}
+
+/// Rotation vectors, each one as close as possible to its predecessor.
+///
+/// Rotating by the angle `a` and by `a - 360` degrees around the same axis is equivalent.
+fn unwrapped_logarithms(quaternions: &[UnitQuaternion]) -> Vec<Vec3> {
+    let mut result: Vec<Vec3> = Vec::with_capacity(quaternions.len());
+    for q in quaternions {
+        let log = q.scaled_axis();
+        let log = match (result.last(), q.axis()) {
+            (Some(previous), Some(axis)) => {
+                let alternative = log - axis.into_inner() * std::f32::consts::TAU;
+                if (alternative - previous).norm() < (log - previous).norm() {
+                    alternative
+                } else {
+                    log
+                }
+            }
+            _ => log,
+        };
+        result.push(log);
+    }
+    result
+}
+
+/// Maps the time derivative of a rotation vector to the (global) angular velocity.
+fn left_jacobian(v: &Vec3) -> Matrix3<f32> {
+    let angle = v.norm();
+    let cross = v.cross_matrix();
+    if angle < 1e-4 {
+        // Taylor expansion
+        return Matrix3::identity() + cross * 0.5;
+    }
+    let a = (1.0 - math::cos(angle)) / (angle * angle);
+    let b = (angle - math::sin(angle)) / (angle * angle * angle);
+    Matrix3::identity() + cross * a + cross * cross * b
+}
+
+impl Spline<UnitQuaternion> for LogQuaternionSpline {
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        UnitQuaternion::from_scaled_axis(self.curve.evaluate(t))
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.curve.grid()
+    }
+}
+
+impl SplineWithVelocity<UnitQuaternion, Vec3> for LogQuaternionSpline {
+    fn evaluate_velocity(&self, t: f32) -> Vec3 {
+        left_jacobian(&self.curve.evaluate(t)) * self.curve.evaluate_velocity(t)
+    }
+}
+
+impl Fingerprint for LogQuaternionSpline {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.curve.write_fingerprint(hasher);
+    }
+}
+
+impl MemoryUsage for LogQuaternionSpline {
+    fn memory_usage(&self) -> usize {
+        self.curve.memory_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::angles2quat;
+
+    #[test]
+    fn keyframes() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 20.0, 0.0),
+            angles2quat(170.0, 0.0, 10.0),
+            angles2quat(-160.0, 0.0, 0.0),
+        ];
+        let s = LogQuaternionSpline::new_centripetal_kochanek_bartels(q, &[[0.0; 3]; 2], false)
+            .unwrap();
+        for (&t, q) in s.grid().iter().zip(&q) {
+            assert!(s.evaluate(t).angle_to(q) < 1e-5);
+        }
+        // No jump when crossing 180 degrees
+        let last = s.grid().len() - 2;
+        let (t0, t1) = (s.grid()[last], s.grid()[last + 1]);
+        for i in 1..10 {
+            let t = t0 + (t1 - t0) * i as f32 / 10.0;
+            let azim = crate::quaternion::quat2angles(s.evaluate(t)).0;
+            assert!(azim.abs() > 150.0);
+        }
+    }
+
+    #[test]
+    fn velocity() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 20.0, 0.0),
+            angles2quat(120.0, 60.0, 30.0),
+        ];
+        let s =
+            LogQuaternionSpline::new_centripetal_kochanek_bartels(q, &[[0.0; 3]], false).unwrap();
+        let h = 1e-3;
+        for t in [0.3, 1.0, 1.7] {
+            let numerical = s
+                .evaluate(t - h)
+                .rotation_to(&s.evaluate(t + h))
+                .scaled_axis()
+                / (2.0 * h);
+            assert!((s.evaluate_velocity(t) - numerical).norm() < 1e-2);
+        }
+    }
+}
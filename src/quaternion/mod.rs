@@ -9,12 +9,28 @@ pub type UnitQuaternion = GenericUnitQuaternion<f32>;
 
 pub type Vec3 = Vector3<f32>;
 
+pub type Isometry3 = nalgebra::Isometry3<f32>;
+
+pub type Rotation3 = nalgebra::Rotation3<f32>;
+
 pub mod centripetalkochanekbartelsspline;
 pub mod cubicdecasteljau;
+pub mod decasteljau;
+pub mod logquaternionspline;
+pub mod piecewiseslerp;
+pub mod resample;
+pub mod squad;
+pub mod swingtwist;
 
 pub use cubicdecasteljau::CubicDeCasteljau;
+pub use decasteljau::DeCasteljau;
+pub use logquaternionspline::LogQuaternionSpline;
+pub use piecewiseslerp::PiecewiseSlerp;
+pub use resample::{resample, Keyframes};
+pub use squad::Squad;
 
-use crate::NormWrapper;
+use crate::math;
+use crate::{NormWrapper, Spline, SplineWithVelocity};
 
 pub struct AngularVelocityNorm;
 
@@ -30,13 +46,21 @@ fn negate(q: &mut UnitQuaternion) {
 }
 
 pub fn canonicalize(quaternions: &mut [UnitQuaternion]) {
+    let _ = canonicalize_with_report(quaternions);
+}
+
+/// Like [`canonicalize()`], but returns the indices of the negated quaternions.
+pub fn canonicalize_with_report(quaternions: &mut [UnitQuaternion]) -> Vec<usize> {
+    let mut flipped = Vec::new();
     let mut p = UnitQuaternion::identity();
-    for q in quaternions {
+    for (i, q) in quaternions.iter_mut().enumerate() {
         if p.dot(q) < 0.0 {
             negate(q);
+            flipped.push(i);
         }
         p = *q;
     }
+    flipped
 }
 
 /// angles in degrees!
@@ -46,3 +70,200 @@ pub fn angles2quat(azim: f32, elev: f32, roll: f32) -> UnitQuaternion {
         * UnitQuaternion::from_axis_angle(&Vec3::x_axis(), elev.to_radians())
         * UnitQuaternion::from_axis_angle(&Vec3::y_axis(), roll.to_radians())
 }
+
+/// Inverse of [`angles2quat()`], angles in degrees!
+///
+/// Elevation is in the range from -90 to 90 degrees,
+/// azimuth and roll from -180 to 180 degrees.
+/// At an elevation of +/-90 degrees, roll is zero.
+#[must_use]
+pub fn quat2angles(q: UnitQuaternion) -> (f32, f32, f32) {
+    let m = q.to_rotation_matrix();
+    let m = m.matrix();
+    let elev = math::atan2(m[(2, 1)], math::hypot(m[(0, 1)], m[(1, 1)]));
+    let (azim, roll) = if m[(2, 1)].abs() < 1.0 - 1e-6 {
+        (
+            math::atan2(-m[(0, 1)], m[(1, 1)]),
+            math::atan2(-m[(2, 0)], m[(2, 2)]),
+        )
+    } else {
+        (math::atan2(m[(1, 0)], m[(0, 0)]), 0.0)
+    };
+    (azim.to_degrees(), elev.to_degrees(), roll.to_degrees())
+}
+
+/// Like [`quat2angles()`], but without jumps between consecutive `times`.
+///
+/// Azimuth and roll are not limited to the range from -180 to 180 degrees
+/// and elevation is allowed to go beyond +/-90 degrees
+/// (turning azimuth and roll by 180 degrees).
+#[must_use]
+pub fn unwrapped_angles(
+    spline: &(impl Spline<UnitQuaternion> + ?Sized),
+    times: impl IntoIterator<Item = f32>,
+) -> Vec<(f32, f32, f32)> {
+    fn unwrap(angle: f32, previous: f32) -> f32 {
+        angle - ((angle - previous) / 360.0).round() * 360.0
+    }
+    let mut result: Vec<(f32, f32, f32)> = Vec::new();
+    for t in times {
+        let (azim, elev, roll) = quat2angles(spline.evaluate(t));
+        let angles = match result.last() {
+            None => (azim, elev, roll),
+            Some(&(a, e, r)) => {
+                let distance =
+                    |(x, y, z): (f32, f32, f32)| (x - a).abs() + (y - e).abs() + (z - r).abs();
+                // Two equivalent solutions
+                [
+                    (azim, elev, roll),
+                    (azim + 180.0, 180.0 - elev, roll + 180.0),
+                ]
+                .iter()
+                .map(|&(x, y, z)| (unwrap(x, a), unwrap(y, e), unwrap(z, r)))
+                .min_by(|&p, &q| distance(p).partial_cmp(&distance(q)).unwrap())
+                .unwrap()
+            }
+        };
+        result.push(angles);
+    }
+    result
+}
+
+/// Rotation matrices of rotation splines over time.
+pub trait EvaluateMatrix: Spline<UnitQuaternion> {
+    fn evaluate_matrix(&self, t: f32) -> Rotation3 {
+        self.evaluate(t).to_rotation_matrix()
+    }
+
+    /// Like [`evaluate_matrix()`](Self::evaluate_matrix), but as an array of columns.
+    fn evaluate_matrix_array(&self, t: f32) -> [[f32; 3]; 3] {
+        self.evaluate_matrix(t).into_inner().into()
+    }
+}
+
+impl<T> EvaluateMatrix for T where T: Spline<UnitQuaternion> + ?Sized {}
+
+/// Total amount of rotation of rotation splines.
+pub trait TotalRotation: SplineWithVelocity<UnitQuaternion, Vec3> {
+    /// Integral of the angular speed between `start` and `end` (in degrees).
+    ///
+    /// This is the sum of all rotations in any direction
+    /// (e.g. 720 degrees for two full turns).
+    /// Times outside of the grid are clamped.
+    fn total_rotation(&self, start: f32, end: f32) -> f32 {
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let (start, first) = self.clamp_parameter_and_find_index(start);
+        let (end, last) = self.clamp_parameter_and_find_index(end);
+        let grid = self.grid();
+        (first..=last)
+            .map(|index| {
+                let a = start.max(grid[index]);
+                let b = end.min(grid[index + 1]);
+                if a < b {
+                    self.integrated_speed::<AngularVelocityNorm>(index, a, b)
+                } else {
+                    0.0
+                }
+            })
+            .sum::<f32>()
+            .to_degrees()
+    }
+}
+
+impl<T> TotalRotation for T where T: SplineWithVelocity<UnitQuaternion, Vec3> + ?Sized {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalize_report() {
+        let mut quaternions = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(200.0, 0.0, 0.0),
+            angles2quat(210.0, 0.0, 0.0),
+            angles2quat(-120.0, 0.0, 0.0),
+        ];
+        let original = quaternions;
+        assert_eq!(canonicalize_with_report(&mut quaternions), [1, 2]);
+        assert_eq!(quaternions[1].coords, -original[1].coords);
+        assert!(canonicalize_with_report(&mut quaternions).is_empty());
+    }
+
+    #[test]
+    fn angles() {
+        for angles in [
+            (0.0, 0.0, 0.0),
+            (30.0, -20.0, 10.0),
+            (-170.0, 80.0, 150.0),
+            (100.0, -45.0, -120.0),
+        ] {
+            let (azim, elev, roll) = quat2angles(angles2quat(angles.0, angles.1, angles.2));
+            assert!((azim - angles.0).abs() < 1e-3);
+            assert!((elev - angles.1).abs() < 1e-3);
+            assert!((roll - angles.2).abs() < 1e-3);
+        }
+        let (azim, elev, roll) = quat2angles(angles2quat(20.0, 90.0, 0.0));
+        assert!((azim - 20.0).abs() < 1e-3 && (elev - 90.0).abs() < 1e-3 && roll == 0.0);
+    }
+
+    #[test]
+    fn unwrapped() {
+        let spline = piecewiseslerp::PiecewiseSlerp::new(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(120.0, 0.0, 0.0),
+                angles2quat(240.0, 0.0, 0.0),
+                angles2quat(0.0, 0.0, 0.0),
+            ],
+            [0.0, 1.0, 2.0, 3.0],
+            false,
+        )
+        .unwrap();
+        let angles = unwrapped_angles(&spline, (0..=30).map(|i| i as f32 * 0.1));
+        assert!((angles.last().unwrap().0 - 360.0).abs() < 1e-3);
+        assert!(angles.windows(2).all(|w| (w[1].0 - w[0].0).abs() < 13.0));
+    }
+
+    #[test]
+    fn matrix() {
+        let s = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(90.0, 0.0, 0.0)],
+            [0.0, 2.0],
+            false,
+        )
+        .unwrap();
+        // Front (y axis) turned to the left (negative x axis)
+        let m = s.evaluate_matrix(2.0);
+        assert!((m * Vec3::y() - Vec3::new(-1.0, 0.0, 0.0)).norm() < 1e-6);
+        let columns = s.evaluate_matrix_array(2.0);
+        assert!((Vec3::from(columns[1]) - Vec3::new(-1.0, 0.0, 0.0)).norm() < 1e-6);
+        assert!((Vec3::from(columns[2]) - Vec3::z()).norm() < 1e-6);
+    }
+
+    #[test]
+    fn total_rotation() {
+        let turn = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(120.0, 0.0, 0.0),
+            angles2quat(240.0, 0.0, 0.0),
+        ];
+        let s = PiecewiseSlerp::new(turn, [0.0, 1.0, 2.0, 3.0], true).unwrap();
+        assert!((s.total_rotation(0.0, 3.0) - 360.0).abs() < 1e-3);
+        assert!((s.total_rotation(2.5, 0.5) - 240.0).abs() < 1e-3);
+        assert!((s.total_rotation(-1.0, 1.0) - 120.0).abs() < 1e-3);
+        assert_eq!(s.total_rotation(1.5, 1.5), 0.0);
+        let s = crate::AsdfRotSpline::new(
+            turn,
+            [Some(0.0), None, None, Some(3.0)],
+            [[0.0; 3]; 3],
+            true,
+        )
+        .unwrap();
+        assert!((s.total_rotation(0.0, 3.0) - 360.0).abs() < 1e-2);
+    }
+}
@@ -0,0 +1,128 @@
+//! Spherical linear interpolation between keyframes.
+//!
+//! The angular velocity is constant within each segment
+//! and jumps at the keyframes.
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::utilities::{check_grid, GridError};
+use crate::{Spline, SplineWithVelocity};
+
+use super::{canonicalize, UnitQuaternion, Vec3};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least two quaternions")]
+    LessThanTwoQuaternions,
+    #[error("length of grid ({grid}) must be {} number of quaternions ({quaternions})", if *.closed {
+        "one more than"
+    } else {
+        "the same as"
+    })]
+    GridVsQuaternions {
+        grid: usize,
+        quaternions: usize,
+        closed: bool,
+    },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+pub struct PiecewiseSlerp {
+    keyframes: Box<[UnitQuaternion]>,
+    grid: Box<[f32]>,
+}
+
+impl PiecewiseSlerp {
+    /// If `closed`, the first quaternion is repeated at the end.
+    pub fn new(
+        quaternions: impl Into<Vec<UnitQuaternion>>,
+        grid: impl Into<Box<[f32]>>,
+        closed: bool,
+    ) -> Result<PiecewiseSlerp, Error> {
+        use Error::*;
+        let mut keyframes = quaternions.into();
+        let grid = grid.into();
+        if keyframes.len() < 2 {
+            return Err(LessThanTwoQuaternions);
+        }
+        if keyframes.len() + closed as usize != grid.len() {
+            return Err(GridVsQuaternions {
+                grid: grid.len(),
+                quaternions: keyframes.len(),
+                closed,
+            });
+        }
+        check_grid(&grid)?;
+        if closed {
+            keyframes.push(keyframes[0]);
+        }
+        canonicalize(&mut keyframes);
+        Ok(PiecewiseSlerp {
+            keyframes: keyframes.into(),
+            grid,
+        })
+    }
+
+    /// Including the repeated first quaternion if `closed`.
+    #[must_use]
+    pub fn keyframes(&self) -> &[UnitQuaternion] {
+        &self.keyframes
+    }
+}
+
+impl Spline<UnitQuaternion> for PiecewiseSlerp {
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let t1 = self.grid[idx + 1];
+        let t = (t - t0) / (t1 - t0);
+        self.keyframes[idx].slerp(&self.keyframes[idx + 1], t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+impl SplineWithVelocity<UnitQuaternion, Vec3> for PiecewiseSlerp {
+    /// At keyframes, the velocity of the following segment is returned.
+    fn evaluate_velocity(&self, t: f32) -> Vec3 {
+        let (_, idx) = self.clamp_parameter_and_find_index(t);
+        let delta_t = self.grid[idx + 1] - self.grid[idx];
+        self.keyframes[idx]
+            .rotation_to(&self.keyframes[idx + 1])
+            .scaled_axis()
+            / delta_t
+    }
+}
+
+impl Fingerprint for PiecewiseSlerp {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.keyframes.write_fingerprint(hasher);
+        self.grid.write_fingerprint(hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::angles2quat;
+
+    #[test]
+    fn constant_velocity() {
+        let q = [
+            angles2quat(0.0, 0.0, 0.0),
+            angles2quat(90.0, 0.0, 0.0),
+            angles2quat(90.0, 30.0, 0.0),
+        ];
+        let s = PiecewiseSlerp::new(q, [0.0, 1.0, 3.0], false).unwrap();
+        assert!(s.evaluate(0.5).angle_to(&angles2quat(45.0, 0.0, 0.0)) < 1e-5);
+        assert!(s.evaluate(3.0).angle_to(&q[2]) < 1e-5);
+        let v = s.evaluate_velocity(0.25);
+        assert!((v - Vec3::new(0.0, 0.0, 90.0f32.to_radians())).norm() < 1e-5);
+        assert!((s.evaluate_velocity(1.5).norm() - 15.0f32.to_radians()).abs() < 1e-5);
+        let closed = PiecewiseSlerp::new(q, [0.0, 1.0, 3.0, 4.0], true).unwrap();
+        assert!(closed.evaluate(4.0).angle_to(&q[0]) < 1e-5);
+    }
+}
@@ -0,0 +1,149 @@
+//! Conversion of rotation splines into keyframes with a fixed rate.
+//!
+//! This is meant for exporting to engines which only support
+//! (spherical linear) interpolation between keyframed orientations.
+
+use crate::Spline;
+
+use super::{canonicalize, UnitQuaternion};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("rate must be a positive number (not {rate:?})")]
+    InvalidRate { rate: f32 },
+    #[error("tolerance must be a non-negative number (not {tolerance:?})")]
+    InvalidTolerance { tolerance: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes {
+    pub times: Vec<f32>,
+    /// Consecutive quaternions are canonicalized, see [`canonicalize()`].
+    pub quaternions: Vec<UnitQuaternion>,
+}
+
+/// Samples `spline` with `rate` keyframes per second.
+///
+/// The first keyframe is at the first grid value,
+/// the last grid value is always included
+/// (even if it is closer than `1 / rate` to the previous keyframe).
+///
+/// If a `tolerance` (in degrees) is given, keyframes are dropped as long as
+/// Slerp between the remaining neighbors doesn't deviate more than that
+/// from the dropped samples.
+pub fn resample(
+    spline: &(impl Spline<UnitQuaternion> + ?Sized),
+    rate: f32,
+    tolerance: Option<f32>,
+) -> Result<Keyframes, Error> {
+    use Error::*;
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    if !(rate > 0.0) || rate.is_infinite() {
+        return Err(InvalidRate { rate });
+    }
+    if let Some(tolerance) = tolerance {
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(tolerance >= 0.0) {
+            return Err(InvalidTolerance { tolerance });
+        }
+    }
+    let grid = spline.grid();
+    let start = grid[0];
+    let end = *grid.last().unwrap();
+    let count = ((end - start) * rate).ceil() as usize;
+    let mut times: Vec<_> = (0..count).map(|k| start + k as f32 / rate).collect();
+    times.push(end);
+    let mut quaternions: Vec<_> = times.iter().map(|&t| spline.evaluate(t)).collect();
+    canonicalize(&mut quaternions);
+    if let Some(tolerance) = tolerance {
+        let keep = decimate(&times, &quaternions, tolerance.to_radians());
+        times = keep.iter().map(|&i| times[i]).collect();
+        quaternions = keep.iter().map(|&i| quaternions[i]).collect();
+    }
+    Ok(Keyframes { times, quaternions })
+}
+
+/// Returns the indices of the keyframes to keep (including the first and last one).
+fn decimate(times: &[f32], quaternions: &[UnitQuaternion], tolerance: f32) -> Vec<usize> {
+    let within_tolerance = |first: usize, last: usize| {
+        let (t0, t1) = (times[first], times[last]);
+        (first + 1..last).all(|k| {
+            let interpolated =
+                quaternions[first].slerp(&quaternions[last], (times[k] - t0) / (t1 - t0));
+            interpolated.angle_to(&quaternions[k]) <= tolerance
+        })
+    };
+    let last = times.len() - 1;
+    let mut keep = vec![0];
+    let mut anchor = 0;
+    for i in 2..=last {
+        if !within_tolerance(anchor, i) {
+            anchor = i - 1;
+            keep.push(anchor);
+        }
+    }
+    if last > 0 {
+        keep.push(last);
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, PiecewiseSlerp};
+    use crate::AsdfRotSpline;
+
+    #[test]
+    fn fixed_rate() {
+        let s = PiecewiseSlerp::new(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(90.0, 0.0, 0.0),
+                angles2quat(90.0, 45.0, 0.0),
+            ],
+            [0.0, 1.0, 2.25],
+            false,
+        )
+        .unwrap();
+        let k = resample(&s, 4.0, None).unwrap();
+        assert_eq!(k.times.len(), 10);
+        assert_eq!(k.times[1], 0.25);
+        assert_eq!(*k.times.last().unwrap(), 2.25);
+        // Piecewise linear input only needs the corners
+        let k = resample(&s, 4.0, Some(0.01)).unwrap();
+        assert_eq!(k.times, [0.0, 1.0, 2.25]);
+        assert!(matches!(
+            resample(&s, 0.0, None),
+            Err(Error::InvalidRate { .. })
+        ));
+        assert!(matches!(
+            resample(&s, 1.0, Some(-1.0)),
+            Err(Error::InvalidTolerance { .. })
+        ));
+    }
+
+    #[test]
+    fn decimation() {
+        let s = AsdfRotSpline::new(
+            [
+                angles2quat(0.0, 0.0, 0.0),
+                angles2quat(90.0, 20.0, 0.0),
+                angles2quat(180.0, 0.0, 30.0),
+            ],
+            [Some(0.0), None, Some(3.0)],
+            [[0.0; 3]],
+            false,
+        )
+        .unwrap();
+        let all = resample(&s, 100.0, None).unwrap();
+        let tolerance = 0.5;
+        let fewer = resample(&s, 100.0, Some(tolerance)).unwrap();
+        assert!(fewer.quaternions.len() < all.quaternions.len() / 4);
+        let reduced = PiecewiseSlerp::new(fewer.quaternions, fewer.times, false).unwrap();
+        for (&t, q) in all.times.iter().zip(&all.quaternions) {
+            assert!(reduced.evaluate(t).angle_to(q).to_degrees() <= tolerance + 1e-3);
+        }
+    }
+}
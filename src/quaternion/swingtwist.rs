@@ -0,0 +1,85 @@
+//! Decomposition of rotations into swing and twist.
+//!
+//! The twist is a rotation around a given axis,
+//! the swing is a rotation around an axis perpendicular to that.
+//! With the y axis (i.e. the front direction, see [`angles2quat()`](super::angles2quat)),
+//! the twist corresponds to the roll.
+
+use nalgebra::Quaternion;
+
+use crate::Spline;
+
+use super::{UnitQuaternion, Vec3};
+
+/// Returns `(swing, twist)`, where `rotation = swing * twist`.
+///
+/// `axis` doesn't have to be normalized.
+/// If the rotation is by 180 degrees around an axis perpendicular to `axis`,
+/// the twist is the identity.
+#[must_use]
+pub fn swing_twist(rotation: UnitQuaternion, axis: Vec3) -> (UnitQuaternion, UnitQuaternion) {
+    let axis = axis.normalize();
+    let q = rotation.quaternion();
+    let projection = axis * q.imag().dot(&axis);
+    let twist = Quaternion::from_parts(q.scalar(), projection);
+    let twist = if twist.norm_squared() > 0.0 {
+        UnitQuaternion::from_quaternion(twist)
+    } else {
+        UnitQuaternion::identity()
+    };
+    (rotation * twist.inverse(), twist)
+}
+
+/// Signed angle (in degrees) of the twist around `axis`, between -180 and 180.
+#[must_use]
+pub fn twist_angle(rotation: UnitQuaternion, axis: Vec3) -> f32 {
+    let (_, twist) = swing_twist(rotation, axis);
+    let angle = twist.angle().to_degrees();
+    match twist.axis() {
+        Some(twist_axis) if twist_axis.dot(&axis) < 0.0 => -angle,
+        _ => angle,
+    }
+}
+
+/// Swing and twist of rotation splines over time.
+pub trait SwingTwist: Spline<UnitQuaternion> {
+    /// See [`swing_twist()`].
+    fn evaluate_swing_twist(&self, t: f32, axis: Vec3) -> (UnitQuaternion, UnitQuaternion) {
+        swing_twist(self.evaluate(t), axis)
+    }
+
+    /// See [`twist_angle()`].
+    fn evaluate_twist_angle(&self, t: f32, axis: Vec3) -> f32 {
+        twist_angle(self.evaluate(t), axis)
+    }
+}
+
+impl<T> SwingTwist for T where T: Spline<UnitQuaternion> + ?Sized {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, PiecewiseSlerp};
+
+    #[test]
+    fn roll() {
+        let y = Vec3::y();
+        let q = angles2quat(30.0, 20.0, 15.0);
+        let (swing, twist) = swing_twist(q, y);
+        assert!((swing * twist).angle_to(&q) < 1e-5);
+        assert!(((swing * Vec3::y()) - q * Vec3::y()).norm() < 1e-5);
+        assert!(swing.axis().unwrap().dot(&y).abs() < 1e-5);
+        // Without azimuth, the swing axis is the x axis
+        assert!((twist_angle(angles2quat(0.0, 20.0, 15.0), y) - 15.0).abs() < 1e-3);
+        assert!((twist_angle(angles2quat(0.0, 0.0, -40.0), y * 2.0) + 40.0).abs() < 1e-3);
+        let s = PiecewiseSlerp::new(
+            [angles2quat(0.0, 0.0, 0.0), angles2quat(0.0, 0.0, 60.0)],
+            [0.0, 1.0],
+            false,
+        )
+        .unwrap();
+        assert!((s.evaluate_twist_angle(0.5, y) - 30.0).abs() < 1e-3);
+        assert!(s.evaluate_twist_angle(0.5, Vec3::z()).abs() < 1e-3);
+    }
+}
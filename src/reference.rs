@@ -0,0 +1,386 @@
+//! High-precision reference implementations for validating single-precision results.
+//!
+//! Everything is computed with `f64`, arc lengths are integrated adaptively
+//! and inverted with tight tolerances.
+//! This is much slower than the regular implementations,
+//! it is meant for tests and for debugging accuracy problems.
+//!
+//! This module is only available with the `reference` feature.
+
+use crate::piecewisecubiccurve::Components;
+use crate::{PiecewiseCubicCurve, Spline, Vector};
+
+/// Relative tolerance of arc lengths and their inverse.
+pub const TOLERANCE: f64 = 1e-12;
+
+/// Maximum number of interval bisections during integration.
+const MAX_DEPTH: usize = 40;
+
+/// Double-precision copy of a [`PiecewiseCubicCurve`].
+///
+/// Arc lengths are based on the Euclidean norm,
+/// like in [`AsdfPosSpline`](crate::AsdfPosSpline).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceCurve {
+    dimensions: usize,
+    /// `dimensions` entries per segment, each starting with the constant term.
+    coefficients: Box<[[f64; 4]]>,
+    grid: Box<[f64]>,
+    /// Cumulative arc lengths at the grid points.
+    lengths: Box<[f64]>,
+}
+
+impl ReferenceCurve {
+    /// Converts all coefficients (exactly) and integrates the arc lengths of all segments.
+    #[must_use]
+    pub fn new<V: Vector + Components>(curve: &PiecewiseCubicCurve<V>) -> ReferenceCurve {
+        let coefficients = curve
+            .segments()
+            .iter()
+            .flat_map(|a| {
+                (0..V::COUNT).map(move |c| {
+                    [
+                        f64::from(a[0].component(c)),
+                        f64::from(a[1].component(c)),
+                        f64::from(a[2].component(c)),
+                        f64::from(a[3].component(c)),
+                    ]
+                })
+            })
+            .collect();
+        let grid: Box<[f64]> = curve.grid().iter().copied().map(f64::from).collect();
+        let mut result = ReferenceCurve {
+            dimensions: V::COUNT,
+            coefficients,
+            grid,
+            lengths: Box::new([]),
+        };
+        let mut lengths = Vec::with_capacity(result.grid.len());
+        lengths.push(0.0);
+        for idx in 0..result.grid.len() - 1 {
+            let length = result.segment_length(idx, result.grid[idx], result.grid[idx + 1]);
+            lengths.push(lengths.last().unwrap() + length);
+        }
+        result.lengths = lengths.into();
+        result
+    }
+
+    #[must_use]
+    pub fn grid(&self) -> &[f64] {
+        &self.grid
+    }
+
+    /// Cumulative arc lengths at the grid points, starting with zero.
+    ///
+    /// This corresponds to the grid of a
+    /// [`ConstantSpeedAdapter`](crate::adapters::ConstantSpeedAdapter).
+    #[must_use]
+    pub fn arc_lengths(&self) -> &[f64] {
+        &self.lengths
+    }
+
+    /// Like [`Spline::evaluate()`](crate::Spline::evaluate), one value per dimension.
+    #[must_use]
+    pub fn evaluate(&self, t: f64) -> Vec<f64> {
+        let (idx, t) = self.find_segment(t);
+        self.segment_coefficients(idx)
+            .iter()
+            .map(|a| ((a[3] * t + a[2]) * t + a[1]) * t + a[0])
+            .collect()
+    }
+
+    /// Like [`SplineWithVelocity::evaluate_velocity()`](crate::SplineWithVelocity),
+    /// one value per dimension.
+    #[must_use]
+    pub fn evaluate_velocity(&self, t: f64) -> Vec<f64> {
+        let (idx, t) = self.find_segment(t);
+        let inverse_duration = self.inverse_duration(idx);
+        self.segment_coefficients(idx)
+            .iter()
+            .map(|a| ((a[3] * 3.0 * t + a[2] * 2.0) * t + a[1]) * inverse_duration)
+            .collect()
+    }
+
+    /// Arc length between the (clamped) parameters `a` and `b`,
+    /// negative if `b` is smaller than `a`.
+    #[must_use]
+    pub fn arc_length(&self, a: f64, b: f64) -> f64 {
+        self.length_at(b) - self.length_at(a)
+    }
+
+    /// Curve parameter where the arc length (from the start) reaches `s`.
+    ///
+    /// This is the inverse of the arc length function,
+    /// as used by [`ConstantSpeedAdapter`](crate::adapters::ConstantSpeedAdapter).
+    /// If `s` is outside, the parameter is clipped.
+    #[must_use]
+    pub fn parameter_at_length(&self, s: f64) -> f64 {
+        let (s, idx) = clamp_and_find_index(&self.lengths, s);
+        let (t0, t1) = (self.grid[idx], self.grid[idx + 1]);
+        let target = s - self.lengths[idx];
+        let length = self.lengths[idx + 1] - self.lengths[idx];
+        if length == 0.0 {
+            // Zero-length ("hold") segment, any t would do
+            return t0;
+        }
+        // Newton's method, falling back to bisection
+        let (mut lower, mut upper) = (t0, t1);
+        let mut t = t0 + (t1 - t0) * target / length;
+        for _ in 0..100 {
+            let residual = self.segment_length(idx, t0, t) - target;
+            if residual.abs() <= TOLERANCE * length {
+                break;
+            }
+            if residual < 0.0 {
+                lower = t;
+            } else {
+                upper = t;
+            }
+            let speed = self.speed(idx, t);
+            let next = t - residual / speed;
+            t = if speed > 0.0 && lower < next && next < upper {
+                next
+            } else {
+                (lower + upper) / 2.0
+            };
+            if upper - lower <= TOLERANCE * (t1 - t0) {
+                break;
+            }
+        }
+        t
+    }
+
+    /// Like [`evaluate()`](Self::evaluate), but with constant speed.
+    #[must_use]
+    pub fn evaluate_constant_speed(&self, s: f64) -> Vec<f64> {
+        self.evaluate(self.parameter_at_length(s))
+    }
+
+    fn segment_coefficients(&self, idx: usize) -> &[[f64; 4]] {
+        &self.coefficients[idx * self.dimensions..(idx + 1) * self.dimensions]
+    }
+
+    /// Zero for zero-length segments (which are never evaluated).
+    fn inverse_duration(&self, idx: usize) -> f64 {
+        let duration = self.grid[idx + 1] - self.grid[idx];
+        if duration > 0.0 {
+            1.0 / duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the segment index and the normalized time (from 0 to 1).
+    fn find_segment(&self, t: f64) -> (usize, f64) {
+        let (t, idx) = clamp_and_find_index(&self.grid, t);
+        (idx, (t - self.grid[idx]) * self.inverse_duration(idx))
+    }
+
+    fn speed(&self, idx: usize, t: f64) -> f64 {
+        let t = (t - self.grid[idx]) * self.inverse_duration(idx);
+        let squared: f64 = self
+            .segment_coefficients(idx)
+            .iter()
+            .map(|a| {
+                let v = (a[3] * 3.0 * t + a[2] * 2.0) * t + a[1];
+                v * v
+            })
+            .sum();
+        squared.sqrt() * self.inverse_duration(idx)
+    }
+
+    /// `a` and `b` must be within segment `idx`.
+    fn segment_length(&self, idx: usize, a: f64, b: f64) -> f64 {
+        adaptive_gauss_kronrod(|t| self.speed(idx, t), a, b, MAX_DEPTH)
+    }
+
+    fn length_at(&self, t: f64) -> f64 {
+        let (t, idx) = clamp_and_find_index(&self.grid, t);
+        self.lengths[idx] + self.segment_length(idx, self.grid[idx], t)
+    }
+}
+
+/// Like [`Spline::clamp_parameter_and_find_index()`](crate::Spline::clamp_parameter_and_find_index).
+fn clamp_and_find_index(grid: &[f64], t: f64) -> (f64, usize) {
+    let first = grid[0];
+    let last = grid[grid.len() - 1];
+    if t < first {
+        (first, 0)
+    } else if t < last {
+        (t, grid.partition_point(|&x| x <= t) - 1)
+    } else {
+        (last, grid.len() - 2)
+    }
+}
+
+/// Recursively bisects the interval until the 15-point Gauss-Kronrod result
+/// and the embedded 7-point Gauss result agree within [`TOLERANCE`].
+///
+/// See [`gauss_kronrod15()`](crate::utilities::gauss_kronrod15) for the single-precision version.
+fn adaptive_gauss_kronrod<F>(f: F, a: f64, b: f64, max_depth: usize) -> f64
+where
+    F: Fn(f64) -> f64 + Copy,
+{
+    let (kronrod, gauss) = gauss_kronrod15(f, a, b);
+    let middle = (a + b) / 2.0;
+    if (kronrod - gauss).abs() <= TOLERANCE * kronrod.abs()
+        || max_depth == 0
+        || middle <= a
+        || middle >= b
+    {
+        return kronrod;
+    }
+    adaptive_gauss_kronrod(f, a, middle, max_depth - 1)
+        + adaptive_gauss_kronrod(f, middle, b, max_depth - 1)
+}
+
+/// Returns the Kronrod and the Gauss estimate.
+fn gauss_kronrod15<F>(f: F, a: f64, b: f64) -> (f64, f64)
+where
+    F: Fn(f64) -> f64,
+{
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let nodes = [
+        0.991455371120812639206854697526329,
+        0.949107912342758524526189684047851,
+        0.864864423359769072789712788640926,
+        0.741531185599394439863864773280788,
+        0.586087235467691130294144845693013,
+        0.405845151377397166906606412076961,
+        0.207784955007898467600689403773245,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let kronrod_weights = [
+        0.022935322010529224963732008058970,
+        0.063092092629978553290700663189204,
+        0.104790010322250183839876322541518,
+        0.140653259715525918745189590510238,
+        0.169004726639267902826583426598550,
+        0.190350578064785409913256402421014,
+        0.204432940075298892414161999234649,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let kronrod_center = 0.209482141084727828012999174891714;
+    // The Gauss nodes are the ones with odd indices (and the center)
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let gauss_weights = [
+        0.129484966168869693270611432679082,
+        0.279705391489276667901467771423780,
+        0.381830050505118944950369775488975,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let gauss_center = 0.417959183673469387755102040816327;
+
+    let center = (a + b) / 2.0;
+    let half_length = (b - a) / 2.0;
+    let f_center = f(center);
+    let mut kronrod = kronrod_center * f_center;
+    let mut gauss = gauss_center * f_center;
+    for (i, x) in nodes.iter().enumerate() {
+        let sum = f(center - half_length * x) + f(center + half_length * x);
+        kronrod += kronrod_weights[i] * sum;
+        if i % 2 == 1 {
+            gauss += gauss_weights[i / 2] * sum;
+        }
+    }
+    (kronrod * half_length, gauss * half_length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::adapters::ConstantSpeedAdapter;
+    use crate::quaternion::Vec3;
+    use crate::NormWrapper;
+
+    struct NormVec3;
+
+    impl NormWrapper<NormVec3> for Vec3 {
+        fn norm(&self) -> f32 {
+            self.norm()
+        }
+    }
+
+    fn curve() -> PiecewiseCubicCurve<Vec3> {
+        PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+            &[
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 2.0, 0.0),
+                Vec3::new(3.0, 1.0, 1.0),
+                Vec3::new(3.0, 1.0, 1.0),
+                Vec3::new(-2.0, 0.5, 0.0),
+            ],
+            &[[0.0; 3]; 3],
+            false,
+            |v| v.norm(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn straight_line() {
+        let curve = PiecewiseCubicCurve::new_hermite(
+            &[Vec3::new(1.0, 0.0, 0.0), Vec3::new(4.0, 4.0, 0.0)],
+            &[Vec3::new(1.5, 2.0, 0.0), Vec3::new(1.5, 2.0, 0.0)],
+            &[0.0, 2.0],
+        )
+        .unwrap();
+        let reference = ReferenceCurve::new(&curve);
+        assert_eq!(reference.arc_lengths(), [0.0, 5.0]);
+        assert!((reference.arc_length(0.5, 1.5) - 2.5).abs() < 1e-12);
+        assert!((reference.parameter_at_length(1.25) - 0.5).abs() < 1e-12);
+        assert_eq!(reference.evaluate(1.0), [2.5, 2.0, 0.0]);
+        assert_eq!(reference.evaluate_velocity(1.0), [1.5, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn single_precision() {
+        let curve = curve();
+        let reference = ReferenceCurve::new(&curve);
+        for i in 0..=40 {
+            let t = i as f32 * 0.1;
+            let expected = reference.evaluate(t.into());
+            let value = curve.evaluate(t);
+            for c in 0..3 {
+                assert!((f64::from(value[c]) - expected[c]).abs() < 1e-5);
+            }
+        }
+        let adapter: ConstantSpeedAdapter<_, _, _, NormVec3> = ConstantSpeedAdapter::adapt(curve);
+        for (s, expected) in adapter.grid().iter().zip(reference.arc_lengths()) {
+            assert!((f64::from(*s) - expected).abs() < 1e-5 * expected.max(1.0));
+        }
+        let total = *reference.arc_lengths().last().unwrap();
+        for i in 0..=50 {
+            let s = total * f64::from(i) / 50.0;
+            let expected = reference.evaluate_constant_speed(s);
+            let value = adapter.evaluate(s as f32);
+            let error = (value
+                - Vec3::new(expected[0] as f32, expected[1] as f32, expected[2] as f32))
+            .norm();
+            assert!(error < 1e-3);
+        }
+        // Hold segment
+        assert_eq!(reference.arc_lengths()[2], reference.arc_lengths()[3]);
+        assert_eq!(
+            reference.parameter_at_length(reference.arc_lengths()[2]),
+            reference.grid()[3]
+        );
+    }
+
+    #[test]
+    fn inverse() {
+        let reference = ReferenceCurve::new(&curve());
+        let total = *reference.arc_lengths().last().unwrap();
+        for i in 0..=20 {
+            let s = total * f64::from(i) / 20.0;
+            let t = reference.parameter_at_length(s);
+            assert!((reference.arc_length(0.0, t) - s).abs() < 1e-10 * total);
+        }
+        assert_eq!(reference.parameter_at_length(-1.0), 0.0);
+        assert_eq!(
+            reference.parameter_at_length(total + 1.0),
+            *reference.grid().last().unwrap()
+        );
+    }
+}
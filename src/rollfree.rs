@@ -0,0 +1,94 @@
+//! Orientations without roll, e.g. for the directivity of sound sources.
+
+use crate::asdfrotspline::Error;
+use crate::lookat::look_along;
+use crate::quaternion::{UnitQuaternion, Vec3};
+use crate::{AsdfRotSpline, Spline};
+
+/// Removes the roll from a rotation spline.
+///
+/// Only the front direction (i.e. the rotated y axis) of the inner spline is used,
+/// which means that only azimuth and elevation are interpolated.
+/// When pointing straight up or down, the azimuth is undefined.
+pub struct RollFreeAdapter<Inner> {
+    inner: Inner,
+}
+
+impl<Inner> RollFreeAdapter<Inner>
+where
+    Inner: Spline<UnitQuaternion>,
+{
+    pub fn adapt(inner: Inner) -> RollFreeAdapter<Inner> {
+        RollFreeAdapter { inner }
+    }
+
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+}
+
+impl<Inner> Spline<UnitQuaternion> for RollFreeAdapter<Inner>
+where
+    Inner: Spline<UnitQuaternion>,
+{
+    fn evaluate(&self, t: f32) -> UnitQuaternion {
+        look_along(self.inner.evaluate(t) * Vec3::y())
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+pub type RollFreeRotSpline = RollFreeAdapter<AsdfRotSpline>;
+
+impl RollFreeRotSpline {
+    /// Like [`AsdfRotSpline::from_angles()`], but with (azimuth, elevation) pairs.
+    pub fn new(
+        angles: impl AsRef<[(f32, f32)]>,
+        times: impl AsRef<[Option<f32>]>,
+        tcb: impl AsRef<[[f32; 3]]>,
+        closed: bool,
+    ) -> Result<RollFreeRotSpline, Error> {
+        let angles: Vec<_> = angles
+            .as_ref()
+            .iter()
+            .map(|&(azim, elev)| (azim, elev, 0.0))
+            .collect();
+        let inner = AsdfRotSpline::from_angles(angles, times, tcb, closed)?;
+        Ok(RollFreeAdapter::adapt(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::quaternion::{angles2quat, quat2angles, PiecewiseSlerp};
+
+    #[test]
+    fn no_roll() {
+        let s = RollFreeRotSpline::new(
+            [(0.0, 0.0), (90.0, 60.0), (180.0, 0.0)],
+            [Some(0.0), None, Some(2.0)],
+            [[0.0; 3]],
+            false,
+        )
+        .unwrap();
+        for i in 0..=20 {
+            let (_, _, roll) = quat2angles(s.evaluate(i as f32 * 0.1));
+            assert!(roll.abs() < 1e-2);
+        }
+        assert!(s.evaluate(2.0).angle_to(&angles2quat(180.0, 0.0, 0.0)) < 1e-3);
+        // Rolling only doesn't change anything
+        let rolling = PiecewiseSlerp::new(
+            [angles2quat(20.0, 10.0, 0.0), angles2quat(20.0, 10.0, 90.0)],
+            [0.0, 1.0],
+            false,
+        )
+        .unwrap();
+        let s = RollFreeAdapter::adapt(rolling);
+        assert!(s.evaluate(0.5).angle_to(&angles2quat(20.0, 10.0, 0.0)) < 1e-3);
+    }
+}
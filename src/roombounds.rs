@@ -0,0 +1,311 @@
+//! Keeping trajectories within the reproducible area of a loudspeaker setup.
+
+use crate::math;
+use crate::quaternion::Vec3;
+use crate::utilities::subdivide_grid;
+use crate::Spline;
+
+/// Maximum number of iterations for projecting onto a general convex region.
+const MAX_ITERATIONS: usize = 100;
+
+/// This allows projecting without allocating, see [`ConvexRegion::project()`].
+pub const MAX_PLANES: usize = 64;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least one plane")]
+    NoPlanes,
+    #[error("too many planes ({planes}; maximum: {MAX_PLANES})")]
+    TooManyPlanes { planes: usize },
+    #[error("index {index}: plane normal must be non-zero and finite, offset must be finite")]
+    InvalidPlane { index: usize },
+    #[error("box minimum must be smaller than maximum in all dimensions")]
+    EmptyBox,
+    #[error("margin must be a positive number (not {margin:?})")]
+    InvalidMargin { margin: f32 },
+}
+
+/// Intersection of half-spaces.
+///
+/// Each half-space is given by a normal vector (pointing outwards) and an offset,
+/// points `p` are inside if `normal.dot(p) <= offset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexRegion {
+    planes: Box<[(Vec3, f32)]>,
+}
+
+impl ConvexRegion {
+    /// Normal vectors are normalized, offsets are scaled accordingly.
+    pub fn new(planes: impl Into<Vec<(Vec3, f32)>>) -> Result<ConvexRegion, Error> {
+        let mut planes = planes.into();
+        if planes.is_empty() {
+            return Err(Error::NoPlanes);
+        }
+        if planes.len() > MAX_PLANES {
+            return Err(Error::TooManyPlanes {
+                planes: planes.len(),
+            });
+        }
+        for (index, (normal, offset)) in planes.iter_mut().enumerate() {
+            let norm = normal.norm();
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(norm > 0.0) || !norm.is_finite() || !offset.is_finite() {
+                return Err(Error::InvalidPlane { index });
+            }
+            *normal /= norm;
+            *offset /= norm;
+        }
+        Ok(ConvexRegion {
+            planes: planes.into(),
+        })
+    }
+
+    pub fn axis_aligned_box(min: Vec3, max: Vec3) -> Result<ConvexRegion, Error> {
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(min.x < max.x && min.y < max.y && min.z < max.z) {
+            return Err(Error::EmptyBox);
+        }
+        ConvexRegion::new(vec![
+            (Vec3::new(1.0, 0.0, 0.0), max.x),
+            (Vec3::new(-1.0, 0.0, 0.0), -min.x),
+            (Vec3::new(0.0, 1.0, 0.0), max.y),
+            (Vec3::new(0.0, -1.0, 0.0), -min.y),
+            (Vec3::new(0.0, 0.0, 1.0), max.z),
+            (Vec3::new(0.0, 0.0, -1.0), -min.z),
+        ])
+    }
+
+    #[must_use]
+    pub fn planes(&self) -> &[(Vec3, f32)] {
+        &self.planes
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.excess(point) <= 0.0
+    }
+
+    /// Largest distance of `point` beyond any of the planes.
+    ///
+    /// This is negative inside the region.
+    #[must_use]
+    pub fn excess(&self, point: Vec3) -> f32 {
+        self.planes
+            .iter()
+            .map(|(normal, offset)| normal.dot(&point) - offset)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Closest point within the region.
+    ///
+    /// This uses Dykstra's projection algorithm,
+    /// which is exact for boxes and converges for all other convex regions.
+    /// This doesn't allocate.
+    #[must_use]
+    pub fn project(&self, point: Vec3) -> Vec3 {
+        if self.contains(point) {
+            return point;
+        }
+        let mut x = point;
+        // The increments of Dykstra's algorithm are multiples of the plane normals
+        let mut increments = [0.0; MAX_PLANES];
+        for _ in 0..MAX_ITERATIONS {
+            let previous = x;
+            for ((normal, offset), increment) in self.planes.iter().zip(&mut increments) {
+                let z = x + normal * *increment;
+                let excess = normal.dot(&z) - offset;
+                *increment = excess.max(0.0);
+                x = z - normal * *increment;
+            }
+            if x == previous {
+                break;
+            }
+        }
+        x
+    }
+
+    /// Like [`project()`](Self::project), but starting to bend inwards
+    /// within `margin` of each plane, resulting in a continuous velocity.
+    #[must_use]
+    pub fn project_smooth(&self, mut point: Vec3, margin: f32) -> Vec3 {
+        for (normal, offset) in self.planes.iter() {
+            let excess = normal.dot(&point) - offset;
+            if excess > -margin {
+                let limited = margin * (math::tanh((excess + margin) / margin) - 1.0);
+                point += normal * (limited - excess);
+            }
+        }
+        self.project(point)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Clamping {
+    /// Project outside positions onto the region boundary.
+    Hard,
+    /// See [`ConvexRegion::project_smooth()`].
+    Smooth { margin: f32 },
+    /// Don't change positions, see [`BoundsAdapter::violations()`].
+    None,
+}
+
+/// A time range where a trajectory is outside of its [`ConvexRegion`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundsViolation {
+    pub begin: f32,
+    pub end: f32,
+    pub peak_time: f32,
+    /// See [`ConvexRegion::excess()`].
+    pub peak: f32,
+}
+
+pub struct BoundsAdapter<Inner> {
+    inner: Inner,
+    region: ConvexRegion,
+    clamping: Clamping,
+}
+
+impl<Inner> BoundsAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    pub fn adapt(
+        inner: Inner,
+        region: ConvexRegion,
+        clamping: Clamping,
+    ) -> Result<BoundsAdapter<Inner>, Error> {
+        if let Clamping::Smooth { margin } = clamping {
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(margin > 0.0) || margin.is_infinite() {
+                return Err(Error::InvalidMargin { margin });
+            }
+        }
+        Ok(BoundsAdapter {
+            inner,
+            region,
+            clamping,
+        })
+    }
+
+    #[must_use]
+    pub fn region(&self) -> &ConvexRegion {
+        &self.region
+    }
+
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Time ranges where the inner spline leaves the region.
+    ///
+    /// Each segment is sampled `samples_per_segment` times,
+    /// shorter excursions might be missed.
+    #[must_use]
+    pub fn violations(&self, samples_per_segment: usize) -> Vec<BoundsViolation> {
+        let times = subdivide_grid(self.inner.grid(), samples_per_segment);
+        let mut violations: Vec<BoundsViolation> = Vec::new();
+        let mut inside = true;
+        for t in times {
+            let excess = self.region.excess(self.inner.evaluate(t));
+            if excess > 0.0 {
+                if inside {
+                    violations.push(BoundsViolation {
+                        begin: t,
+                        end: t,
+                        peak_time: t,
+                        peak: excess,
+                    });
+                }
+                let v = violations.last_mut().unwrap();
+                v.end = t;
+                if excess > v.peak {
+                    v.peak = excess;
+                    v.peak_time = t;
+                }
+                inside = false;
+            } else {
+                inside = true;
+            }
+        }
+        violations
+    }
+}
+
+impl<Inner> Spline<Vec3> for BoundsAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    fn evaluate(&self, t: f32) -> Vec3 {
+        let position = self.inner.evaluate(t);
+        match self.clamping {
+            Clamping::Hard => self.region.project(position),
+            Clamping::Smooth { margin } => self.region.project_smooth(position, margin),
+            Clamping::None => position,
+        }
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    fn line() -> PiecewiseCubicCurve<Vec3> {
+        let zero = Vec3::zeros();
+        PiecewiseCubicCurve::new(
+            [[
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(4.0, 0.0, 0.0),
+                zero,
+                zero,
+            ]],
+            [0.0, 4.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn hard_and_smooth() {
+        let region =
+            ConvexRegion::axis_aligned_box(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(2.0, 1.0, 1.0))
+                .unwrap();
+        let hard = BoundsAdapter::adapt(line(), region.clone(), Clamping::Hard).unwrap();
+        assert_eq!(hard.evaluate(1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(hard.evaluate(3.0), Vec3::new(2.0, 0.0, 0.0));
+        let smooth =
+            BoundsAdapter::adapt(line(), region, Clamping::Smooth { margin: 0.5 }).unwrap();
+        assert_eq!(smooth.evaluate(1.0), Vec3::new(1.0, 0.0, 0.0));
+        let x = smooth.evaluate(2.0).x;
+        assert!(1.5 < x && x < 2.0);
+        assert!(smooth.evaluate(2.5).x > x);
+        let violations = smooth.violations(8);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].begin, 2.5);
+        assert_eq!(violations[0].end, 4.0);
+        assert_eq!(violations[0].peak, 2.0);
+    }
+
+    #[test]
+    fn general_region() {
+        // Wedge: x + y <= 1, x - y <= 1
+        let region = ConvexRegion::new(vec![
+            (Vec3::new(1.0, 1.0, 0.0), 1.0),
+            (Vec3::new(1.0, -1.0, 0.0), 1.0),
+        ])
+        .unwrap();
+        let projected = region.project(Vec3::new(3.0, 0.5, 0.0));
+        assert!((projected - Vec3::new(1.0, 0.0, 0.0)).norm() < 1e-5);
+        assert!(ConvexRegion::new(vec![(Vec3::zeros(), 1.0)]).is_err());
+        assert!(matches!(
+            ConvexRegion::new(vec![(Vec3::new(1.0, 0.0, 0.0), 1.0); MAX_PLANES + 1]),
+            Err(Error::TooManyPlanes { .. })
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+//! Rotation-minimizing frames, using the "double reflection" method.
+//!
+//! Wang, W., Jüttler, B., Zheng, D. and Liu, Y. (2008).
+//! "Computation of rotation minimizing frames".
+//! ACM Transactions on Graphics, 27(1).
+//! <https://doi.org/10.1145/1330511.1330513>
+//!
+//! Like in [`lookat`](crate::lookat), the y axis points forward (along the tangent).
+
+use nalgebra::{Matrix3, Rotation3};
+
+use crate::lookat::{keyframe_spline, look_along, thin_out_keyframes};
+use crate::quaternion::{UnitQuaternion, Vec3};
+use crate::utilities::subdivide_grid;
+use crate::{AsdfRotSpline, SplineWithVelocity};
+
+/// Frames at the given (ascending) times.
+///
+/// The first frame has no roll (i.e. its x axis is horizontal),
+/// unless `initial` is given.
+/// Where the tangent is undefined (e.g. when not moving),
+/// the previous tangent is used.
+#[must_use]
+pub fn rotation_minimizing_frames<S>(
+    spline: &S,
+    times: &[f32],
+    initial: Option<UnitQuaternion>,
+) -> Vec<UnitQuaternion>
+where
+    S: SplineWithVelocity<Vec3, Vec3> + ?Sized,
+{
+    let mut result = Vec::with_capacity(times.len());
+    let first = match times.first() {
+        Some(&t) => t,
+        None => return result,
+    };
+    let mut position = spline.evaluate(first);
+    let mut tangent = spline.evaluate_velocity(first);
+    let frame = match initial {
+        Some(frame) => frame,
+        None => look_along(tangent),
+    };
+    if tangent == Vec3::zeros() {
+        tangent = frame * Vec3::y();
+    }
+    tangent.normalize_mut();
+    let mut up = frame * Vec3::z();
+    result.push(frame);
+    for &t in &times[1..] {
+        let next_position = spline.evaluate(t);
+        let mut next_tangent = spline.evaluate_velocity(t);
+        if next_tangent == Vec3::zeros() {
+            next_tangent = tangent;
+        }
+        next_tangent.normalize_mut();
+        let v1 = next_position - position;
+        let c1 = v1.dot(&v1);
+        let (up_l, tangent_l) = if c1 > 0.0 {
+            (
+                up - v1 * (2.0 / c1 * v1.dot(&up)),
+                tangent - v1 * (2.0 / c1 * v1.dot(&tangent)),
+            )
+        } else {
+            (up, tangent)
+        };
+        let v2 = next_tangent - tangent_l;
+        let c2 = v2.dot(&v2);
+        let mut next_up = if c2 > 0.0 {
+            up_l - v2 * (2.0 / c2 * v2.dot(&up_l))
+        } else {
+            up_l
+        };
+        // Remove accumulated numerical errors
+        next_up -= next_tangent * next_tangent.dot(&next_up);
+        next_up.normalize_mut();
+        let x = next_tangent.cross(&next_up);
+        let matrix = Matrix3::from_columns(&[x, next_tangent, next_up]);
+        result.push(UnitQuaternion::from_rotation_matrix(
+            &Rotation3::from_matrix_unchecked(matrix),
+        ));
+        position = next_position;
+        tangent = next_tangent;
+        up = next_up;
+    }
+    result
+}
+
+/// Creates a rotation spline from rotation-minimizing frames.
+///
+/// Each segment is divided into `samples_per_segment` parts.
+/// Frames that are (nearly) equal to their predecessor are skipped,
+/// constant parts lead to "hold" segments.
+pub fn rotation_minimizing_spline<S>(
+    spline: &S,
+    samples_per_segment: usize,
+    initial: Option<UnitQuaternion>,
+) -> Result<AsdfRotSpline, crate::asdfrotspline::Error>
+where
+    S: SplineWithVelocity<Vec3, Vec3> + ?Sized,
+{
+    let times = subdivide_grid(spline.grid(), samples_per_segment);
+    let frames = rotation_minimizing_frames(spline, &times, initial);
+    let (quaternions, times) = thin_out_keyframes(times.into_iter().zip(frames));
+    keyframe_spline(quaternions, times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{math, PiecewiseCubicCurve, Spline};
+
+    #[test]
+    fn helix() {
+        // One turn of a helix, which has a well-known twist
+        let n = 16;
+        let pitch = 1.0;
+        let positions: Vec<_> = (0..=n)
+            .map(|i| {
+                let phi = i as f32 / n as f32 * std::f32::consts::TAU;
+                Vec3::new(
+                    math::cos(phi),
+                    math::sin(phi),
+                    pitch * phi / std::f32::consts::TAU,
+                )
+            })
+            .collect();
+        let tangents: Vec<_> = (0..n)
+            .flat_map(|i| [i, i + 1])
+            .map(|i| {
+                let phi = i as f32 / n as f32 * std::f32::consts::TAU;
+                Vec3::new(
+                    -math::sin(phi),
+                    math::cos(phi),
+                    pitch / std::f32::consts::TAU,
+                ) * std::f32::consts::TAU
+            })
+            .collect();
+        let grid: Vec<_> = (0..=n).map(|i| i as f32 / n as f32).collect();
+        let curve = PiecewiseCubicCurve::new_hermite(&positions, &tangents, &grid).unwrap();
+        let times = subdivide_grid(curve.grid(), 16);
+        let frames = rotation_minimizing_frames(&curve, &times, None);
+        for (frame, &t) in frames.iter().zip(&times) {
+            let tangent = curve.evaluate_velocity(t).normalize();
+            assert!((frame * Vec3::y() - tangent).norm() < 1e-4);
+        }
+        // Total twist of a helix with radius r and pitch p: 2 pi (1 - cos(atan(2 pi r / p)))
+        let alpha = math::atan2(std::f32::consts::TAU, pitch);
+        let twist = std::f32::consts::TAU * (1.0 - math::cos(alpha));
+        let first = frames.first().unwrap();
+        let last = frames.last().unwrap();
+        // Start and end tangents are equal, the difference is only the twist
+        let angle = first.angle_to(last);
+        assert!((angle - (std::f32::consts::TAU - twist)).abs() < 1e-2);
+
+        let spline = rotation_minimizing_spline(&curve, 4, None).unwrap();
+        assert!(spline.evaluate(1.0).angle_to(last) < 1e-2);
+    }
+
+    #[test]
+    fn straight_then_turn() {
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let x = Vec3::new(-1.0, 0.0, 0.0);
+        let curve = PiecewiseCubicCurve::new_hermite(
+            &[Vec3::zeros(), y * 9.0, y * 10.0 + x],
+            &[y, y, y, x],
+            &[0.0, 9.0, 10.0],
+        )
+        .unwrap();
+        let spline = rotation_minimizing_spline(&curve, 4, None).unwrap();
+        // The rotation must not start before the turn
+        for t in [0.0, 3.0, 6.0, 8.5, 9.0] {
+            assert!(spline.evaluate(t).angle() < 1e-4);
+        }
+        let frames = rotation_minimizing_frames(&curve, &[10.0], None);
+        assert!(spline.evaluate(10.0).angle_to(&frames[0]) < 1e-4);
+    }
+}
@@ -0,0 +1,121 @@
+//! Mapping between spline times and audio sample indices.
+//!
+//! Calculations are done in double precision,
+//! to avoid drifting off by one sample for long durations.
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("sample rate must be a positive number (not {sample_rate:?})")]
+    InvalidSampleRate { sample_rate: f32 },
+    #[error("index {index}: time must be finite (not {time:?})")]
+    InvalidTime { index: usize, time: f32 },
+    #[error("index {index}: time snaps to the same sample ({sample}) as the previous one")]
+    SameSample { index: usize, sample: i64 },
+}
+
+/// Times snapped to the sample grid, see [`SampleGrid::snap_times()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnappedTimes {
+    pub times: Vec<f32>,
+    pub samples: Vec<i64>,
+    /// Largest absolute difference (in seconds) between original and snapped times.
+    pub max_shift: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleGrid {
+    sample_rate: f32,
+}
+
+impl SampleGrid {
+    pub fn new(sample_rate: f32) -> Result<SampleGrid, Error> {
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(sample_rate > 0.0) || sample_rate.is_infinite() {
+            return Err(Error::InvalidSampleRate { sample_rate });
+        }
+        Ok(SampleGrid { sample_rate })
+    }
+
+    #[must_use]
+    pub fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Index of the sample nearest to `t` (with sample 0 at time 0).
+    #[must_use]
+    pub fn nearest_sample(&self, t: f32) -> i64 {
+        (f64::from(t) * f64::from(self.sample_rate)).round() as i64
+    }
+
+    #[must_use]
+    pub fn sample_time(&self, sample: i64) -> f32 {
+        (sample as f64 / f64::from(self.sample_rate)) as f32
+    }
+
+    #[must_use]
+    pub fn snap(&self, t: f32) -> f32 {
+        self.sample_time(self.nearest_sample(t))
+    }
+
+    /// Snaps (e.g. knot) times to the nearest sample.
+    ///
+    /// Times must be strictly ascending after snapping.
+    pub fn snap_times(&self, times: impl AsRef<[f32]>) -> Result<SnappedTimes, Error> {
+        use Error::*;
+        let times = times.as_ref();
+        let mut result = SnappedTimes {
+            times: Vec::with_capacity(times.len()),
+            samples: Vec::with_capacity(times.len()),
+            max_shift: 0.0,
+        };
+        for (index, &time) in times.iter().enumerate() {
+            if !time.is_finite() {
+                return Err(InvalidTime { index, time });
+            }
+            let sample = self.nearest_sample(time);
+            if let Some(&previous) = result.samples.last() {
+                if sample <= previous {
+                    return Err(SameSample { index, sample });
+                }
+            }
+            let snapped = self.sample_time(sample);
+            result.max_shift = result.max_shift.max((snapped - time).abs());
+            result.samples.push(sample);
+            result.times.push(snapped);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_sample() {
+        let grid = SampleGrid::new(48000.0).unwrap();
+        assert_eq!(grid.nearest_sample(0.0), 0);
+        assert_eq!(grid.nearest_sample(1.0), 48000);
+        assert_eq!(grid.nearest_sample(-0.5), -24000);
+        assert_eq!(grid.nearest_sample(3600.0), 172_800_000);
+        assert_eq!(grid.sample_time(24000), 0.5);
+        assert!(SampleGrid::new(0.0).is_err());
+    }
+
+    #[test]
+    fn snap_times() {
+        let grid = SampleGrid::new(10.0).unwrap();
+        let snapped = grid.snap_times([0.0, 0.14, 1.0]).unwrap();
+        assert_eq!(snapped.samples, [0, 1, 10]);
+        assert_eq!(snapped.times, [0.0, 0.1, 1.0]);
+        assert!((snapped.max_shift - 0.04).abs() < 1e-6);
+        assert!(matches!(
+            grid.snap_times([0.0, 0.12, 0.14]),
+            Err(Error::SameSample {
+                index: 2,
+                sample: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+//! Joint interpolation of position and rotation using dual quaternions.
+//!
+//! Between keyframes, poses follow a screw motion (ScLERP),
+//! i.e. a rotation around an axis combined with a translation along that axis.
+//! In contrast to [`AsdfPoseSpline`](crate::AsdfPoseSpline),
+//! the path of the position depends on the rotation.
+
+use nalgebra::UnitDualQuaternion;
+
+use crate::fingerprint::{Fingerprint, FingerprintHasher};
+use crate::quaternion::{Isometry3, Vec3};
+use crate::utilities::{check_grid, GridError};
+use crate::Spline;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least two poses")]
+    LessThanTwoPoses,
+    #[error("length of grid ({grid}) must be {} number of poses ({poses})", if *.closed {
+        "one more than"
+    } else {
+        "the same as"
+    })]
+    GridVsPoses {
+        grid: usize,
+        poses: usize,
+        closed: bool,
+    },
+    #[error("index {index}: rotation by 180 degrees to the next pose is ambiguous")]
+    OppositeRotation { index: usize },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+/// Screw linear interpolation between keyframe poses.
+///
+/// The pose is continuous, but the velocity jumps at the keyframes.
+pub struct ScrewSpline {
+    keyframes: Box<[UnitDualQuaternion<f32>]>,
+    grid: Box<[f32]>,
+}
+
+impl ScrewSpline {
+    /// If `closed`, the first pose is repeated at the end.
+    pub fn new(
+        poses: impl AsRef<[Isometry3]>,
+        grid: impl Into<Box<[f32]>>,
+        closed: bool,
+    ) -> Result<ScrewSpline, Error> {
+        use Error::*;
+        let poses = poses.as_ref();
+        let grid = grid.into();
+        if poses.len() < 2 {
+            return Err(LessThanTwoPoses);
+        }
+        if poses.len() + closed as usize != grid.len() {
+            return Err(GridVsPoses {
+                grid: grid.len(),
+                poses: poses.len(),
+                closed,
+            });
+        }
+        check_grid(&grid)?;
+        let mut keyframes: Vec<_> = poses
+            .iter()
+            .map(UnitDualQuaternion::from_isometry)
+            .collect();
+        if closed {
+            keyframes.push(keyframes[0]);
+        }
+        if let Some(index) = keyframes
+            .windows(2)
+            .position(|w| w[0].try_sclerp(&w[1], 0.5, f32::EPSILON).is_none())
+        {
+            return Err(OppositeRotation { index });
+        }
+        Ok(ScrewSpline {
+            keyframes: keyframes.into(),
+            grid,
+        })
+    }
+
+    /// Including the repeated first pose if `closed`.
+    #[must_use]
+    pub fn keyframes(&self) -> Vec<Isometry3> {
+        self.keyframes.iter().map(|dq| dq.to_isometry()).collect()
+    }
+
+    #[must_use]
+    pub fn evaluate_position(&self, t: f32) -> Vec3 {
+        self.evaluate(t).translation.vector
+    }
+}
+
+impl Spline<Isometry3> for ScrewSpline {
+    fn evaluate(&self, t: f32) -> Isometry3 {
+        let (t, idx) = self.clamp_parameter_and_find_index(t);
+        let t0 = self.grid[idx];
+        let t1 = self.grid[idx + 1];
+        let t = (t - t0) / (t1 - t0);
+        self.keyframes[idx]
+            .sclerp(&self.keyframes[idx + 1], t)
+            .to_isometry()
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+impl Fingerprint for ScrewSpline {
+    fn write_fingerprint(&self, hasher: &mut FingerprintHasher) {
+        self.keyframes.write_fingerprint(hasher);
+        self.grid.write_fingerprint(hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use nalgebra::Translation3;
+
+    use crate::quaternion::angles2quat;
+
+    fn pose(x: f32, y: f32, azim: f32) -> Isometry3 {
+        Isometry3::from_parts(Translation3::new(x, y, 0.0), angles2quat(azim, 0.0, 0.0))
+    }
+
+    #[test]
+    fn screw_motion() {
+        // Rotating around the origin while keeping the distance
+        let s = ScrewSpline::new(
+            [pose(0.0, 1.0, 0.0), pose(-1.0, 0.0, 90.0)],
+            [0.0, 1.0],
+            false,
+        )
+        .unwrap();
+        let middle = s.evaluate(0.5);
+        let expected = pose(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, 45.0);
+        assert!((middle.translation.vector - expected.translation.vector).norm() < 1e-5);
+        assert!(middle.rotation.angle_to(&expected.rotation) < 1e-5);
+        assert!((s.evaluate_position(0.25).norm() - 1.0).abs() < 1e-5);
+        // Pure translation
+        let s = ScrewSpline::new(
+            [
+                pose(0.0, 0.0, 10.0),
+                pose(2.0, 0.0, 10.0),
+                pose(2.0, 2.0, 10.0),
+            ],
+            [0.0, 1.0, 2.0, 3.0],
+            true,
+        )
+        .unwrap();
+        assert!((s.evaluate_position(1.5) - Vec3::new(2.0, 1.0, 0.0)).norm() < 1e-5);
+        assert_eq!(s.keyframes().len(), 4);
+        assert!(matches!(
+            ScrewSpline::new(
+                [pose(0.0, 0.0, 0.0), pose(0.0, 0.0, 180.0)],
+                [0.0, 1.0],
+                false
+            ),
+            Err(Error::OppositeRotation { index: 0 })
+        ));
+    }
+}
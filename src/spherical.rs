@@ -0,0 +1,259 @@
+//! Spherical coordinates, using the same conventions as [`angles2quat()`](crate::quaternion::angles2quat).
+//!
+//! Azimuth is measured (in degrees) counter-clockwise around the z axis,
+//! starting at the y axis.
+//! Elevation is measured (in degrees) upwards from the x/y plane.
+
+use crate::math;
+use crate::quaternion::Vec3;
+use crate::{Spline, SplineWithVelocity};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spherical {
+    pub azimuth: f32,
+    pub elevation: f32,
+    pub distance: f32,
+}
+
+impl Spherical {
+    /// Azimuth is in the range from -180 to 180 degrees.
+    ///
+    /// At the origin, all angles are zero, directly above and below, azimuth is zero.
+    #[must_use]
+    pub fn from_cartesian(position: Vec3) -> Spherical {
+        let horizontal = math::hypot(position.x, position.y);
+        Spherical {
+            azimuth: math::atan2(-position.x, position.y).to_degrees(),
+            elevation: math::atan2(position.z, horizontal).to_degrees(),
+            distance: position.norm(),
+        }
+    }
+
+    #[must_use]
+    pub fn to_cartesian(&self) -> Vec3 {
+        let (sin_azim, cos_azim) = math::sin_cos(self.azimuth.to_radians());
+        let (sin_elev, cos_elev) = math::sin_cos(self.elevation.to_radians());
+        Vec3::new(-sin_azim * cos_elev, cos_azim * cos_elev, sin_elev) * self.distance
+    }
+
+    /// Adds multiples of 360 degrees to the azimuth to get as close as possible to `previous`.
+    ///
+    /// Where the azimuth is undefined (at the origin and directly above and below),
+    /// the previous azimuth is used.
+    /// The same goes for the elevation at the origin.
+    #[must_use]
+    pub fn unwrap(mut self, previous: &Spherical, position: Vec3) -> Spherical {
+        if position.x == 0.0 && position.y == 0.0 {
+            self.azimuth = previous.azimuth;
+            if position.z == 0.0 {
+                self.elevation = previous.elevation;
+            }
+        } else {
+            let turns = ((previous.azimuth - self.azimuth) / 360.0).round();
+            self.azimuth += turns * 360.0;
+        }
+        self
+    }
+}
+
+/// Evaluates a position spline in spherical coordinates relative to an origin.
+pub struct SphericalAdapter<Inner> {
+    inner: Inner,
+    origin: Vec3,
+}
+
+impl<Inner> SphericalAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    pub fn adapt(inner: Inner, origin: Vec3) -> SphericalAdapter<Inner> {
+        SphericalAdapter { inner, origin }
+    }
+
+    #[must_use]
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    /// Like [`evaluate()`](Spline::evaluate), but without jumps in azimuth,
+    /// given the result of the previous time step.
+    #[must_use]
+    pub fn evaluate_unwrapped(&self, t: f32, previous: &Spherical) -> Spherical {
+        let position = self.inner.evaluate(t) - self.origin;
+        Spherical::from_cartesian(position).unwrap(previous, position)
+    }
+
+    /// Evaluates at the given (ascending) times with continuous azimuth.
+    ///
+    /// The times must be close enough so that no azimuth change
+    /// of 180 degrees or more happens from one time to the next.
+    #[must_use]
+    pub fn sample_unwrapped(&self, times: &[f32]) -> Vec<Spherical> {
+        let mut result: Vec<Spherical> = Vec::with_capacity(times.len());
+        for &t in times {
+            let value = match result.last() {
+                Some(previous) => self.evaluate_unwrapped(t, previous),
+                None => self.evaluate(t),
+            };
+            result.push(value);
+        }
+        result
+    }
+}
+
+impl<Inner> Spline<Spherical> for SphericalAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    fn evaluate(&self, t: f32) -> Spherical {
+        Spherical::from_cartesian(self.inner.evaluate(t) - self.origin)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+/// Automatically implemented for all three-dimensional splines with velocity.
+pub trait RadialVelocity: SplineWithVelocity<Vec3, Vec3> {
+    /// Rate of change of the distance to `listener`.
+    ///
+    /// Positive values mean that the distance increases.
+    /// At the listener position, the result is zero.
+    fn radial_velocity(&self, t: f32, listener: Vec3) -> f32 {
+        let direction = self.evaluate(t) - listener;
+        let distance = direction.norm();
+        if distance == 0.0 {
+            0.0
+        } else {
+            self.evaluate_velocity(t).dot(&direction) / distance
+        }
+    }
+}
+
+impl<S> RadialVelocity for S where S: SplineWithVelocity<Vec3, Vec3> + ?Sized {}
+
+/// Automatically implemented for all three-dimensional splines with velocity.
+pub trait Direction: SplineWithVelocity<Vec3, Vec3> {
+    /// Unit vector pointing from `origin` to the position at time `t`.
+    ///
+    /// At the origin, the direction of movement is used instead.
+    /// If not moving either, the result is a zero vector.
+    fn direction(&self, t: f32, origin: Vec3) -> Vec3 {
+        let direction = self.evaluate(t) - origin;
+        let direction = if direction == Vec3::zeros() {
+            self.evaluate_velocity(t)
+        } else {
+            direction
+        };
+        direction.try_normalize(0.0).unwrap_or_else(Vec3::zeros)
+    }
+
+    /// Angular velocity (in radians per second) of [`direction()`](Self::direction).
+    ///
+    /// The axis of rotation is the direction of the result.
+    /// To avoid arbitrarily large values when passing close to the origin,
+    /// distances below `min_distance` are treated as `min_distance`.
+    fn direction_angular_velocity(&self, t: f32, origin: Vec3, min_distance: f32) -> Vec3 {
+        let relative = self.evaluate(t) - origin;
+        let distance = relative.norm();
+        if distance == 0.0 {
+            return Vec3::zeros();
+        }
+        relative.cross(&self.evaluate_velocity(t)) / (distance * distance.max(min_distance))
+    }
+}
+
+impl<S> Direction for S where S: SplineWithVelocity<Vec3, Vec3> + ?Sized {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f32::consts::SQRT_2;
+
+    use crate::quaternion::angles2quat;
+    use crate::PiecewiseCubicCurve;
+
+    #[test]
+    fn same_as_angles2quat() {
+        let s = Spherical {
+            azimuth: 30.0,
+            elevation: 20.0,
+            distance: 2.0,
+        };
+        let rotated = angles2quat(30.0, 20.0, 0.0) * Vec3::new(0.0, 2.0, 0.0);
+        assert!((s.to_cartesian() - rotated).norm() < 1e-6);
+        let back = Spherical::from_cartesian(rotated);
+        assert!((back.azimuth - 30.0).abs() < 1e-4);
+        assert!((back.elevation - 20.0).abs() < 1e-4);
+        assert!((back.distance - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn unwrapping() {
+        // Straight line passing behind the origin (from left to right)
+        let zero = Vec3::zeros();
+        let line = PiecewiseCubicCurve::new(
+            [[
+                Vec3::new(-1.0, -1.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                zero,
+                zero,
+            ]],
+            [0.0, 1.0],
+        )
+        .unwrap();
+        let s = SphericalAdapter::adapt(line, Vec3::new(0.0, 0.0, 0.0));
+        let times: Vec<_> = (0..=10).map(|i| i as f32 / 10.0).collect();
+        let result = s.sample_unwrapped(&times);
+        assert!((result[0].azimuth - 135.0).abs() < 1e-4);
+        assert!((result[10].azimuth - 225.0).abs() < 1e-4);
+        assert!(result.windows(2).all(|w| w[0].azimuth < w[1].azimuth));
+    }
+
+    #[test]
+    fn radial_velocity() {
+        let zero = Vec3::zeros();
+        let line = PiecewiseCubicCurve::new(
+            [[
+                Vec3::new(-1.0, 1.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                zero,
+                zero,
+            ]],
+            [0.0, 1.0],
+        )
+        .unwrap();
+        let listener = Vec3::new(0.0, 0.0, 0.0);
+        assert!((line.radial_velocity(0.0, listener) + SQRT_2).abs() < 1e-6);
+        assert!(line.radial_velocity(0.5, listener).abs() < 1e-6);
+        assert!((line.radial_velocity(1.0, listener) - SQRT_2).abs() < 1e-6);
+        assert_eq!(line.radial_velocity(0.5, Vec3::new(0.0, 1.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn direction() {
+        let zero = Vec3::zeros();
+        let line = PiecewiseCubicCurve::new(
+            [[
+                Vec3::new(-1.0, 1.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                zero,
+                zero,
+            ]],
+            [0.0, 1.0],
+        )
+        .unwrap();
+        assert_eq!(line.direction(0.5, zero), Vec3::new(0.0, 1.0, 0.0));
+        // Passing through the origin
+        let listener = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(line.direction(0.5, listener), Vec3::new(1.0, 0.0, 0.0));
+        // Turning clockwise (seen from above) with 2 radians per second
+        let omega = line.direction_angular_velocity(0.5, zero, 0.1);
+        assert!((omega - Vec3::new(0.0, 0.0, -2.0)).norm() < 1e-6);
+        let omega = line.direction_angular_velocity(0.5, Vec3::new(0.0, 0.9, 0.0), 0.2);
+        assert!((omega - Vec3::new(0.0, 0.0, -10.0)).norm() < 1e-4);
+        assert_eq!(line.direction_angular_velocity(0.5, listener, 0.1), zero);
+    }
+}
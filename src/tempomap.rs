@@ -0,0 +1,158 @@
+//! Conversion between musical beats and seconds.
+
+use crate::utilities::{check_grid, GridError};
+use crate::{MonotoneCubicSpline, Spline, SplineWithVelocity};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least one tempo change point")]
+    NoChangePoints,
+    #[error("number of tempi ({tempi}) must be the same as number of beats ({beats})")]
+    TempiVsBeats { tempi: usize, beats: usize },
+    #[error("index {index}: tempo must be a positive number (not {tempo:?})")]
+    InvalidTempo { index: usize, tempo: f32 },
+    #[error("start time must be finite (not {start:?})")]
+    InvalidStart { start: f32 },
+    #[error(transparent)]
+    FromGridError(#[from] GridError),
+}
+
+/// Tempo changes at given beats, in beats per minute.
+///
+/// Between change points, the tempo changes gradually.
+/// Before the first and after the last change point, the tempo is constant.
+pub struct TempoMap {
+    /// Seconds to beats.
+    inner: MonotoneCubicSpline,
+}
+
+impl TempoMap {
+    /// `start` is the time (in seconds) of the first change point.
+    pub fn new(
+        beats: impl AsRef<[f32]>,
+        tempi: impl AsRef<[f32]>,
+        start: f32,
+    ) -> Result<TempoMap, Error> {
+        use Error::*;
+        let mut beats = beats.as_ref().to_vec();
+        let mut tempi = tempi.as_ref().to_vec();
+        if beats.is_empty() {
+            return Err(NoChangePoints);
+        }
+        if beats.len() != tempi.len() {
+            return Err(TempiVsBeats {
+                tempi: tempi.len(),
+                beats: beats.len(),
+            });
+        }
+        if !start.is_finite() {
+            return Err(InvalidStart { start });
+        }
+        for (index, &tempo) in tempi.iter().enumerate() {
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(tempo > 0.0) || tempo.is_infinite() {
+                return Err(InvalidTempo { index, tempo });
+            }
+        }
+        check_grid(&beats)?;
+        if beats.len() == 1 {
+            beats.push(beats[0] + 1.0);
+            tempi.push(tempi[0]);
+        }
+        let mut times = vec![start];
+        for i in 0..beats.len() - 1 {
+            let average_tempo = (tempi[i] + tempi[i + 1]) / 2.0;
+            times.push(times[i] + (beats[i + 1] - beats[i]) * 60.0 / average_tempo);
+        }
+        let slopes: Vec<_> = tempi.iter().map(|&tempo| Some(tempo / 60.0)).collect();
+        let inner = MonotoneCubicSpline::with_slopes(beats, slopes, times, false).map_err(|e| {
+            use crate::monotonecubicspline::MonotoneError as E1;
+            use crate::monotonecubicspline::MonotoneWithSlopesError as E2;
+            match e {
+                E2::FromMonotoneError(E1::FromGridError(e)) => FromGridError(e),
+                _ => unreachable!(),
+            }
+        })?;
+        Ok(TempoMap { inner })
+    }
+
+    /// Tempo (in beats per minute) at the given time (in seconds).
+    #[must_use]
+    pub fn tempo(&self, seconds: f32) -> f32 {
+        self.inner.inner_ref().evaluate_velocity(seconds) * 60.0
+    }
+
+    #[must_use]
+    pub fn beats(&self, seconds: f32) -> f32 {
+        let curve = self.inner.inner_ref();
+        let first = *curve.grid().first().unwrap();
+        let last = *curve.grid().last().unwrap();
+        let clamped = seconds.max(first).min(last);
+        curve.evaluate(clamped) + (seconds - clamped) * curve.evaluate_velocity(clamped)
+    }
+
+    #[must_use]
+    pub fn seconds(&self, beats: f32) -> f32 {
+        let curve = self.inner.inner_ref();
+        let first = *curve.grid().first().unwrap();
+        let last = *curve.grid().last().unwrap();
+        if beats < curve.evaluate(first) {
+            first + (beats - curve.evaluate(first)) / curve.evaluate_velocity(first)
+        } else if beats > curve.evaluate(last) {
+            last + (beats - curve.evaluate(last)) / curve.evaluate_velocity(last)
+        } else {
+            self.inner
+                .get_time(beats)
+                .expect("beats are strictly ascending")
+        }
+    }
+
+    /// Spline mapping seconds to beats.
+    #[must_use]
+    pub fn inner_ref(&self) -> &MonotoneCubicSpline {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_tempo() {
+        let map = TempoMap::new([0.0], [120.0], 1.0).unwrap();
+        assert_eq!(map.seconds(0.0), 1.0);
+        assert_eq!(map.seconds(4.0), 3.0);
+        assert_eq!(map.seconds(-2.0), 0.0);
+        assert_eq!(map.beats(3.0), 4.0);
+        assert_eq!(map.tempo(5.0), 120.0);
+    }
+
+    #[test]
+    fn accelerando() {
+        let map = TempoMap::new([0.0, 4.0, 8.0], [60.0, 120.0, 120.0], 0.0).unwrap();
+        let four = map.seconds(4.0);
+        assert!((four - 4.0 * 60.0 / 90.0).abs() < 1e-5);
+        assert!((map.tempo(four) - 120.0).abs() < 1e-3);
+        for beats in [0.5, 3.0, 6.0, 10.0] {
+            assert!((map.beats(map.seconds(beats)) - beats).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            TempoMap::new([0.0, 1.0], [60.0, 0.0], 0.0),
+            Err(Error::InvalidTempo { index: 1, .. })
+        ));
+        assert!(matches!(
+            TempoMap::new([1.0, 1.0], [60.0, 60.0], 0.0),
+            Err(Error::FromGridError(_))
+        ));
+        assert!(matches!(
+            TempoMap::new([], [], 0.0),
+            Err(Error::NoChangePoints)
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Spline, Vector};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("there must be at least one section")]
+    NoSections,
+    #[error("index {index}: start time must be finite (not {start:?})")]
+    NonFiniteStart { index: usize, start: f32 },
+    #[error("index {index}: start times must be strictly ascending")]
+    StartNotAscending { index: usize },
+    #[error("index {index}: crossfade duration must be a finite non-negative number (not {crossfade:?})")]
+    InvalidCrossfade { index: usize, crossfade: f32 },
+    #[error("index {index}: crossfade must end before the next section starts")]
+    CrossfadeTooLong { index: usize },
+    #[error("the timeline must not have zero duration")]
+    ZeroDuration,
+}
+
+/// A spline that becomes active at a given time.
+///
+/// The spline is evaluated with the global time (i.e. it is *not* shifted by `start`),
+/// outside of its own grid it keeps its first/last value.
+pub struct TimelineSection<V> {
+    pub start: f32,
+    /// Duration of the linear crossfade from the previous section.
+    pub crossfade: f32,
+    pub spline: Box<dyn Spline<V>>,
+}
+
+impl<V> TimelineSection<V> {
+    pub fn new(start: f32, spline: impl Spline<V> + 'static) -> TimelineSection<V> {
+        TimelineSection {
+            start,
+            crossfade: 0.0,
+            spline: Box::new(spline),
+        }
+    }
+
+    #[must_use]
+    pub fn with_crossfade(mut self, crossfade: f32) -> TimelineSection<V> {
+        self.crossfade = crossfade;
+        self
+    }
+}
+
+/// Sequence of splines, each of them being active until the next one starts.
+///
+/// Before the first start time, the first spline is used.
+pub struct Timeline<V> {
+    sections: Box<[TimelineSection<V>]>,
+    grid: Box<[f32]>,
+}
+
+impl<V: Vector> Timeline<V> {
+    pub fn new(sections: impl Into<Vec<TimelineSection<V>>>) -> Result<Timeline<V>, Error> {
+        use Error::*;
+        let sections = sections.into();
+        if sections.is_empty() {
+            return Err(NoSections);
+        }
+        for (index, section) in sections.iter().enumerate() {
+            let crossfade = section.crossfade;
+            #[allow(clippy::neg_cmp_op_on_partial_ord)]
+            if !(crossfade >= 0.0) || crossfade.is_infinite() || (index == 0 && crossfade != 0.0) {
+                return Err(InvalidCrossfade { index, crossfade });
+            }
+            let start = section.start;
+            if !start.is_finite() {
+                return Err(NonFiniteStart { index, start });
+            }
+            if index > 0 {
+                let previous = &sections[index - 1];
+                #[allow(clippy::neg_cmp_op_on_partial_ord)]
+                if !(previous.start < section.start) {
+                    return Err(StartNotAscending { index });
+                }
+                if previous.start + previous.crossfade > section.start {
+                    return Err(CrossfadeTooLong { index: index - 1 });
+                }
+            }
+        }
+        let mut grid: Vec<_> = sections.iter().map(|s| s.start).collect();
+        let last = sections.last().unwrap();
+        let end = last
+            .spline
+            .grid()
+            .last()
+            .unwrap()
+            .max(last.start + last.crossfade);
+        if end > last.start {
+            grid.push(end);
+        }
+        if grid.len() < 2 {
+            return Err(ZeroDuration);
+        }
+        Ok(Timeline {
+            sections: sections.into(),
+            grid: grid.into(),
+        })
+    }
+
+    #[must_use]
+    pub fn sections(&self) -> &[TimelineSection<V>] {
+        &self.sections
+    }
+}
+
+impl<V: Vector> Spline<V> for Timeline<V> {
+    fn evaluate(&self, t: f32) -> V {
+        let index = self
+            .sections
+            .iter()
+            .rposition(|s| s.start <= t)
+            .unwrap_or(0);
+        let section = &self.sections[index];
+        let value = section.spline.evaluate(t);
+        if index > 0 && t < section.start + section.crossfade {
+            let weight = (t - section.start) / section.crossfade;
+            let previous = self.sections[index - 1].spline.evaluate(t);
+            previous * (1.0 - weight) + value * weight
+        } else {
+            value
+        }
+    }
+
+    fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    fn constant(value: f32, start: f32, end: f32) -> PiecewiseCubicCurve<f32> {
+        PiecewiseCubicCurve::new([[value, 0.0, 0.0, 0.0]], [start, end]).unwrap()
+    }
+
+    #[test]
+    fn hold_and_crossfade() {
+        let timeline = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(2.0, constant(3.0, 2.0, 4.0)).with_crossfade(1.0),
+        ])
+        .unwrap();
+        assert_eq!(timeline.grid(), &[0.0, 2.0, 4.0]);
+        assert_eq!(timeline.evaluate(-1.0), 1.0);
+        assert_eq!(timeline.evaluate(1.5), 1.0); // hold
+        assert_eq!(timeline.evaluate(2.5), 2.0); // crossfade
+        assert_eq!(timeline.evaluate(3.0), 3.0);
+        assert_eq!(timeline.evaluate(5.0), 3.0);
+    }
+
+    #[test]
+    fn errors() {
+        let result = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(0.0, constant(3.0, 2.0, 4.0)),
+        ]);
+        assert!(matches!(result, Err(Error::StartNotAscending { index: 1 })));
+        let result = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)).with_crossfade(1.0)
+        ]);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidCrossfade { index: 0, .. })
+        ));
+        let result = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(1.0, constant(3.0, 2.0, 4.0)).with_crossfade(2.0),
+            TimelineSection::new(2.0, constant(3.0, 2.0, 4.0)),
+        ]);
+        assert!(matches!(result, Err(Error::CrossfadeTooLong { index: 1 })));
+        let result = Timeline::new(vec![
+            TimelineSection::new(f32::NEG_INFINITY, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(1.0, constant(3.0, 2.0, 4.0)),
+        ]);
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteStart { index: 0, .. })
+        ));
+        let result = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(f32::NAN, constant(3.0, 2.0, 4.0)),
+        ]);
+        assert!(matches!(
+            result,
+            Err(Error::NonFiniteStart { index: 1, .. })
+        ));
+        let result = Timeline::new(vec![
+            TimelineSection::new(0.0, constant(1.0, 0.0, 1.0)),
+            TimelineSection::new(1.0, constant(3.0, 2.0, 4.0)).with_crossfade(f32::INFINITY),
+        ]);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidCrossfade { index: 1, .. })
+        ));
+        let result = Timeline::<f32>::new(vec![]);
+        assert!(matches!(result, Err(Error::NoSections)));
+    }
+}
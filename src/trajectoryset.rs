@@ -0,0 +1,207 @@
+use crate::quaternion::{UnitQuaternion, Vec3};
+use crate::Spline;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("scene duration must be a non-negative number (not {duration:?})")]
+    InvalidDuration { duration: f32 },
+    #[error("name {name:?} is already in use")]
+    DuplicateName { name: String },
+    #[error("{name:?}: time range ({start:?} to {end:?}) exceeds scene duration ({duration:?})")]
+    OutOfRange {
+        name: String,
+        start: f32,
+        end: f32,
+        duration: f32,
+    },
+}
+
+/// Values of all splines of a [`TrajectorySet`] at one time instance.
+///
+/// The values are in the order in which the splines were added.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrajectoryFrame {
+    pub positions: Vec<Vec3>,
+    pub rotations: Vec<UnitQuaternion>,
+    pub automations: Vec<f32>,
+}
+
+/// A collection of named splines within a scene of a given duration.
+///
+/// The scene starts at time 0, all splines must be within the scene duration.
+/// Outside of their own time range, splines keep their first/last value.
+pub struct TrajectorySet {
+    duration: f32,
+    positions: Vec<(String, Box<dyn Spline<Vec3>>)>,
+    rotations: Vec<(String, Box<dyn Spline<UnitQuaternion>>)>,
+    automations: Vec<(String, Box<dyn Spline<f32>>)>,
+}
+
+impl TrajectorySet {
+    pub fn new(duration: f32) -> Result<TrajectorySet, Error> {
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(duration >= 0.0) {
+            return Err(Error::InvalidDuration { duration });
+        }
+        Ok(TrajectorySet {
+            duration,
+            positions: Vec::new(),
+            rotations: Vec::new(),
+            automations: Vec::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    fn check<V>(&self, name: &str, spline: &dyn Spline<V>) -> Result<(), Error> {
+        let names = self
+            .positions
+            .iter()
+            .map(|(n, _)| n)
+            .chain(self.rotations.iter().map(|(n, _)| n))
+            .chain(self.automations.iter().map(|(n, _)| n));
+        for n in names {
+            if n == name {
+                return Err(Error::DuplicateName { name: name.into() });
+            }
+        }
+        let start = *spline.grid().first().unwrap();
+        let end = *spline.grid().last().unwrap();
+        if start < 0.0 || end > self.duration {
+            return Err(Error::OutOfRange {
+                name: name.into(),
+                start,
+                end,
+                duration: self.duration,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn add_position(
+        &mut self,
+        name: impl Into<String>,
+        spline: impl Spline<Vec3> + 'static,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.check(&name, &spline)?;
+        self.positions.push((name, Box::new(spline)));
+        Ok(())
+    }
+
+    pub fn add_rotation(
+        &mut self,
+        name: impl Into<String>,
+        spline: impl Spline<UnitQuaternion> + 'static,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.check(&name, &spline)?;
+        self.rotations.push((name, Box::new(spline)));
+        Ok(())
+    }
+
+    pub fn add_automation(
+        &mut self,
+        name: impl Into<String>,
+        spline: impl Spline<f32> + 'static,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.check(&name, &spline)?;
+        self.automations.push((name, Box::new(spline)));
+        Ok(())
+    }
+
+    /// Index into [`TrajectoryFrame::positions`].
+    #[must_use]
+    pub fn position_index(&self, name: &str) -> Option<usize> {
+        self.positions.iter().position(|(n, _)| n == name)
+    }
+
+    /// Index into [`TrajectoryFrame::rotations`].
+    #[must_use]
+    pub fn rotation_index(&self, name: &str) -> Option<usize> {
+        self.rotations.iter().position(|(n, _)| n == name)
+    }
+
+    /// Index into [`TrajectoryFrame::automations`].
+    #[must_use]
+    pub fn automation_index(&self, name: &str) -> Option<usize> {
+        self.automations.iter().position(|(n, _)| n == name)
+    }
+
+    /// Releases the excess capacity after adding all splines.
+    pub fn shrink_to_fit(&mut self) {
+        self.positions.shrink_to_fit();
+        self.rotations.shrink_to_fit();
+        self.automations.shrink_to_fit();
+    }
+
+    #[must_use]
+    pub fn evaluate(&self, t: f32) -> TrajectoryFrame {
+        let mut frame = TrajectoryFrame::default();
+        self.evaluate_into(t, &mut frame);
+        frame
+    }
+
+    /// Re-uses the memory of an existing frame (e.g. from the previous call).
+    pub fn evaluate_into(&self, t: f32, frame: &mut TrajectoryFrame) {
+        frame.positions.clear();
+        frame
+            .positions
+            .extend(self.positions.iter().map(|(_, s)| s.evaluate(t)));
+        frame.rotations.clear();
+        frame
+            .rotations
+            .extend(self.rotations.iter().map(|(_, s)| s.evaluate(t)));
+        frame.automations.clear();
+        frame
+            .automations
+            .extend(self.automations.iter().map(|(_, s)| s.evaluate(t)));
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    use crate::PiecewiseCubicCurve;
+
+    #[test]
+    fn evaluate() {
+        let mut set = TrajectorySet::new(10.0).unwrap();
+        let ramp = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        set.add_automation("gain", ramp).unwrap();
+        let zero = Vec3::zeros();
+        let line =
+            PiecewiseCubicCurve::new([[zero, Vec3::new(4.0, 0.0, 0.0), zero, zero]], [2.0, 6.0])
+                .unwrap();
+        set.add_position("source", line).unwrap();
+        assert_eq!(set.position_index("source"), Some(0));
+        assert_eq!(set.automation_index("source"), None);
+        let frame = set.evaluate(3.0);
+        assert_eq!(frame.positions, [Vec3::new(1.0, 0.0, 0.0)]);
+        assert_eq!(frame.automations, [1.0]);
+        assert!(frame.rotations.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let mut set = TrajectorySet::new(1.0).unwrap();
+        let ramp = || PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 1.0]).unwrap();
+        set.add_automation("gain", ramp()).unwrap();
+        assert!(matches!(
+            set.add_automation("gain", ramp()),
+            Err(Error::DuplicateName { .. })
+        ));
+        let long = PiecewiseCubicCurve::new([[0.0f32, 1.0, 0.0, 0.0]], [0.0, 2.0]).unwrap();
+        assert!(matches!(
+            set.add_automation("long", long),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(TrajectorySet::new(f32::NAN).is_err());
+    }
+}
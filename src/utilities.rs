@@ -1,8 +1,25 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use crate::math;
+
+/// Result of a root finding algorithm.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Root {
+    /// Approximate location of the root.
+    pub x: f32,
+    /// Function value at `x`.
+    pub residual: f32,
+    /// Number of function evaluations.
+    pub calls: usize,
+}
+
 /// <https://en.wikipedia.org/wiki/Bisection_method>
 ///
 /// Root must be within `[xmin, xmax]`, otherwise one of those is returned
 /// (whichever has a function value closer to zero).
-pub fn bisect<F>(f: F, mut xmin: f32, mut xmax: f32, xtol: f32, max_calls: usize) -> f32
+/// Both ends are always evaluated, even if `max_calls` is smaller than two.
+/// When `max_calls` is reached, the better end of the current bracket is returned.
+pub fn bisect<F>(f: F, mut xmin: f32, mut xmax: f32, xtol: f32, max_calls: usize) -> Root
 where
     F: Fn(f32) -> f32,
 {
@@ -11,24 +28,35 @@ where
     let mut fmin = f(xmin);
     calls += 1;
     if fmin == 0.0 {
-        return xmin;
+        return Root {
+            x: xmin,
+            residual: fmin,
+            calls,
+        };
     }
     let mut fmax = f(xmax);
     calls += 1;
     if fmax == 0.0 {
-        return xmax;
+        return Root {
+            x: xmax,
+            residual: fmax,
+            calls,
+        };
     }
-    assert!(max_calls >= calls);
     if fmin * fmax < 0.0 {
-        while (max_calls - calls) > 0 && (xmax - xmin) > xtol {
+        while calls < max_calls && (xmax - xmin) > xtol {
             let xmid = (xmin + xmax) / 2.0;
             if xmid <= xmin || xmid >= xmax {
-                return xmid;
+                break;
             }
             let fmid = f(xmid);
             calls += 1;
             if fmid == 0.0 {
-                return xmid;
+                return Root {
+                    x: xmid,
+                    residual: fmid,
+                    calls,
+                };
             }
             if fmin * fmid < 0.0 {
                 xmax = xmid;
@@ -39,13 +67,433 @@ where
             }
         }
     }
-    if fmin.abs() < fmax.abs() {
-        xmin
-    } else {
-        xmax
+    closer_to_zero((xmin, fmin), (xmax, fmax), calls)
+}
+
+/// Whichever of two evaluated points has a function value closer to zero.
+fn closer_to_zero(a: (f32, f32), b: (f32, f32), calls: usize) -> Root {
+    let (x, residual) = if a.1.abs() < b.1.abs() { a } else { b };
+    Root { x, residual, calls }
+}
+
+/// <https://en.wikipedia.org/wiki/Brent%27s_method>
+///
+/// Combines inverse quadratic interpolation, the secant method and bisection.
+/// The requirements and the behavior if there is no sign change
+/// are the same as in [`bisect()`].
+pub fn brent<F>(f: F, xmin: f32, xmax: f32, xtol: f32, max_calls: usize) -> Root
+where
+    F: Fn(f32) -> f32,
+{
+    assert!(xmin <= xmax);
+    let mut calls: usize = 0;
+    let (mut a, mut fa) = (xmin, f(xmin));
+    calls += 1;
+    if fa == 0.0 {
+        return Root {
+            x: a,
+            residual: fa,
+            calls,
+        };
+    }
+    let (mut b, mut fb) = (xmax, f(xmax));
+    calls += 1;
+    if fb == 0.0 {
+        return Root {
+            x: b,
+            residual: fb,
+            calls,
+        };
+    }
+    if fa * fb > 0.0 || calls >= max_calls {
+        return closer_to_zero((a, fa), (b, fb), calls);
+    }
+    let (mut c, mut fc) = (a, fa);
+    let mut d = b - a;
+    let mut e = d;
+    while calls < max_calls {
+        if fb * fc > 0.0 {
+            // Root is between a and b
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            // b is always the best guess so far
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+        let tol = 2.0 * f32::EPSILON * b.abs() + 0.5 * xtol;
+        let m = 0.5 * (c - b);
+        if m.abs() <= tol || fb == 0.0 {
+            return Root {
+                x: b,
+                residual: fb,
+                calls,
+            };
+        }
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            #[allow(clippy::float_cmp)]
+            let (mut p, mut q) = if a == c {
+                // Secant method
+                (2.0 * m * s, 1.0 - s)
+            } else {
+                // Inverse quadratic interpolation
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (2.0 * m * q * (q - r) - (b - a) * (r - 1.0)),
+                    (q - 1.0) * (r - 1.0) * (s - 1.0),
+                )
+            };
+            if p > 0.0 {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            if 2.0 * p < (3.0 * m * q - (tol * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                // Interpolation failed, use bisection
+                d = m;
+                e = m;
+            }
+        } else {
+            // Bounds decreasing too slowly, use bisection
+            d = m;
+            e = m;
+        }
+        a = b;
+        fa = fb;
+        b += if d.abs() > tol { d } else { tol.copysign(m) };
+        fb = f(b);
+        calls += 1;
+    }
+    closer_to_zero((a, fa), (b, fb), calls)
+}
+
+/// <https://en.wikipedia.org/wiki/ITP_method>
+///
+/// Interpolate, truncate and project, with hyperparameters
+/// `k1 = 0.2 / (xmax - xmin)`, `k2 = 2` and `n0 = 1`.
+/// It needs at most one more function call than [`bisect()`],
+/// but typically converges much faster.
+/// Only basic arithmetic is used, which makes the results reproducible across platforms.
+///
+/// The requirements and the behavior if there is no sign change
+/// are the same as in [`bisect()`].
+pub fn itp<F>(f: F, xmin: f32, xmax: f32, xtol: f32, max_calls: usize) -> Root
+where
+    F: Fn(f32) -> f32,
+{
+    assert!(xmin <= xmax);
+    let mut calls: usize = 0;
+    let (mut a, fa) = (xmin, f(xmin));
+    calls += 1;
+    if fa == 0.0 {
+        return Root {
+            x: a,
+            residual: fa,
+            calls,
+        };
+    }
+    let (mut b, fb) = (xmax, f(xmax));
+    calls += 1;
+    if fb == 0.0 {
+        return Root {
+            x: b,
+            residual: fb,
+            calls,
+        };
+    }
+    if fa * fb > 0.0 {
+        return closer_to_zero((a, fa), (b, fb), calls);
+    }
+    // Make the function increasing
+    let sign = if fa < 0.0 { 1.0 } else { -1.0 };
+    let (mut ya, mut yb) = (fa * sign, fb * sign);
+    let epsilon = xtol / 2.0;
+    let k1 = 0.2 / (b - a);
+    let mut n_half = 0;
+    let mut width = b - a;
+    while width > 2.0 * epsilon && n_half < 100 {
+        width /= 2.0;
+        n_half += 1;
+    }
+    let n_max = n_half + 1;
+    let mut j = 0;
+    while b - a > 2.0 * epsilon && calls < max_calls {
+        let x_half = (a + b) / 2.0;
+        let r = epsilon * 2.0f32.powi(n_max - j) - (b - a) / 2.0;
+        let delta = k1 * (b - a) * (b - a);
+        // Interpolation (regula falsi)
+        let x_f = (yb * a - ya * b) / (yb - ya);
+        let sigma = if x_half < x_f { -1.0 } else { 1.0 };
+        // Truncation
+        let x_t = if delta <= (x_half - x_f).abs() {
+            x_f + sigma * delta
+        } else {
+            x_half
+        };
+        // Projection
+        let x_itp = if (x_t - x_half).abs() <= r {
+            x_t
+        } else {
+            x_half - sigma * r
+        };
+        // Rounding errors might lead to a value outside of the bracket
+        let x_itp = if a < x_itp && x_itp < b {
+            x_itp
+        } else if a < x_half && x_half < b {
+            x_half
+        } else {
+            break;
+        };
+        let y = f(x_itp) * sign;
+        calls += 1;
+        if y > 0.0 {
+            b = x_itp;
+            yb = y;
+        } else if y < 0.0 {
+            a = x_itp;
+            ya = y;
+        } else {
+            return Root {
+                x: x_itp,
+                residual: 0.0,
+                calls,
+            };
+        }
+        j += 1;
+    }
+    closer_to_zero((a, ya * sign), (b, yb * sign), calls)
+}
+
+/// <https://en.wikipedia.org/wiki/Newton%27s_method>, safeguarded by bisection.
+///
+/// `f` returns the function value and its derivative.
+/// It must be non-positive at `xmin` and non-negative at `xmax`
+/// (which is not checked and those values are never evaluated).
+/// Whenever a Newton step would leave the current bracket,
+/// a bisection step is taken instead.
+/// The function is evaluated at least once, even if `max_calls` is zero.
+pub fn newton_bisect<F>(
+    f: F,
+    x0: f32,
+    mut xmin: f32,
+    mut xmax: f32,
+    xtol: f32,
+    max_calls: usize,
+) -> Root
+where
+    F: Fn(f32) -> (f32, f32),
+{
+    assert!(xmin <= xmax);
+    let mut x = x0.clamp(xmin, xmax);
+    let mut calls: usize = 0;
+    loop {
+        let (fx, dfx) = f(x);
+        calls += 1;
+        let root = Root {
+            x,
+            residual: fx,
+            calls,
+        };
+        if fx == 0.0 || calls >= max_calls {
+            return root;
+        }
+        if fx < 0.0 {
+            xmin = x;
+        } else {
+            xmax = x;
+        }
+        let step = x - fx / dfx;
+        let next = if xmin < step && step < xmax {
+            step
+        } else {
+            (xmin + xmax) / 2.0
+        };
+        if (next - x).abs() <= xtol || xmax - xmin <= xtol {
+            return root;
+        }
+        x = next;
+    }
+}
+
+/// Root finding algorithm, see [`SolverOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootFinder {
+    /// See [`bisect()`].
+    Bisection,
+    /// See [`brent()`].
+    Brent,
+    /// See [`itp()`].
+    Itp,
+    /// See [`newton_bisect()`].
+    Newton,
+}
+
+/// Tolerance of the spline parameter, see [`SolverOptions`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// Relative to the parameter range of the segment,
+    /// which works the same for very short and very long segments.
+    Relative(f32),
+    /// The same for all segments.
+    Absolute(f32),
+}
+
+impl Tolerance {
+    /// Absolute tolerance within the parameter range from `t0` to `t1`.
+    #[must_use]
+    pub fn absolute(self, t0: f32, t1: f32) -> f32 {
+        match self {
+            Tolerance::Relative(tolerance) => tolerance * (t1 - t0),
+            Tolerance::Absolute(tolerance) => tolerance,
+        }
+    }
+}
+
+/// Numerical settings, e.g. for inverting the arc length function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverOptions {
+    pub root_finder: RootFinder,
+    /// Tolerance of the spline parameter.
+    pub accuracy: Tolerance,
+    /// Maximum number of function evaluations per root.
+    pub max_calls: usize,
+    /// Integration method for arc lengths.
+    pub quadrature: Quadrature,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SolverOptionsError {
+    #[error("maximum number of calls must be at least {minimum} (not {max_calls})")]
+    TooFewCalls { max_calls: usize, minimum: usize },
+    #[error("accuracy must be a positive number (not {accuracy:?})")]
+    InvalidAccuracy { accuracy: f32 },
+}
+
+impl SolverOptions {
+    /// Smallest allowed value of [`max_calls`](Self::max_calls).
+    ///
+    /// Bracketing methods need two calls for the ends of the search range.
+    pub const MIN_CALLS: usize = 3;
+
+    pub fn validate(&self) -> Result<(), SolverOptionsError> {
+        use SolverOptionsError::*;
+        if self.max_calls < Self::MIN_CALLS {
+            return Err(TooFewCalls {
+                max_calls: self.max_calls,
+                minimum: Self::MIN_CALLS,
+            });
+        }
+        let accuracy = match self.accuracy {
+            Tolerance::Relative(accuracy) | Tolerance::Absolute(accuracy) => accuracy,
+        };
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(accuracy > 0.0) || accuracy.is_infinite() {
+            return Err(InvalidAccuracy { accuracy });
+        }
+        Ok(())
+    }
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        SolverOptions {
+            root_finder: RootFinder::Newton,
+            accuracy: Tolerance::Relative(1e-5),
+            max_calls: 50,
+            quadrature: Quadrature::default(),
+        }
+    }
+}
+
+/// Aggregated information about multiple [`Root`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SolverStatistics {
+    /// Number of roots that have been searched for.
+    pub roots: usize,
+    /// Total number of function evaluations.
+    pub calls: usize,
+    /// Largest number of function evaluations for a single root.
+    pub max_calls: usize,
+    /// Largest absolute residual.
+    pub max_residual: f32,
+}
+
+/// Thread-safe accumulator for [`SolverStatistics`].
+#[derive(Default)]
+pub(crate) struct SolverCounters {
+    roots: AtomicUsize,
+    calls: AtomicUsize,
+    max_calls: AtomicUsize,
+    /// Bit pattern of a non-negative `f32`, those are ordered like the values
+    max_residual: AtomicU32,
+}
+
+impl SolverCounters {
+    pub(crate) fn record(&self, root: &Root) {
+        self.roots.fetch_add(1, Ordering::Relaxed);
+        self.calls.fetch_add(root.calls, Ordering::Relaxed);
+        self.max_calls.fetch_max(root.calls, Ordering::Relaxed);
+        self.max_residual
+            .fetch_max(root.residual.abs().to_bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn statistics(&self) -> SolverStatistics {
+        SolverStatistics {
+            roots: self.roots.load(Ordering::Relaxed),
+            calls: self.calls.load(Ordering::Relaxed),
+            max_calls: self.max_calls.load(Ordering::Relaxed),
+            max_residual: f32::from_bits(self.max_residual.load(Ordering::Relaxed)),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.roots.store(0, Ordering::Relaxed);
+        self.calls.store(0, Ordering::Relaxed);
+        self.max_calls.store(0, Ordering::Relaxed);
+        self.max_residual.store(0, Ordering::Relaxed);
+    }
+}
+
+/// <https://en.wikipedia.org/wiki/Golden-section_search>
+///
+/// Returns the location of the maximum of a unimodal function within `[xmin, xmax]`.
+pub(crate) fn golden_section_maximum<F>(f: F, mut xmin: f32, mut xmax: f32, xtol: f32) -> f32
+where
+    F: Fn(f32) -> f32,
+{
+    let ratio = (math::sqrt(5.0) - 1.0) / 2.0;
+    let mut x1 = xmax - ratio * (xmax - xmin);
+    let mut x2 = xmin + ratio * (xmax - xmin);
+    let mut f1 = f(x1);
+    let mut f2 = f(x2);
+    while xmax - xmin > xtol {
+        if f1 < f2 {
+            xmin = x1;
+            x1 = x2;
+            f1 = f2;
+            x2 = xmin + ratio * (xmax - xmin);
+            f2 = f(x2);
+        } else {
+            xmax = x2;
+            x2 = x1;
+            f2 = f1;
+            x1 = xmax - ratio * (xmax - xmin);
+            f1 = f(x1);
+        }
     }
-    // TODO: return number of calls?
-    // TODO: return function value that's supposedly zero?
+    (xmin + xmax) / 2.0
 }
 
 /// Gauss-Legendre quadrature of order 13.
@@ -93,6 +541,121 @@ where
         0.0921214998377276,
         0.04048400476531615,
     ];
+    gauss_legendre(f, a, b, &times, &weights)
+}
+
+/// Like [`gauss_legendre13()`], but of order 5.
+pub fn gauss_legendre5<F>(f: F, a: f32, b: f32) -> f32
+where
+    F: Fn(f32) -> f32,
+{
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let times = [
+        -0.906179845938664,
+        -0.5384693101056831,
+        0.0,
+        0.5384693101056831,
+        0.906179845938664,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let weights = [
+        0.236926885056189,
+        0.47862867049936647,
+        0.5688888888888889,
+        0.47862867049936647,
+        0.236926885056189,
+    ];
+    gauss_legendre(f, a, b, &times, &weights)
+}
+
+/// Like [`gauss_legendre13()`], but of order 7.
+pub fn gauss_legendre7<F>(f: F, a: f32, b: f32) -> f32
+where
+    F: Fn(f32) -> f32,
+{
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let times = [
+        -0.9491079123427585,
+        -0.7415311855993945,
+        -0.4058451513773972,
+        0.0,
+        0.4058451513773972,
+        0.7415311855993945,
+        0.9491079123427585,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let weights = [
+        0.12948496616886968,
+        0.2797053914892767,
+        0.38183005050511903,
+        0.4179591836734694,
+        0.38183005050511903,
+        0.2797053914892767,
+        0.12948496616886968,
+    ];
+    gauss_legendre(f, a, b, &times, &weights)
+}
+
+/// Like [`gauss_legendre13()`], but of order 21.
+pub fn gauss_legendre21<F>(f: F, a: f32, b: f32) -> f32
+where
+    F: Fn(f32) -> f32,
+{
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let times = [
+        -0.9937521706203895,
+        -0.9672268385663063,
+        -0.9200993341504008,
+        -0.8533633645833173,
+        -0.7684399634756779,
+        -0.6671388041974123,
+        -0.5516188358872198,
+        -0.4243421202074388,
+        -0.2880213168024011,
+        -0.1455618541608951,
+        0.0,
+        0.1455618541608951,
+        0.2880213168024011,
+        0.4243421202074388,
+        0.5516188358872198,
+        0.6671388041974123,
+        0.7684399634756779,
+        0.8533633645833173,
+        0.9200993341504008,
+        0.9672268385663063,
+        0.9937521706203895,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let weights = [
+        0.016017228257774487,
+        0.0369537897708525,
+        0.05713442542685717,
+        0.07610011362837928,
+        0.0934444234560338,
+        0.1087972991671485,
+        0.12183141605372846,
+        0.13226893863333744,
+        0.13988739479107304,
+        0.14452440398997,
+        0.14608113364969041,
+        0.14452440398997,
+        0.13988739479107304,
+        0.13226893863333744,
+        0.12183141605372846,
+        0.1087972991671485,
+        0.0934444234560338,
+        0.07610011362837928,
+        0.05713442542685717,
+        0.0369537897708525,
+        0.016017228257774487,
+    ];
+    gauss_legendre(f, a, b, &times, &weights)
+}
+
+fn gauss_legendre<F>(f: F, a: f32, b: f32, times: &[f32], weights: &[f32]) -> f32
+where
+    F: Fn(f32) -> f32,
+{
     assert_eq!(times.len(), weights.len());
     let sum = (0..times.len())
         .map(|i| weights[i] * f((b - a) * times[i] / 2.0 + (a + b) / 2.0))
@@ -100,6 +663,229 @@ where
     (b - a) * sum / 2.0
 }
 
+/// Gauss-Kronrod quadrature with 15 points (and the embedded 7-point Gauss rule).
+///
+/// <https://en.wikipedia.org/wiki/Gauss%E2%80%93Kronrod_quadrature_formula>
+///
+/// Returns the integral and an error estimate (computed like in QUADPACK).
+/// Nodes and weights were taken from QUADPACK's `qk15`.
+pub fn gauss_kronrod15<F>(f: F, a: f32, b: f32) -> (f32, f32)
+where
+    F: Fn(f32) -> f32,
+{
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let nodes = [
+        0.991455371120812639206854697526329,
+        0.949107912342758524526189684047851,
+        0.864864423359769072789712788640926,
+        0.741531185599394439863864773280788,
+        0.586087235467691130294144845693013,
+        0.405845151377397166906606412076961,
+        0.207784955007898467600689403773245,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let kronrod_weights = [
+        0.022935322010529224963732008058970,
+        0.063092092629978553290700663189204,
+        0.104790010322250183839876322541518,
+        0.140653259715525918745189590510238,
+        0.169004726639267902826583426598550,
+        0.190350578064785409913256402421014,
+        0.204432940075298892414161999234649,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let kronrod_center = 0.209482141084727828012999174891714;
+    // The Gauss nodes are the ones with odd indices (and the center)
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let gauss_weights = [
+        0.129484966168869693270611432679082,
+        0.279705391489276667901467771423780,
+        0.381830050505118944950369775488975,
+    ];
+    #[allow(clippy::unreadable_literal, clippy::excessive_precision)]
+    let gauss_center = 0.417959183673469387755102040816327;
+
+    let center = (a + b) / 2.0;
+    let half_length = (b - a) / 2.0;
+    // Function values in ascending order of their arguments
+    let mut values = [0.0; 15];
+    values[7] = f(center);
+    for (i, x) in nodes.iter().enumerate() {
+        values[i] = f(center - half_length * x);
+        values[14 - i] = f(center + half_length * x);
+    }
+    let f_center = values[7];
+    let pairs = || (0..7).map(|i| (values[i], values[14 - i]));
+    let mut kronrod = kronrod_center * f_center;
+    let mut gauss = gauss_center * f_center;
+    let mut absolute = kronrod_center * f_center.abs();
+    for (i, (f1, f2)) in pairs().enumerate() {
+        kronrod += kronrod_weights[i] * (f1 + f2);
+        absolute += kronrod_weights[i] * (f1.abs() + f2.abs());
+        if i % 2 == 1 {
+            gauss += gauss_weights[i / 2] * (f1 + f2);
+        }
+    }
+    let mean = kronrod / 2.0;
+    let mut deviation = kronrod_center * (f_center - mean).abs();
+    for (i, (f1, f2)) in pairs().enumerate() {
+        deviation += kronrod_weights[i] * ((f1 - mean).abs() + (f2 - mean).abs());
+    }
+    let half_length = half_length.abs();
+    let value = kronrod * half_length;
+    let deviation = deviation * half_length;
+    let mut error = ((kronrod - gauss) * half_length).abs();
+    if deviation != 0.0 && error != 0.0 {
+        error = deviation * math::powf(200.0 * error / deviation, 1.5).min(1.0);
+    }
+    let roundoff = 50.0 * f32::EPSILON * absolute * half_length;
+    (value, error.max(roundoff))
+}
+
+/// Upper limit for the number of intervals in [`adaptive_gauss_kronrod()`].
+pub const MAX_INTERVALS: usize = 64;
+
+/// Adaptive quadrature based on [`gauss_kronrod15()`].
+///
+/// The interval with the largest error estimate is bisected repeatedly,
+/// until the total error estimate is at most `tolerance` (relative to the integral)
+/// or the number of intervals reaches `max_intervals`
+/// (which is limited to [`MAX_INTERVALS`]).
+/// If the first pass is accurate enough, no subdivision happens at all.
+/// Because of rounding errors, the `tolerance` is at least `50 * f32::EPSILON`.
+/// This doesn't allocate.
+///
+/// Returns the integral and the total error estimate.
+pub fn adaptive_gauss_kronrod<F>(
+    f: F,
+    a: f32,
+    b: f32,
+    tolerance: f32,
+    max_intervals: usize,
+) -> (f32, f32)
+where
+    F: Fn(f32) -> f32,
+{
+    let tolerance = tolerance.max(50.0 * f32::EPSILON);
+    let max_intervals = max_intervals.min(MAX_INTERVALS);
+    let (mut value, mut error) = gauss_kronrod15(&f, a, b);
+    // (start, end, value, error)
+    let mut storage = [(0.0, 0.0, 0.0, 0.0); MAX_INTERVALS];
+    storage[0] = (a, b, value, error);
+    let mut len = 1;
+    while error > tolerance * value.abs() && len < max_intervals {
+        let intervals = &mut storage[..len];
+        let (idx, _) =
+            intervals
+                .iter()
+                .enumerate()
+                .fold((0, -1.0), |(best, largest), (i, interval)| {
+                    if interval.3 > largest {
+                        (i, interval.3)
+                    } else {
+                        (best, largest)
+                    }
+                });
+        let (start, end, _, _) = intervals[idx];
+        let middle = (start + end) / 2.0;
+        if middle <= start || middle >= end {
+            // Interval can't be split anymore
+            break;
+        }
+        let (left_value, left_error) = gauss_kronrod15(&f, start, middle);
+        let (right_value, right_error) = gauss_kronrod15(&f, middle, end);
+        intervals[idx] = (start, middle, left_value, left_error);
+        storage[len] = (middle, end, right_value, right_error);
+        len += 1;
+        let intervals = &storage[..len];
+        value = intervals.iter().map(|i| i.2).sum();
+        error = intervals.iter().map(|i| i.3).sum();
+    }
+    (value, error)
+}
+
+/// Upper limit for the number of levels in [`romberg()`].
+pub const MAX_LEVELS: usize = 20;
+
+/// <https://en.wikipedia.org/wiki/Romberg%27s_method>
+///
+/// The trapezoidal rule is refined (by halving the step size)
+/// and extrapolated, until two consecutive diagonal elements of the Romberg table
+/// differ by at most `tolerance` (relative to the integral),
+/// but at most `max_levels` times (which is limited to [`MAX_LEVELS`]).
+/// At least 4 levels are used, to avoid premature convergence.
+/// This doesn't allocate.
+///
+/// Returns the integral and the last difference.
+pub fn romberg<F>(f: F, a: f32, b: f32, tolerance: f32, max_levels: usize) -> (f32, f32)
+where
+    F: Fn(f32) -> f32,
+{
+    let mut previous = [0.0; MAX_LEVELS];
+    let mut current = [0.0; MAX_LEVELS];
+    previous[0] = (b - a) * (f(a) + f(b)) / 2.0;
+    let mut last = 0;
+    let mut difference = f32::INFINITY;
+    for level in 1..max_levels.clamp(4, MAX_LEVELS) {
+        let steps = 1_usize << level;
+        let h = (b - a) / steps as f32;
+        // Only the new (odd) points have to be evaluated
+        let sum: f32 = (1..steps).step_by(2).map(|k| f(a + k as f32 * h)).sum();
+        current[0] = previous[0] / 2.0 + h * sum;
+        let mut factor = 1.0;
+        for j in 1..=level {
+            factor *= 4.0;
+            current[j] = current[j - 1] + (current[j - 1] - previous[j - 1]) / (factor - 1.0);
+        }
+        difference = (current[level] - previous[level - 1]).abs();
+        std::mem::swap(&mut previous, &mut current);
+        last = level;
+        if level >= 3 && difference <= tolerance * previous[level].abs() {
+            break;
+        }
+    }
+    (previous[last], difference)
+}
+
+/// Numerical integration method, see [`SolverOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quadrature {
+    /// See [`gauss_legendre5()`], enough for nearly straight segments.
+    GaussLegendre5,
+    /// See [`gauss_legendre7()`].
+    GaussLegendre7,
+    /// See [`gauss_legendre13()`], this is the default.
+    GaussLegendre13,
+    /// See [`gauss_legendre21()`].
+    GaussLegendre21,
+    /// See [`adaptive_gauss_kronrod()`], with a relative tolerance of `1e-5`.
+    AdaptiveGaussKronrod,
+    /// See [`romberg()`], with a relative tolerance of `1e-5` and at most 12 levels.
+    Romberg,
+}
+
+impl Quadrature {
+    pub fn integrate<F>(self, f: F, a: f32, b: f32) -> f32
+    where
+        F: Fn(f32) -> f32,
+    {
+        match self {
+            Quadrature::GaussLegendre5 => gauss_legendre5(f, a, b),
+            Quadrature::GaussLegendre7 => gauss_legendre7(f, a, b),
+            Quadrature::GaussLegendre13 => gauss_legendre13(f, a, b),
+            Quadrature::GaussLegendre21 => gauss_legendre21(f, a, b),
+            Quadrature::AdaptiveGaussKronrod => adaptive_gauss_kronrod(f, a, b, 1e-5, 32).0,
+            Quadrature::Romberg => romberg(f, a, b, 1e-5, 12).0,
+        }
+    }
+}
+
+impl Default for Quadrature {
+    fn default() -> Self {
+        Quadrature::GaussLegendre13
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum GridError {
     #[error("index {index}: NaN values are not allowed in grid")]
@@ -109,12 +895,153 @@ pub enum GridError {
 }
 
 pub fn check_grid(grid: &[f32]) -> Result<(), GridError> {
+    check_grid_with_jumps(grid, &[])
+}
+
+/// Like [`check_grid()`], but values at the given `jumps` indices
+/// may be equal to their successor.
+///
+/// Jumps are not allowed at the first and last segment.
+pub fn check_grid_with_jumps(grid: &[f32], jumps: &[usize]) -> Result<(), GridError> {
     use GridError::*;
     if let Some(index) = grid.iter().copied().position(f32::is_nan) {
         return Err(GridNan { index });
     }
-    if let Some(index) = grid.windows(2).position(|w| w[0] >= w[1]) {
+    let allowed = |i: usize| 0 < i && i + 2 < grid.len() && jumps.contains(&i);
+    if let Some(index) = grid
+        .windows(2)
+        .enumerate()
+        .position(|(i, w)| w[0] > w[1] || (w[0] == w[1] && !allowed(i)))
+    {
         return Err(GridNotAscending { index: index + 1 });
     }
     Ok(())
 }
+
+/// Divides each interval of `grid` into `n` parts (at least one).
+pub(crate) fn subdivide_grid(grid: &[f32], n: usize) -> Vec<f32> {
+    let n = n.max(1);
+    let mut result = Vec::with_capacity((grid.len() - 1) * n + 1);
+    result.push(grid[0]);
+    for ts in grid.windows(2) {
+        if let [t0, t1] = *ts {
+            result.extend((1..=n).map(|k| t0 + (t1 - t0) * k as f32 / n as f32));
+        } else {
+            unreachable!();
+        }
+    }
+    result
+}
+
+/// Uniform time buckets for finding the segment that contains a given time.
+///
+/// Each bucket stores the index of the segment containing its start time,
+/// the binary search is only done between this segment and the one of the next bucket.
+#[derive(Debug, Clone)]
+pub(crate) struct BucketIndex {
+    start: f32,
+    scale: f32,
+    segments: Box<[usize]>,
+}
+
+impl BucketIndex {
+    /// `grid` must be valid (see [`check_grid_with_jumps()`]) and have at least two values.
+    pub(crate) fn new(grid: &[f32], buckets: usize) -> BucketIndex {
+        let buckets = buckets.max(1);
+        let start = grid[0];
+        let duration = grid[grid.len() - 1] - start;
+        let segments = (0..=buckets)
+            .map(|b| {
+                let t = start + duration * b as f32 / buckets as f32;
+                grid.partition_point(|&x| x <= t)
+                    .saturating_sub(1)
+                    .min(grid.len() - 2)
+            })
+            .collect();
+        BucketIndex {
+            start,
+            scale: buckets as f32 / duration,
+            segments,
+        }
+    }
+
+    /// Number of bytes allocated on the heap.
+    pub(crate) fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(&*self.segments)
+    }
+
+    /// Like [`Spline::clamp_parameter_and_find_index()`](crate::Spline::clamp_parameter_and_find_index),
+    /// but only for `t` within the first and last grid value.
+    ///
+    /// The same `grid` as in [`new()`](Self::new) must be used.
+    pub(crate) fn find_index(&self, grid: &[f32], t: f32) -> usize {
+        let buckets = self.segments.len() - 1;
+        let bucket = (((t - self.start) * self.scale) as usize).min(buckets - 1);
+        let (lo, hi) = (self.segments[bucket], self.segments[bucket + 1]);
+        let found = grid[lo..=hi + 1].partition_point(|&x| x <= t);
+        if found > 0 && lo + found < grid.len() && t < grid[lo + found] {
+            lo + found - 1
+        } else {
+            // Rounding errors near bucket boundaries
+            grid.partition_point(|&x| x <= t) - 1
+        }
+    }
+}
+
+/// Evaluates a polynomial, `coefficients` start with the constant term.
+pub(crate) fn evaluate_polynomial(coefficients: &[f32], x: f32) -> f32 {
+    coefficients.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+}
+
+/// Real roots of a polynomial within `[a, b]`, in ascending order.
+///
+/// `coefficients` start with the constant term.
+/// Roots are isolated between the roots of the derivative and refined by bisection.
+/// Roots of even multiplicity (where the sign doesn't change) are only found
+/// if the function value is exactly zero.
+pub(crate) fn polynomial_roots(coefficients: &[f32], a: f32, b: f32) -> Vec<f32> {
+    let mut coefficients = coefficients;
+    while coefficients.last() == Some(&0.0) {
+        coefficients = &coefficients[..coefficients.len() - 1];
+    }
+    match *coefficients {
+        [] | [_] => return Vec::new(),
+        [c0, c1] => {
+            let x = -c0 / c1;
+            return if a <= x && x <= b {
+                vec![x]
+            } else {
+                Vec::new()
+            };
+        }
+        _ => {}
+    }
+    let derivative: Vec<_> = coefficients
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, c)| c * i as f32)
+        .collect();
+    let mut bounds = vec![a];
+    bounds.extend(polynomial_roots(&derivative, a, b));
+    bounds.push(b);
+    let f = |x| evaluate_polynomial(coefficients, x);
+    let mut roots = Vec::new();
+    let mut push = |x: f32| {
+        if roots.last() != Some(&x) {
+            roots.push(x);
+        }
+    };
+    for w in bounds.windows(2) {
+        let (f0, f1) = (f(w[0]), f(w[1]));
+        if f0 == 0.0 {
+            push(w[0]);
+        } else if f0 * f1 < 0.0 {
+            push(bisect(f, w[0], w[1], 0.0, 200).x);
+        }
+    }
+    if f(b) == 0.0 {
+        push(b);
+    }
+    roots
+}
@@ -0,0 +1,169 @@
+//! Smooth pseudo-random perturbations of trajectories.
+
+use crate::quaternion::Vec3;
+use crate::{PiecewiseCubicCurve, Spline, SplineWithVelocity};
+
+/// Upper limit for the number of random offsets, see [`WobbleAdapter::adapt()`].
+pub const MAX_SEGMENTS: usize = 1 << 20;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("frequency must be a positive number (not {frequency:?})")]
+    InvalidFrequency { frequency: f32 },
+    #[error("amplitude must be finite")]
+    InvalidAmplitude,
+    #[error("too many segments ({segments:?}), at most {MAX_SEGMENTS} are allowed")]
+    TooManySegments { segments: f32 },
+}
+
+/// <https://prng.di.unimi.it/splitmix64.c>
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in the range from -1 to 1.
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
+
+/// Adds band-limited noise to a position spline.
+///
+/// Random offsets (within `amplitude` in each dimension) are generated
+/// `frequency` times per second and interpolated with a Catmull-Rom spline.
+/// The same `seed` always leads to the same perturbation.
+pub struct WobbleAdapter<Inner> {
+    inner: Inner,
+    noise: PiecewiseCubicCurve<Vec3>,
+}
+
+impl<Inner> WobbleAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    /// The duration of the inner spline times `frequency`
+    /// must not exceed [`MAX_SEGMENTS`].
+    pub fn adapt(
+        inner: Inner,
+        amplitude: Vec3,
+        frequency: f32,
+        seed: u64,
+    ) -> Result<WobbleAdapter<Inner>, Error> {
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(frequency > 0.0) || frequency.is_infinite() {
+            return Err(Error::InvalidFrequency { frequency });
+        }
+        if !amplitude.iter().all(|a| a.is_finite()) {
+            return Err(Error::InvalidAmplitude);
+        }
+        let start = *inner.grid().first().unwrap();
+        let end = *inner.grid().last().unwrap();
+        let segments = ((end - start) * frequency).ceil();
+        #[allow(clippy::neg_cmp_op_on_partial_ord)]
+        if !(segments <= MAX_SEGMENTS as f32) {
+            return Err(Error::TooManySegments { segments });
+        }
+        let segments = (segments as usize).max(1);
+        let period = 1.0 / frequency;
+        let mut rng = SplitMix64(seed);
+        // One additional value on each side for the tangents
+        let values: Vec<_> = (0..segments + 3)
+            .map(|_| {
+                let random = Vec3::new(rng.next_f32(), rng.next_f32(), rng.next_f32());
+                random.component_mul(&amplitude)
+            })
+            .collect();
+        let grid: Vec<_> = (0..=segments).map(|i| start + i as f32 * period).collect();
+        let tangents: Vec<_> = (0..segments)
+            .flat_map(|i| [i + 1, i + 2])
+            .map(|i| (values[i + 1] - values[i - 1]) / (2.0 * period))
+            .collect();
+        let noise = PiecewiseCubicCurve::new_hermite(&values[1..segments + 2], &tangents, &grid)
+            .map_err(|_| Error::InvalidFrequency { frequency })?;
+        Ok(WobbleAdapter { inner, noise })
+    }
+
+    /// The perturbation that's added to the inner spline.
+    #[must_use]
+    pub fn noise(&self) -> &PiecewiseCubicCurve<Vec3> {
+        &self.noise
+    }
+
+    #[must_use]
+    pub fn inner_ref(&self) -> &Inner {
+        &self.inner
+    }
+}
+
+impl<Inner> Spline<Vec3> for WobbleAdapter<Inner>
+where
+    Inner: Spline<Vec3>,
+{
+    fn evaluate(&self, t: f32) -> Vec3 {
+        self.inner.evaluate(t) + self.noise.evaluate(t)
+    }
+
+    fn grid(&self) -> &[f32] {
+        self.inner.grid()
+    }
+}
+
+impl<Inner> SplineWithVelocity<Vec3, Vec3> for WobbleAdapter<Inner>
+where
+    Inner: SplineWithVelocity<Vec3, Vec3>,
+{
+    fn evaluate_velocity(&self, t: f32) -> Vec3 {
+        self.inner.evaluate_velocity(t) + self.noise.evaluate_velocity(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn still() -> PiecewiseCubicCurve<Vec3> {
+        let zero = Vec3::zeros();
+        PiecewiseCubicCurve::new([[Vec3::new(1.0, 2.0, 3.0), zero, zero, zero]], [0.0, 10.0])
+            .unwrap()
+    }
+
+    #[test]
+    fn deterministic() {
+        let amplitude = Vec3::new(0.5, 0.5, 0.0);
+        let a = WobbleAdapter::adapt(still(), amplitude, 2.0, 42).unwrap();
+        let b = WobbleAdapter::adapt(still(), amplitude, 2.0, 42).unwrap();
+        let c = WobbleAdapter::adapt(still(), amplitude, 2.0, 43).unwrap();
+        assert_eq!(a.noise().grid().len(), 21);
+        for i in 0..=100 {
+            let t = i as f32 * 0.1;
+            assert_eq!(a.evaluate(t), b.evaluate(t));
+            let offset = a.evaluate(t) - Vec3::new(1.0, 2.0, 3.0);
+            // Catmull-Rom might overshoot a little
+            assert!(offset.x.abs() < 0.75 && offset.y.abs() < 0.75);
+            assert_eq!(offset.z, 0.0);
+        }
+        assert_ne!(a.evaluate(5.25), c.evaluate(5.25));
+        assert!(WobbleAdapter::adapt(still(), amplitude, 0.0, 0).is_err());
+    }
+
+    #[test]
+    fn too_many_segments() {
+        let amplitude = Vec3::new(0.5, 0.5, 0.0);
+        // The inner spline is 10 seconds long
+        let frequency = MAX_SEGMENTS as f32 / 10.0;
+        assert!(WobbleAdapter::adapt(still(), amplitude, frequency / 16.0, 0).is_ok());
+        for frequency in [2.0 * frequency, f32::MAX] {
+            assert!(matches!(
+                WobbleAdapter::adapt(still(), amplitude, frequency, 0),
+                Err(Error::TooManySegments { .. })
+            ));
+        }
+    }
+}
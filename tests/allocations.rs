@@ -0,0 +1,152 @@
+//! Checks that sampling doesn't allocate, see `Spline::sample_range_into()`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use asdfspline::adapters::ConstantSpeedAdapter;
+use asdfspline::asdfposspline::{AsdfPosSpline1, NormF32};
+use asdfspline::quaternion::{angles2quat, UnitQuaternion, Vec3};
+use asdfspline::roombounds::{BoundsAdapter, Clamping, ConvexRegion};
+use asdfspline::utilities::{Quadrature, RootFinder, SolverOptions};
+use asdfspline::{AsdfRotSpline, PiecewiseCubicCurve, Spline};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Only allocations of the current test thread are counted
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn assert_no_allocations<Value: Copy, S: Spline<Value>>(spline: &S, fill: Value) {
+    let mut output = [fill; 64];
+    let first = *spline.grid().first().unwrap();
+    let last = *spline.grid().last().unwrap();
+    let step = (last - first) / (output.len() - 10) as f32;
+    // Some samples are outside of the grid on purpose
+    let count = allocations(|| spline.sample_range_into(first - 5.0 * step, step, &mut output));
+    assert_eq!(count, 0);
+}
+
+fn curve() -> PiecewiseCubicCurve<f32> {
+    PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+        &[0.0, 3.0, 3.5, -1.0, 2.0],
+        &[[0.0, 0.5, 0.0]; 3],
+        false,
+        |x: &f32| x.abs(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn constant_speed() {
+    for quadrature in [
+        Quadrature::GaussLegendre5,
+        Quadrature::GaussLegendre7,
+        Quadrature::GaussLegendre13,
+        Quadrature::GaussLegendre21,
+        Quadrature::AdaptiveGaussKronrod,
+        Quadrature::Romberg,
+    ] {
+        for root_finder in [
+            RootFinder::Bisection,
+            RootFinder::Brent,
+            RootFinder::Itp,
+            RootFinder::Newton,
+        ] {
+            let spline = ConstantSpeedAdapter::<_, _, _, NormF32>::adapt(curve())
+                .with_solver_options(SolverOptions {
+                    root_finder,
+                    quadrature,
+                    ..SolverOptions::default()
+                })
+                .unwrap();
+            assert_no_allocations(&spline, 0.0);
+        }
+    }
+    let spline = ConstantSpeedAdapter::<_, _, _, NormF32>::adapt_with_table(curve(), 8);
+    assert_no_allocations(&spline, 0.0);
+}
+
+#[test]
+fn lazy() {
+    let spline = ConstantSpeedAdapter::<_, _, _, NormF32>::adapt_lazy(curve());
+    spline.prepare(0.0..0.0);
+    assert_no_allocations(&spline, 0.0);
+    let spline = ConstantSpeedAdapter::<_, _, _, NormF32>::adapt_with_lazy_table(curve(), 8);
+    spline.prepare(0.0..f32::INFINITY);
+    assert_no_allocations(&spline, 0.0);
+}
+
+#[test]
+fn asdf_splines() {
+    let positions = [0.0, 3.0, 4.0, -1.0, 2.0];
+    let times = [Some(0.0), None, Some(4.0), None, Some(9.0)];
+    let speeds = [None, None, Some(2.0), None, None];
+    let spline =
+        AsdfPosSpline1::new(positions, times, speeds, [[0.0, 0.0, 0.0]; 3], false).unwrap();
+    assert_no_allocations(&spline, 0.0);
+
+    let quaternions = [
+        angles2quat(0.0, 0.0, 0.0),
+        angles2quat(90.0, 0.0, 0.0),
+        angles2quat(90.0, 45.0, 0.0),
+        angles2quat(0.0, 0.0, 30.0),
+    ];
+    let times = [Some(0.0), None, Some(3.0), Some(5.0)];
+    let spline = AsdfRotSpline::new(quaternions, times, [[0.0, 0.0, 0.0]; 2], false).unwrap();
+    assert_no_allocations(&spline, UnitQuaternion::identity());
+}
+
+#[test]
+fn bounds() {
+    let path = || {
+        PiecewiseCubicCurve::new_centripetal_kochanek_bartels(
+            &[
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(3.0, 1.0, 0.0),
+                Vec3::new(-2.0, 2.0, 1.0),
+                Vec3::new(0.0, -3.0, 0.0),
+            ],
+            &[[0.0, 0.0, 0.0]; 2],
+            false,
+            |v: &Vec3| v.norm(),
+        )
+        .unwrap()
+    };
+    // Wedge with a rounded-off tip (so the projection is not trivial)
+    let region = ConvexRegion::new(vec![
+        (Vec3::new(1.0, 1.0, 0.0), 1.0),
+        (Vec3::new(1.0, -1.0, 0.0), 1.0),
+        (Vec3::new(2.0, 1.0, 0.0), 1.5),
+        (Vec3::new(0.0, 0.0, 1.0), 0.5),
+    ])
+    .unwrap();
+    for clamping in [
+        Clamping::Hard,
+        Clamping::Smooth { margin: 0.2 },
+        Clamping::None,
+    ] {
+        let spline = BoundsAdapter::adapt(path(), region.clone(), clamping).unwrap();
+        assert_no_allocations(&spline, Vec3::zeros());
+    }
+}
@@ -0,0 +1,177 @@
+//! Property-based tests of the constructors with random (but valid) input data.
+
+use asdfspline::quaternion::{angles2quat, Vec3};
+use asdfspline::{
+    AsdfPosSpline, AsdfRotSpline, MonotoneCubicSpline, NormWrapper, Spline, SplineWithVelocity,
+};
+use proptest::prelude::*;
+
+struct NormVec3;
+
+impl NormWrapper<NormVec3> for Vec3 {
+    fn norm(&self) -> f32 {
+        self.norm()
+    }
+}
+
+fn vec3() -> impl Strategy<Value = Vec3> {
+    [-10.0f32..10.0, -10.0f32..10.0, -10.0f32..10.0].prop_map(Vec3::from)
+}
+
+/// Positions where consecutive ones are not too close to each other.
+fn waypoints(max: usize) -> impl Strategy<Value = Vec<Vec3>> {
+    prop::collection::vec(vec3(), 2..=max).prop_filter("repeated position", |positions| {
+        positions.windows(2).all(|w| (w[1] - w[0]).norm() > 0.5)
+    })
+}
+
+/// Strictly ascending times, only the first and last one are guaranteed to be given.
+fn times_with_gaps(count: usize) -> impl Strategy<Value = Vec<Option<f32>>> {
+    (
+        -10.0f32..10.0,
+        prop::collection::vec(0.5f32..5.0, count - 1),
+        prop::collection::vec(any::<bool>(), count),
+    )
+        .prop_map(|(start, steps, given)| {
+            let mut time = start;
+            let mut times = vec![Some(time)];
+            for (i, step) in steps.into_iter().enumerate() {
+                time += step;
+                times.push(if given[i + 1] { Some(time) } else { None });
+            }
+            *times.last_mut().unwrap() = Some(time);
+            times
+        })
+}
+
+fn tcb(count: usize) -> impl Strategy<Value = Vec<[f32; 3]>> {
+    prop::collection::vec([-0.5f32..0.5, -0.5f32..0.5, -0.5f32..0.5], count)
+}
+
+/// Waypoints, times (with gaps) and TCB values of an open spline.
+fn open_spline_data() -> impl Strategy<Value = (Vec<Vec3>, Vec<Option<f32>>, Vec<[f32; 3]>)> {
+    waypoints(8).prop_flat_map(|positions| {
+        let count = positions.len();
+        (Just(positions), times_with_gaps(count), tcb(count - 2))
+    })
+}
+
+fn assert_ascending(grid: &[f32]) {
+    assert!(grid.windows(2).all(|w| w[0] < w[1]), "{grid:?}");
+}
+
+proptest! {
+    #[test]
+    fn position_spline((positions, times, tcb) in open_spline_data()) {
+        let speeds = vec![None; positions.len()];
+        let s = AsdfPosSpline::<Vec3, NormVec3>::new(&positions, &times, speeds, &tcb, false)
+            .unwrap();
+        let grid = s.grid();
+        assert_ascending(grid);
+        for ((&t, given), position) in grid.iter().zip(&times).zip(&positions) {
+            if let Some(given) = given {
+                prop_assert_eq!(t, *given);
+            }
+            prop_assert!((s.evaluate(t) - position).norm() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn constant_speed((positions, times, tcb) in open_spline_data()) {
+        // Only the first and last time are given
+        let last = times.len() - 1;
+        let times: Vec<_> = (0..=last)
+            .map(|i| if i == 0 || i == last { times[i] } else { None })
+            .collect();
+        let speeds = vec![None; positions.len()];
+        let s = AsdfPosSpline::<Vec3, NormVec3>::new(&positions, &times, speeds, &tcb, false)
+            .unwrap();
+        let (start, end) = (s.grid()[0], s.grid()[last]);
+        let speeds: Vec<_> = (1..50)
+            .map(|i| s.evaluate_velocity(start + (end - start) * i as f32 / 50.0).norm())
+            .collect();
+        let mean = speeds.iter().sum::<f32>() / speeds.len() as f32;
+        for speed in speeds {
+            prop_assert!((speed - mean).abs() < 0.02 * mean, "{} vs. {}", speed, mean);
+        }
+    }
+
+    #[test]
+    fn rotation_spline(
+        angles in prop::collection::vec(
+            (-180.0f32..180.0, -80.0f32..80.0, -45.0f32..45.0), 2..6),
+    ) {
+        let quaternions: Vec<_> = angles
+            .iter()
+            .map(|&(azim, elev, roll)| angles2quat(azim, elev, roll))
+            .collect();
+        prop_assume!(quaternions.windows(2).all(|w| {
+            let angle = w[0].angle_to(&w[1]).to_degrees();
+            angle > 1.0 && angle < 170.0
+        }));
+        let mut times = vec![None; quaternions.len()];
+        times[0] = Some(0.0);
+        *times.last_mut().unwrap() = Some(quaternions.len() as f32);
+        let tcb = vec![[0.0; 3]; quaternions.len() - 2];
+        let s = AsdfRotSpline::new(quaternions.clone(), times, tcb, false).unwrap();
+        assert_ascending(s.grid());
+        for (&t, q) in s.grid().iter().zip(&quaternions) {
+            prop_assert!(s.evaluate(t).angle_to(q) < 1e-2);
+        }
+    }
+
+    #[test]
+    fn monotone_time_map(
+        start in -10.0f32..10.0,
+        steps in prop::collection::vec((0.1f32..5.0, 0.0f32..5.0), 1..10),
+    ) {
+        let mut grid = vec![start];
+        let mut values = vec![0.0];
+        for (dt, dv) in steps {
+            grid.push(grid.last().unwrap() + dt);
+            values.push(values.last().unwrap() + dv);
+        }
+        let s = MonotoneCubicSpline::new(values.clone(), grid.clone(), false).unwrap();
+        let s = s.inner_ref();
+        for (&t, &value) in grid.iter().zip(&values) {
+            prop_assert!((s.evaluate(t) - value).abs() < 1e-4);
+        }
+        let end = *grid.last().unwrap();
+        let samples: Vec<_> = (0..=200)
+            .map(|i| s.evaluate(start + (end - start) * i as f32 / 200.0))
+            .collect();
+        prop_assert!(samples.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn reference_arc_lengths((positions, _, tcb) in open_spline_data()) {
+        use asdfspline::adapters::ConstantSpeedAdapter;
+        use asdfspline::reference::ReferenceCurve;
+        use asdfspline::utilities::{Quadrature, SolverOptions};
+        use asdfspline::PiecewiseCubicCurve;
+
+        let curve =
+            PiecewiseCubicCurve::new_centripetal_kochanek_bartels(&positions, &tcb, false, |v| {
+                v.norm()
+            })
+            .unwrap();
+        let reference = ReferenceCurve::new(&curve);
+        let s: ConstantSpeedAdapter<_, _, _, NormVec3> = ConstantSpeedAdapter::adapt(curve)
+            .with_solver_options(SolverOptions {
+                quadrature: Quadrature::AdaptiveGaussKronrod,
+                ..SolverOptions::default()
+            })
+            .unwrap();
+        let total = *reference.arc_lengths().last().unwrap();
+        for (&length, &expected) in s.grid().iter().zip(reference.arc_lengths()) {
+            prop_assert!((f64::from(length) - expected).abs() < 1e-4 * total);
+        }
+        for i in 0..=20 {
+            let length = total * f64::from(i) / 20.0;
+            let expected = reference.evaluate_constant_speed(length);
+            let expected = Vec3::new(expected[0] as f32, expected[1] as f32, expected[2] as f32);
+            prop_assert!((s.evaluate(length as f32) - expected).norm() < 1e-3 * total as f32);
+        }
+    }
+}